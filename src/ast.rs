@@ -13,6 +13,8 @@ pub struct Model {
 /// Top-level items in a MiniZinc model
 #[derive(Debug, Clone, PartialEq)]
 pub enum Item {
+    /// Include directive: `include "globals.mzn";`
+    Include(Include),
     /// Enum definition: `enum Color = {Red, Green, Blue};`
     EnumDef(EnumDef),
     /// Variable or parameter declaration: `int: n = 5;`
@@ -23,6 +25,17 @@ pub enum Item {
     Solve(Solve),
     /// Output item: `output ["x = ", show(x)];`
     Output(Output),
+    /// Predicate definition: `predicate differ(var int: a, var int: b) = a != b;`
+    Predicate(Predicate),
+    /// Function definition: `function var int: double(var int: x) = 2 * x;`
+    Function(Function),
+}
+
+/// Include directive naming another model file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Include {
+    pub path: String,
+    pub span: Span,
 }
 
 /// Enumerated type definition
@@ -33,15 +46,52 @@ pub struct EnumDef {
     pub span: Span,
 }
 
+/// User-defined predicate, inlined at each call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    pub name: String,
+    pub params: Vec<Param>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+/// User-defined function, inlined at each call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub return_type: TypeInst,
+    pub name: String,
+    pub params: Vec<Param>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+/// Formal parameter of a predicate or function: `var int: a`, `array[int] of var int: xs`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub type_inst: TypeInst,
+    pub name: String,
+    pub span: Span,
+}
+
 /// Variable or parameter declaration
 #[derive(Debug, Clone, PartialEq)]
 pub struct VarDecl {
     pub type_inst: TypeInst,
     pub name: String,
+    /// Annotations after the name: `var 1..10: x :: output_var;`
+    pub annotations: Vec<Annotation>,
     pub expr: Option<Expr>,
     pub span: Span,
 }
 
+/// Declaration annotation: `output_var`, `output_array([1..3])`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub name: String,
+    pub args: Vec<Expr>,
+    pub span: Span,
+}
+
 /// Type-inst (type + instantiation)
 #[derive(Debug, Clone, PartialEq)]
 pub enum TypeInst {
@@ -56,6 +106,12 @@ pub enum TypeInst {
         base_type: BaseType,
         domain: Expr,
     },
+    /// Set type: var set of 1..n
+    /// The element type carries the universe the set draws its members from
+    Set {
+        is_var: bool,
+        element_type: Box<TypeInst>,
+    },
     /// Array type: array[1..n] of var int or array[1..n, 1..m] of var int
     /// For multi-dimensional arrays: index_sets contains one entry per dimension
     Array {
@@ -78,6 +134,8 @@ pub enum BaseType {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Constraint {
     pub expr: Expr,
+    /// Annotations after the expression: `constraint alldifferent(x) :: domain;`
+    pub annotations: Vec<Annotation>,
     pub span: Span,
 }
 
@@ -90,12 +148,84 @@ pub enum SearchOption {
     Incomplete,
 }
 
+/// Variable selection strategy of a search annotation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VarSelection {
+    InputOrder,
+    FirstFail,
+    AntiFirstFail,
+    Smallest,
+    Largest,
+    OccurrenceMostConstrained,
+    MaxRegret,
+    DomWDeg,
+    /// Any strategy name not listed above
+    Other(String),
+}
+
+impl VarSelection {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "input_order" => Self::InputOrder,
+            "first_fail" => Self::FirstFail,
+            "anti_first_fail" => Self::AntiFirstFail,
+            "smallest" => Self::Smallest,
+            "largest" => Self::Largest,
+            "occurrence" | "most_constrained" => Self::OccurrenceMostConstrained,
+            "max_regret" => Self::MaxRegret,
+            "dom_w_deg" => Self::DomWDeg,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// Value selection strategy of a search annotation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueSelection {
+    IndomainMin,
+    IndomainMax,
+    IndomainMedian,
+    IndomainMiddle,
+    IndomainRandom,
+    IndomainSplit,
+    IndomainReverseSplit,
+    /// Any strategy name not listed above
+    Other(String),
+}
+
+impl ValueSelection {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "indomain_min" | "indomain" => Self::IndomainMin,
+            "indomain_max" => Self::IndomainMax,
+            "indomain_median" => Self::IndomainMedian,
+            "indomain_middle" => Self::IndomainMiddle,
+            "indomain_random" => Self::IndomainRandom,
+            "indomain_split" => Self::IndomainSplit,
+            "indomain_reverse_split" => Self::IndomainReverseSplit,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// Variable search annotation: `int_search(x, first_fail, indomain_min)`
+///
+/// `seq_search([...])` is flattened into one annotation per inner search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchAnnotation {
+    /// The variables to branch on (usually an array identifier or literal)
+    pub variables: Expr,
+    pub var_select: VarSelection,
+    pub val_select: ValueSelection,
+    pub span: Span,
+}
+
 /// Solve item
 #[derive(Debug, Clone, PartialEq)]
 pub enum Solve {
-    Satisfy { search_option: Option<SearchOption>, span: Span },
-    Minimize { expr: Expr, search_option: Option<SearchOption>, span: Span },
-    Maximize { expr: Expr, search_option: Option<SearchOption>, span: Span },
+    Satisfy { search_option: Option<SearchOption>, search: Vec<SearchAnnotation>, span: Span },
+    Minimize { expr: Expr, search_option: Option<SearchOption>, search: Vec<SearchAnnotation>, span: Span },
+    Maximize { expr: Expr, search_option: Option<SearchOption>, search: Vec<SearchAnnotation>, span: Span },
 }
 
 /// Output item
@@ -171,6 +301,15 @@ pub enum ExprKind {
         else_expr: Option<Box<Expr>>,
     },
     
+    /// Let expression: `let { var int: t = x + y; constraint t > 0 } in t <= 10`
+    ///
+    /// The declared names are only visible in the later items and the body.
+    Let {
+        decls: Vec<VarDecl>,
+        constraints: Vec<Expr>,
+        body: Box<Expr>,
+    },
+    
     /// Array comprehension: `[i*2 | i in 1..n]`
     ArrayComp {
         expr: Box<Expr>,
@@ -233,7 +372,15 @@ pub enum BinOp {
     
     // Set
     In,       // in
+    Subset,   // subset
+    Superset, // superset
+    Union,    // union
+    Intersect, // intersect
+    Diff,     // diff
     Range,    // ..
+    
+    // Array
+    Concat,   // ++
 }
 
 /// Unary operators
@@ -291,7 +438,13 @@ impl fmt::Display for BinOp {
             BinOp::Iff => "<->",
             BinOp::Xor => "xor",
             BinOp::In => "in",
+            BinOp::Subset => "subset",
+            BinOp::Superset => "superset",
+            BinOp::Union => "union",
+            BinOp::Intersect => "intersect",
+            BinOp::Diff => "diff",
             BinOp::Range => "..",
+            BinOp::Concat => "++",
         };
         write!(f, "{}", s)
     }
@@ -11,6 +11,8 @@ pub struct Error {
     pub kind: ErrorKind,
     pub span: Span,
     pub source: Option<String>,
+    /// File the source was read from, when known (see [`Error::filename`])
+    filename: Option<Box<str>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -18,6 +20,7 @@ pub enum ErrorKind {
     // Lexer errors
     UnexpectedChar(char),
     UnterminatedString,
+    UnterminatedComment,
     InvalidNumber(String),
     
     // Parser errors
@@ -78,19 +81,64 @@ pub enum ErrorKind {
     Message(String),
 }
 
+/// Coarse classification of an error, for callers that match on the stage
+/// that failed rather than on the exact [`ErrorKind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// Invalid characters, numbers or strings in the source text
+    Lex,
+    /// Source text that does not form a valid model
+    Parse,
+    /// A well-formed model that could not be translated to Selen
+    Translate,
+    /// A MiniZinc feature this crate does not support yet
+    Unsupported,
+    /// An expression of the wrong type or arity
+    TypeMismatch,
+}
+
 impl Error {
     pub fn new(kind: ErrorKind, span: Span) -> Self {
         Self {
             kind,
             span,
             source: None,
+            filename: None,
         }
     }
     
+    /// Which stage of the pipeline produced this error
+    pub fn category(&self) -> ErrorCategory {
+        match &self.kind {
+            ErrorKind::UnexpectedChar(_)
+            | ErrorKind::UnterminatedString
+            | ErrorKind::UnterminatedComment
+            | ErrorKind::InvalidNumber(_) => ErrorCategory::Lex,
+            ErrorKind::UnexpectedToken { .. }
+            | ErrorKind::UnexpectedEof
+            | ErrorKind::InvalidExpression(_)
+            | ErrorKind::InvalidTypeInst(_) => ErrorCategory::Parse,
+            ErrorKind::UnsupportedFeature { .. } => ErrorCategory::Unsupported,
+            ErrorKind::TypeError { .. } => ErrorCategory::TypeMismatch,
+            _ => ErrorCategory::Translate,
+        }
+    }
+
     pub fn with_source(mut self, source: String) -> Self {
         self.source = Some(source);
         self
     }
+
+    /// Name the file the error's source came from, shown in its message
+    pub fn with_filename(mut self, filename: &str) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// File the error's source came from, when known
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
     
     pub fn unexpected_token(expected: &str, found: &str, span: Span) -> Self {
         Self::new(
@@ -197,30 +245,9 @@ impl Error {
     
     /// Get the line and column of the error in the source
     pub fn location(&self) -> (usize, usize) {
-        if let Some(source) = &self.source {
-            let mut line = 1;
-            let mut col = 1;
-            let pos = if self.span.start >= source.len() {
-                // For EOF errors, point to the last character
-                source.len().saturating_sub(1)
-            } else {
-                self.span.start
-            };
-            
-            for (i, c) in source.chars().enumerate() {
-                if i >= pos {
-                    break;
-                }
-                if c == '\n' {
-                    line += 1;
-                    col = 1;
-                } else {
-                    col += 1;
-                }
-            }
-            (line, col)
-        } else {
-            (0, 0)
+        match &self.source {
+            Some(source) => line_col(source, self.span),
+            None => (0, 0),
         }
     }
     
@@ -247,11 +274,64 @@ impl Error {
     }
 }
 
+/// 1-based line and column of the start of `span` in `source`
+fn line_col(source: &str, span: Span) -> (usize, usize) {
+    let mut line = 1;
+    let mut col = 1;
+    let pos = if span.start >= source.len() {
+        // For EOF errors, point to the last character
+        source.len().saturating_sub(1)
+    } else {
+        span.start
+    };
+    
+    for (i, c) in source.chars().enumerate() {
+        if i >= pos {
+            break;
+        }
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+/// A construct the translator approximated or dropped instead of failing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Warning {
+    pub fn new(message: &str, span: Span) -> Self {
+        Self { message: message.to_string(), span }
+    }
+
+    /// Render the warning with the line and column of its span in `source`
+    pub fn display_in(&self, source: &str) -> String {
+        let (line, col) = line_col(source, self.span);
+        format!("Warning at line {}, column {}: {}", line, col, self.message)
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Warning: {}", self.message)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let (line, col) = self.location();
         
         write!(f, "Error")?;
+        if let Some(filename) = &self.filename {
+            write!(f, " in {}", filename)?;
+        }
         if line > 0 {
             write!(f, " at line {}, column {}", line, col)?;
         }
@@ -264,6 +344,9 @@ impl fmt::Display for Error {
             ErrorKind::UnterminatedString => {
                 write!(f, "Unterminated string literal")
             }
+            ErrorKind::UnterminatedComment => {
+                write!(f, "Unterminated block comment (missing '*/')")
+            }
             ErrorKind::InvalidNumber(s) => {
                 write!(f, "Invalid number: {}", s)
             }
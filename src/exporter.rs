@@ -0,0 +1,429 @@
+//! Export a translated model as a standalone Selen Rust program
+//!
+//! The program is rebuilt from what Selen records about the model: every
+//! variable with its bounds, the constraint expressions Selen holds until
+//! search starts, and the propagators it has already created (through their
+//! metadata). Constraints Selen only records opaquely cannot be reproduced;
+//! they are listed as comments, so the program is a faithful if partial
+//! picture of the translation. It is meant for debugging, not as a
+//! replacement for Zelen.
+
+use crate::error::{Error, Result};
+use crate::translator::TranslatedModel;
+use crate::ast::Span;
+use selen::optimization::constraint_metadata::{
+    ConstraintData, ConstraintType, ConstraintValue, TransformationType, ViewInfo,
+};
+use selen::prelude::VarId;
+use selen::runtime_api::{ComparisonOp, ConstraintKind, ExprBuilder};
+use selen::variables::{Val, Var};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Translate MiniZinc source and write the equivalent Selen program to `out_path`
+///
+/// # Example
+///
+/// ```no_run
+/// zelen::export_selen_program("var 1..3: x; constraint x > 1; solve satisfy;", "model.rs").unwrap();
+/// ```
+pub fn export_selen_program(source: &str, out_path: impl AsRef<Path>) -> Result<()> {
+    let ast = crate::parse(source)?;
+    let model_data = crate::Translator::translate_with_vars(&ast).map_err(|e| e.with_source(source.to_string()))?;
+    let out_path = out_path.as_ref();
+    std::fs::write(out_path, selen_program(&model_data)).map_err(|e| {
+        Error::message(&format!("Cannot write '{}': {}", out_path.display(), e), Span::dummy())
+    })
+}
+
+/// Render a translated model as the source of a Selen program
+pub fn selen_program(model_data: &TranslatedModel) -> String {
+    let names = variable_names(model_data);
+    let mut out = String::new();
+
+    out.push_str("//! Selen program exported by zelen\n\n");
+    out.push_str("use selen::prelude::*;\n\n");
+    out.push_str("fn main() {\n");
+    out.push_str("    let mut model = Model::default();\n\n");
+
+    out.push_str("    // Variables, in Selen declaration order\n");
+    for (index, var) in model_data.model.get_vars().iter().enumerate() {
+        let declaration = match var {
+            Var::VarI(domain) => format!("model.int({}, {})", domain.min(), domain.max()),
+            Var::VarF(domain) => format!("model.float({:?}, {:?})", domain.min, domain.max),
+        };
+        let _ = write!(out, "    let v{} = {};", index, declaration);
+        if let Some(name) = names.get(&index) {
+            let _ = write!(out, " // {}", name);
+        }
+        out.push('\n');
+    }
+
+    out.push_str("\n    // Constraints\n");
+    for kind in &model_data.model.pending_constraint_asts {
+        match pending_code(kind) {
+            Some(code) => {
+                let _ = writeln!(out, "    {};", code);
+            }
+            None => {
+                let _ = writeln!(out, "    // not exported: {:?}", kind);
+            }
+        }
+    }
+    let registry = model_data.model.get_constraint_registry();
+    for id in registry.get_all_constraint_ids() {
+        let Some(metadata) = registry.get_constraint(id) else {
+            continue;
+        };
+        match constraint_code(&metadata.constraint_type, &metadata.data, &metadata.variables) {
+            Some(code) => {
+                let _ = writeln!(out, "    {};", code);
+            }
+            None => {
+                let vars: Vec<String> = metadata.variables.iter().map(|&v| var_ident(v)).collect();
+                let _ = writeln!(
+                    out,
+                    "    // not exported: {:?} over [{}]",
+                    metadata.constraint_type,
+                    vars.join(", ")
+                );
+            }
+        }
+    }
+
+    out.push_str("\n    match model.solve() {\n");
+    out.push_str("        Ok(solution) => {\n");
+    let mut named: Vec<(&usize, &String)> = names.iter().collect();
+    named.sort_by_key(|(_, name)| name.as_str());
+    for (index, name) in named {
+        let _ = writeln!(out, "            println!(\"{} = {{:?}}\", solution[v{}]);", name, index);
+    }
+    out.push_str("        }\n");
+    out.push_str("        Err(e) => println!(\"No solution: {:?}\", e),\n");
+    out.push_str("    }\n");
+    out.push_str("}\n");
+    out
+}
+
+/// MiniZinc names of the Selen variables, keyed by declaration index
+fn variable_names(model_data: &TranslatedModel) -> HashMap<usize, String> {
+    let scalars = [&model_data.int_vars, &model_data.bool_vars, &model_data.float_vars]
+        .into_iter()
+        .flat_map(|vars| vars.values().copied());
+    let arrays = [&model_data.int_var_arrays, &model_data.bool_var_arrays, &model_data.float_var_arrays]
+        .into_iter()
+        .flat_map(|arrays| arrays.values().flatten().copied());
+    scalars
+        .chain(arrays)
+        .filter_map(|var| Some((var_index(var), model_data.name_of(var)?)))
+        .collect()
+}
+
+/// Declaration index of a variable
+///
+/// Selen keeps the index private; its `Debug` output is `VarId(<index>)`.
+fn var_index(var: VarId) -> usize {
+    let debug = format!("{:?}", var);
+    debug
+        .trim_start_matches("VarId(")
+        .trim_end_matches(')')
+        .parse()
+        .unwrap_or(usize::MAX)
+}
+
+fn var_ident(var: VarId) -> String {
+    format!("v{}", var_index(var))
+}
+
+/// Code for a plain variable or constant operand
+fn operand_code(view: &ViewInfo) -> Option<String> {
+    match view {
+        ViewInfo::Variable { var_id } => Some(var_ident(*var_id)),
+        ViewInfo::Constant { value: ConstraintValue::Integer(i) } => Some(i.to_string()),
+        ViewInfo::Constant { value: ConstraintValue::Float(f) } => Some(format!("{:?}", f)),
+        _ => None,
+    }
+}
+
+/// Variables of an n-ary constraint whose operands are all plain variables
+fn nary_vars(data: &ConstraintData) -> Option<Vec<String>> {
+    let ConstraintData::NAry { operands } = data else {
+        return None;
+    };
+    operands
+        .iter()
+        .map(|operand| match operand {
+            ViewInfo::Variable { var_id } => Some(var_ident(*var_id)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn var_list(vars: &[VarId]) -> String {
+    vars.iter().map(|&v| var_ident(v)).collect::<Vec<_>>().join(", ")
+}
+
+/// Code for a Selen constraint expression operand
+fn expr_code(expr: &ExprBuilder) -> String {
+    match expr {
+        ExprBuilder::Var(var) => var_ident(*var),
+        ExprBuilder::Val(Val::ValI(i)) => format!("ExprBuilder::from({})", i),
+        ExprBuilder::Val(Val::ValF(f)) => format!("ExprBuilder::from({:?})", f),
+        ExprBuilder::Add(a, b) => format!("{}.add({})", expr_code(a), expr_code(b)),
+        ExprBuilder::Sub(a, b) => format!("{}.sub({})", expr_code(a), expr_code(b)),
+        ExprBuilder::Mul(a, b) => format!("{}.mul({})", expr_code(a), expr_code(b)),
+        ExprBuilder::Div(a, b) => format!("{}.div({})", expr_code(a), expr_code(b)),
+        ExprBuilder::Modulo(a, b) => format!("{}.modulo({})", expr_code(a), expr_code(b)),
+    }
+}
+
+fn comparison_method(op: &ComparisonOp) -> &'static str {
+    match op {
+        ComparisonOp::Eq => "eq",
+        ComparisonOp::Ne => "ne",
+        ComparisonOp::Lt => "lt",
+        ComparisonOp::Le => "le",
+        ComparisonOp::Gt => "gt",
+        ComparisonOp::Ge => "ge",
+    }
+}
+
+/// `lin_*` call for `coeffs * vars <op> constant`, or `None` for operators Selen
+/// has no linear form of (the int strict comparisons are shifted by one)
+fn linear_code<T: std::fmt::Debug + Copy + std::ops::Neg<Output = T>>(
+    coeffs: &[T],
+    vars: &[VarId],
+    op: &ComparisonOp,
+    constant: T,
+    shift: Option<fn(T) -> T>,
+    reif: Option<VarId>,
+) -> Option<String> {
+    let negated: Vec<T> = coeffs.iter().map(|&c| -c).collect();
+    let (name, coeffs, constant) = match op {
+        ComparisonOp::Eq => ("lin_eq", coeffs.to_vec(), constant),
+        ComparisonOp::Ne => ("lin_ne", coeffs.to_vec(), constant),
+        ComparisonOp::Le => ("lin_le", coeffs.to_vec(), constant),
+        ComparisonOp::Ge => ("lin_le", negated, -constant),
+        ComparisonOp::Lt => ("lin_le", coeffs.to_vec(), shift?(constant)),
+        ComparisonOp::Gt => ("lin_le", negated, shift?(-constant)),
+    };
+    let coeffs: Vec<String> = coeffs.iter().map(|c| format!("{:?}", c)).collect();
+    Some(match reif {
+        Some(b) => format!(
+            "model.{}_reif(&[{}], &[{}], {:?}, {})",
+            name,
+            coeffs.join(", "),
+            var_list(vars),
+            constant,
+            var_ident(b)
+        ),
+        None => format!("model.{}(&[{}], &[{}], {:?})", name, coeffs.join(", "), var_list(vars), constant),
+    })
+}
+
+/// Reproduce a constraint Selen has not yet turned into propagators
+fn pending_code(kind: &ConstraintKind) -> Option<String> {
+    match kind {
+        ConstraintKind::Binary { left, op, right } => {
+            Some(format!("model.new({}.{}({}))", expr_code(left), comparison_method(op), expr_code(right)))
+        }
+        ConstraintKind::ReifiedBinary {
+            left: ExprBuilder::Var(x),
+            op,
+            right: ExprBuilder::Var(y),
+            reif_var,
+        } => Some(format!(
+            "model.{}_reif({}, {}, {})",
+            comparison_method(op),
+            var_ident(*x),
+            var_ident(*y),
+            var_ident(*reif_var)
+        )),
+        ConstraintKind::LinearInt { coeffs, vars, op, constant } => {
+            linear_code(coeffs, vars, op, *constant, Some(|c| c - 1), None)
+        }
+        ConstraintKind::LinearFloat { coeffs, vars, op, constant } => {
+            linear_code(coeffs, vars, op, *constant, None, None)
+        }
+        ConstraintKind::ReifiedLinearInt { coeffs, vars, op, constant, reif_var }
+            if matches!(op, ComparisonOp::Eq | ComparisonOp::Ne | ComparisonOp::Le) =>
+        {
+            linear_code(coeffs, vars, op, *constant, None, Some(*reif_var))
+        }
+        ConstraintKind::ReifiedLinearFloat { coeffs, vars, op, constant, reif_var }
+            if matches!(op, ComparisonOp::Eq | ComparisonOp::Ne | ComparisonOp::Le) =>
+        {
+            linear_code(coeffs, vars, op, *constant, None, Some(*reif_var))
+        }
+        ConstraintKind::BoolAnd { x, y, z } => Some(bind(format!("model.bool_and(&[{}])", var_list(&[*x, *y])), *z)),
+        ConstraintKind::BoolOr { x, y, z } => Some(bind(format!("model.bool_or(&[{}])", var_list(&[*x, *y])), *z)),
+        ConstraintKind::BoolNot { x, y } => Some(bind(format!("model.bool_not({})", var_ident(*x)), *y)),
+        ConstraintKind::BoolXor { x, y, z } => {
+            Some(bind(format!("model.bool_xor({}, {})", var_ident(*x), var_ident(*y)), *z))
+        }
+        ConstraintKind::BoolImplies { x, y } => {
+            Some(format!("model.bool_clause(&[{}], &[{}])", var_ident(*y), var_ident(*x)))
+        }
+        ConstraintKind::AllDifferent { vars } => Some(format!("model.alldiff(&[{}])", var_list(vars))),
+        ConstraintKind::AllEqual { vars } => Some(format!("model.alleq(&[{}])", var_list(vars))),
+        ConstraintKind::Minimum { vars, result } => {
+            Some(bind(format!("model.min(&[{}]).unwrap()", var_list(vars)), *result))
+        }
+        ConstraintKind::Maximum { vars, result } => {
+            Some(bind(format!("model.max(&[{}]).unwrap()", var_list(vars)), *result))
+        }
+        ConstraintKind::Sum { vars, result } => Some(bind(format!("model.sum(&[{}])", var_list(vars)), *result)),
+        ConstraintKind::Element { index, array, value } => Some(format!(
+            "model.element(&[{}], {}, {})",
+            var_list(array),
+            var_ident(*index),
+            var_ident(*value)
+        )),
+        ConstraintKind::Table { vars, tuples } => {
+            let tuples: Vec<String> = tuples
+                .iter()
+                .map(|t| format!("vec![{}]", t.iter().map(|v| format!("int({})", v)).collect::<Vec<_>>().join(", ")))
+                .collect();
+            Some(format!("model.table(&[{}], vec![{}])", var_list(vars), tuples.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// Binds the result of a Selen function that creates its own result variable
+fn bind(call: String, result: VarId) -> String {
+    format!("{{ let r = {}; model.new(r.eq({})) }}", call, var_ident(result))
+}
+
+/// Reproduce a constraint from its metadata, if Selen recorded enough of it
+///
+/// `variables` lists the constrained variables; reified and boolean
+/// constraints record their result variable last.
+fn constraint_code(constraint_type: &ConstraintType, data: &ConstraintData, variables: &[VarId]) -> Option<String> {
+    match constraint_type {
+        ConstraintType::EqualityReified
+        | ConstraintType::InequalityReified
+        | ConstraintType::LessThanReified
+        | ConstraintType::LessEqualReified
+        | ConstraintType::GreaterThanReified
+        | ConstraintType::GreaterEqualReified => {
+            // Linear reifications record no coefficients and cannot be rebuilt
+            let ConstraintData::Binary { left: ViewInfo::Variable { var_id: x }, right: ViewInfo::Variable { var_id: y } } = data
+            else {
+                return None;
+            };
+            let [_, _, b] = variables else {
+                return None;
+            };
+            let method = match constraint_type {
+                ConstraintType::EqualityReified => "eq_reif",
+                ConstraintType::InequalityReified => "ne_reif",
+                ConstraintType::LessThanReified => "lt_reif",
+                ConstraintType::LessEqualReified => "le_reif",
+                ConstraintType::GreaterThanReified => "gt_reif",
+                _ => "ge_reif",
+            };
+            Some(format!("model.{}({}, {}, {})", method, var_ident(*x), var_ident(*y), var_ident(*b)))
+        }
+        ConstraintType::BooleanAnd | ConstraintType::BooleanOr => {
+            let operands = nary_vars(data)?;
+            let function = if *constraint_type == ConstraintType::BooleanAnd { "bool_and" } else { "bool_or" };
+            Some(bind(format!("model.{}(&[{}])", function, operands.join(", ")), *variables.last()?))
+        }
+        ConstraintType::BooleanNot => {
+            let [operand, result] = variables else {
+                return None;
+            };
+            Some(bind(format!("model.bool_not({})", var_ident(*operand)), *result))
+        }
+        ConstraintType::Equals
+        | ConstraintType::NotEquals
+        | ConstraintType::LessThan
+        | ConstraintType::LessThanOrEquals
+        | ConstraintType::GreaterThan
+        | ConstraintType::GreaterThanOrEquals => {
+            let ConstraintData::Binary { left, right } = data else {
+                return None;
+            };
+            // Strict comparisons are recorded as `x.next() <= y` / `x.prev() >= y`
+            let unwrap_step = |view: &ViewInfo| match view {
+                ViewInfo::Transformed {
+                    base_var,
+                    transformation: TransformationType::Next | TransformationType::Previous,
+                } if matches!(constraint_type, ConstraintType::LessThan | ConstraintType::GreaterThan) => {
+                    ViewInfo::Variable { var_id: *base_var }
+                }
+                other => other.clone(),
+            };
+            let (left, right) = (unwrap_step(left), unwrap_step(right));
+            let method = match constraint_type {
+                ConstraintType::Equals => "eq",
+                ConstraintType::NotEquals => "ne",
+                ConstraintType::LessThan => "lt",
+                ConstraintType::LessThanOrEquals => "le",
+                ConstraintType::GreaterThan => "gt",
+                _ => "ge",
+            };
+            // Constants can only appear on the right of the comparison
+            let (left, right, method) = match (&left, &right) {
+                (ViewInfo::Constant { .. }, ViewInfo::Variable { .. }) => {
+                    let flipped = match method {
+                        "lt" => "gt",
+                        "le" => "ge",
+                        "gt" => "lt",
+                        "ge" => "le",
+                        same => same,
+                    };
+                    (right, left, flipped)
+                }
+                _ => (left, right, method),
+            };
+            if !matches!(left, ViewInfo::Variable { .. }) {
+                return None;
+            }
+            Some(format!("model.new({}.{}({}))", operand_code(&left)?, method, operand_code(&right)?))
+        }
+        ConstraintType::AllDifferent => Some(format!("model.alldiff(&[{}])", nary_vars(data)?.join(", "))),
+        ConstraintType::AllEqual => Some(format!("model.alleq(&[{}])", nary_vars(data)?.join(", "))),
+        ConstraintType::Element => {
+            let mut vars = nary_vars(data)?;
+            let value = vars.pop()?;
+            let index = vars.pop()?;
+            Some(format!("model.element(&[{}], {}, {})", vars.join(", "), index, value))
+        }
+        ConstraintType::Sum | ConstraintType::Addition => {
+            // Operands followed by the result: x1 + ... + xn - s = 0
+            let vars = nary_vars(data)?;
+            let coeffs: Vec<&str> = (0..vars.len()).map(|i| if i + 1 == vars.len() { "-1" } else { "1" }).collect();
+            Some(format!("model.lin_eq(&[{}], &[{}], 0)", coeffs.join(", "), vars.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_simple_model() {
+        let source = r#"
+            array[1..3] of var 1..3: q;
+            var 0..10: t;
+            constraint alldifferent(q);
+            constraint q[1] < q[2];
+            constraint t = sum(q);
+            solve satisfy;
+        "#;
+        let path = std::env::temp_dir().join(format!("zelen_export_{}.rs", std::process::id()));
+        export_selen_program(source, &path).unwrap();
+        let program = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(program.contains("let v0 = model.int(1, 3); // q[1]"), "{}", program);
+        assert!(program.contains("model.int(0, 10); // t"), "{}", program);
+        assert!(program.contains("model.alldiff(&[v0, v1, v2])"), "{}", program);
+        assert!(!program.contains("not exported"), "{}", program);
+    }
+}
@@ -19,20 +19,36 @@ pub enum TokenKind {
     Constraint,
     Enum,
     Float,
+    Function,
+    Include,
     Int,
     Maximize,
     Minimize,
     Of,
     Output,
     Par,
+    Predicate,
     Satisfy,
+    Set,
     Solve,
     Var,
     Where,
     In,
+    Subset,
+    Superset,
+    Union,
+    Intersect,
+    Diff,
+    If,
+    Then,
+    Elseif,
+    Else,
+    Endif,
+    Let,
     
     // Operators
     Plus,         // +
+    PlusPlus,     // ++
     Minus,        // -
     Star,         // *
     Slash,        // /
@@ -74,12 +90,23 @@ pub enum TokenKind {
     IntLit(i64),
     FloatLit(f64),
     StringLit(String),
+    /// String literal containing `\(expr)` interpolations
+    InterpolatedString(Vec<StringPart>),
     BoolLit(bool),
     
     // Special
     Eof,
 }
 
+/// Segment of an interpolated string literal
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    /// Literal text with escapes already processed
+    Text(String),
+    /// `\(expr)`: source position of the first character of `expr`
+    Expr(usize),
+}
+
 #[derive(Clone)]
 pub struct Lexer {
     source: Vec<char>,
@@ -99,7 +126,7 @@ impl Lexer {
     }
     
     pub fn next_token(&mut self) -> Result<Token> {
-        self.skip_whitespace_and_comments();
+        self.skip_whitespace_and_comments()?;
         
         let start = self.pos;
         
@@ -131,7 +158,12 @@ impl Lexer {
         let kind = match ch {
             '+' => {
                 self.advance();
-                TokenKind::Plus
+                if self.current_char == Some('+') {
+                    self.advance();
+                    TokenKind::PlusPlus
+                } else {
+                    TokenKind::Plus
+                }
             }
             '-' => {
                 self.advance();
@@ -276,7 +308,7 @@ impl Lexer {
         self.current_char = self.source.get(self.pos).copied();
     }
     
-    fn skip_whitespace_and_comments(&mut self) {
+    fn skip_whitespace_and_comments(&mut self) -> Result<()> {
         while let Some(ch) = self.current_char {
             if ch.is_whitespace() {
                 self.advance();
@@ -285,10 +317,47 @@ impl Lexer {
                 while self.current_char.is_some() && self.current_char != Some('\n') {
                     self.advance();
                 }
+            } else if ch == '/' && self.peek() == Some('*') {
+                self.skip_block_comment()?;
             } else {
                 break;
             }
         }
+        Ok(())
+    }
+
+    /// Skip a `/* ... */` comment, including any nested block comments
+    fn skip_block_comment(&mut self) -> Result<()> {
+        let start = self.pos;
+        let mut depth = 0usize;
+        while let Some(ch) = self.current_char {
+            if ch == '/' && self.peek() == Some('*') {
+                depth += 1;
+                self.advance();
+            } else if ch == '*' && self.peek() == Some('/') {
+                depth -= 1;
+                self.advance();
+                if depth == 0 {
+                    self.advance();
+                    return Ok(());
+                }
+            }
+            self.advance();
+        }
+        Err(Error::new(ErrorKind::UnterminatedComment, Span::new(start, self.pos)))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source.get(self.pos + 1).copied()
+    }
+
+    /// A lexer over the same source, positioned at character `pos`
+    pub fn at(&self, pos: usize) -> Self {
+        Self {
+            source: self.source.clone(),
+            pos,
+            current_char: self.source.get(pos).copied(),
+        }
     }
     
     fn lex_number(&mut self, start: usize) -> Result<Token> {
@@ -361,12 +430,21 @@ impl Lexer {
             "array" => TokenKind::Array,
             "bool" => TokenKind::Bool,
             "constraint" => TokenKind::Constraint,
+            "diff" => TokenKind::Diff,
             "div" => TokenKind::Div,
+            "else" => TokenKind::Else,
+            "elseif" => TokenKind::Elseif,
+            "endif" => TokenKind::Endif,
             "enum" => TokenKind::Enum,
             "false" => TokenKind::BoolLit(false),
             "float" => TokenKind::Float,
+            "function" => TokenKind::Function,
+            "if" => TokenKind::If,
             "in" => TokenKind::In,
+            "include" => TokenKind::Include,
             "int" => TokenKind::Int,
+            "intersect" => TokenKind::Intersect,
+            "let" => TokenKind::Let,
             "maximize" => TokenKind::Maximize,
             "minimize" => TokenKind::Minimize,
             "mod" => TokenKind::Mod,
@@ -374,9 +452,15 @@ impl Lexer {
             "of" => TokenKind::Of,
             "output" => TokenKind::Output,
             "par" => TokenKind::Par,
+            "predicate" => TokenKind::Predicate,
             "satisfy" => TokenKind::Satisfy,
+            "set" => TokenKind::Set,
             "solve" => TokenKind::Solve,
+            "subset" => TokenKind::Subset,
+            "superset" => TokenKind::Superset,
+            "then" => TokenKind::Then,
             "true" => TokenKind::BoolLit(true),
+            "union" => TokenKind::Union,
             "var" => TokenKind::Var,
             "where" => TokenKind::Where,
             "xor" => TokenKind::Xor,
@@ -392,14 +476,44 @@ impl Lexer {
     fn lex_string(&mut self, start: usize) -> Result<Token> {
         self.advance(); // Skip opening "
         let mut s = String::new();
+        let mut parts = Vec::new();
         
         while let Some(ch) = self.current_char {
             if ch == '"' {
                 self.advance();
+                let kind = if parts.is_empty() {
+                    TokenKind::StringLit(s)
+                } else {
+                    if !s.is_empty() {
+                        parts.push(StringPart::Text(s));
+                    }
+                    TokenKind::InterpolatedString(parts)
+                };
                 return Ok(Token {
-                    kind: TokenKind::StringLit(s),
+                    kind,
                     span: Span::new(start, self.pos),
                 });
+            } else if ch == '\\' && self.peek() == Some('(') {
+                // Interpolation: remember where the expression starts and skip to
+                // its closing parenthesis; the parser reads the expression itself
+                if !s.is_empty() {
+                    parts.push(StringPart::Text(std::mem::take(&mut s)));
+                }
+                self.advance();
+                self.advance();
+                parts.push(StringPart::Expr(self.pos));
+                let mut depth = 1;
+                while depth > 0 {
+                    match self.current_char {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some('"') | None => {
+                            return Err(Error::new(ErrorKind::UnterminatedString, Span::new(start, self.pos)));
+                        }
+                        _ => {}
+                    }
+                    self.advance();
+                }
             } else if ch == '\\' {
                 self.advance();
                 if let Some(escaped) = self.current_char {
@@ -522,4 +636,43 @@ mod tests {
         let tokens = lex_all("int % this is a comment\nvar").unwrap();
         assert_eq!(tokens, vec![TokenKind::Int, TokenKind::Var]);
     }
+
+    #[test]
+    fn test_block_comments() {
+        let tokens = lex_all("var /* domain\n follows */ int /* a /* b */ c */ : x; /**/").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                TokenKind::Var,
+                TokenKind::Int,
+                TokenKind::Colon,
+                TokenKind::Ident("x".to_string()),
+                TokenKind::Semicolon,
+            ]
+        );
+
+        // Division and conjunction still lex next to a comment
+        let tokens = lex_all("a / b /\\ c/*x*/").unwrap();
+        assert_eq!(tokens.len(), 5);
+
+        let err = lex_all("int: x; /* outer /* inner */ still open").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::UnterminatedComment));
+        assert_eq!(err.span.start, 8);
+        assert!(format!("{}", err).contains("Unterminated block comment"));
+    }
+
+    #[test]
+    fn test_string_interpolation_parts() {
+        let tokens = lex_all(r#""x = \(x[i + 1])\n""#).unwrap();
+        assert_eq!(
+            tokens,
+            vec![TokenKind::InterpolatedString(vec![
+                StringPart::Text("x = ".to_string()),
+                StringPart::Expr(7),
+                StringPart::Text("\n".to_string()),
+            ])]
+        );
+
+        assert!(lex_all(r#""open \(x""#).is_err());
+    }
 }
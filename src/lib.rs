@@ -59,21 +59,26 @@
 
 pub mod ast;
 pub mod error;
+pub mod exporter;
 pub mod lexer;
 pub mod parser;
 pub mod translator;
 
 pub use ast::*;
-pub use error::{Error, Result};
+pub use error::{Error, ErrorCategory, Result, Warning};
+pub use exporter::export_selen_program;
 pub use lexer::Lexer;
 pub use parser::Parser;
-pub use translator::{Translator, TranslatedModel, ObjectiveType};
+pub use translator::{Translator, TranslatedModel, TranslatorOptions, ObjectiveType, SearchHeuristic};
 
 // Re-export commonly used Selen types for convenience
 pub use selen;
 // Re-export specific selen types to avoid conflicts
 pub use selen::prelude::{Model, Solution, VarId};
 
+use std::ops::ControlFlow;
+use std::path::Path;
+
 /// Configuration for the Selen solver backend
 ///
 /// Allows customizing solver behavior like timeout, memory limits, and solution enumeration.
@@ -172,6 +177,24 @@ pub fn parse(source: &str) -> Result<ast::Model> {
     parser.parse_model()
 }
 
+/// Parse the MiniZinc model in a file
+///
+/// Like [`parse`], but errors name the file they come from.
+///
+/// # Example
+///
+/// ```no_run
+/// let ast = zelen::parse_file("model.mzn");
+/// ```
+pub fn parse_file(path: impl AsRef<Path>) -> Result<ast::Model> {
+    let path = path.as_ref();
+    let filename = path.display().to_string();
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        Error::message(&format!("Cannot read '{}': {}", filename, e), ast::Span::dummy())
+    })?;
+    parse(&source).map_err(|e| e.with_filename(&filename))
+}
+
 /// Translate a MiniZinc AST to a Selen model
 ///
 /// # Arguments
@@ -217,7 +240,7 @@ pub fn translate(ast: &ast::Model) -> Result<selen::prelude::Model> {
 /// ```
 pub fn build_model(source: &str) -> Result<selen::prelude::Model> {
     let ast = parse(source)?;
-    translate(&ast)
+    translate(&ast).map_err(|e| e.with_source(source.to_string()))
 }
 
 /// Parse and translate MiniZinc source directly to a Selen model with custom configuration
@@ -247,6 +270,29 @@ pub fn build_model_with_config(source: &str, config: SolverConfig) -> Result<sel
     let ast = parse(source)?;
     let selen_config = config.to_selen_config();
     Translator::translate_with_config(&ast, selen_config)
+        .map_err(|e| e.with_source(source.to_string()))
+}
+
+/// Translate a parsed model with a solver configuration and translation options
+///
+/// The returned model carries `config`'s limits and the variable mappings
+/// needed to format its solutions, so a caller that already holds the AST
+/// does not have to parse the source again to get a configured model.
+///
+/// # Example
+///
+/// ```
+/// let ast = zelen::parse("var 1..10: x; solve satisfy;").unwrap();
+/// let config = zelen::SolverConfig::default().with_time_limit_ms(5000);
+/// let model_data = zelen::translate_with_vars_and_config(&ast, &config, zelen::TranslatorOptions::default());
+/// assert!(model_data.is_ok());
+/// ```
+pub fn translate_with_vars_and_config(
+    ast: &ast::Model,
+    config: &SolverConfig,
+    options: TranslatorOptions,
+) -> Result<TranslatedModel> {
+    Translator::translate_with_vars_config_and_options(ast, config.to_selen_config(), options)
 }
 
 /// Solve a MiniZinc model with custom solver configuration and return solutions
@@ -293,6 +339,48 @@ pub fn solve_with_config(
     }
 }
 
+/// Enumerate the solutions of a MiniZinc model, passing each one to a callback
+///
+/// Solutions are handed to `on_solution` as the solver finds them, without being
+/// collected, together with the translated model for looking up variables by
+/// name or formatting the solution. Returning [`ControlFlow::Break`] stops the
+/// enumeration; it also ends after `max_solutions` solutions or when the time
+/// limit is reached. The `all_solutions` flag is ignored, since the callback
+/// decides when to stop.
+///
+/// Returns the number of solutions passed to the callback.
+///
+/// # Example
+///
+/// ```
+/// use std::ops::ControlFlow;
+///
+/// let seen = zelen::solve_each("var 1..10: x; solve satisfy;", zelen::SolverConfig::default(), |model_data, solution| {
+///     assert!(solution.get_int(model_data.int_vars["x"]) >= 1);
+///     ControlFlow::Break(())
+/// }).unwrap();
+/// assert_eq!(seen, 1);
+/// ```
+pub fn solve_each(
+    source: &str,
+    config: SolverConfig,
+    mut on_solution: impl FnMut(&TranslatedModel, &selen::core::Solution) -> ControlFlow<()>,
+) -> Result<usize> {
+    let max = config.max_solutions.unwrap_or(usize::MAX);
+    let ast = parse(source)?;
+    let mut model_data = Translator::translate_with_vars_and_config(&ast, config.to_selen_config())
+        .map_err(|e| e.with_source(source.to_string()))?;
+    let model = std::mem::take(&mut model_data.model);
+    let mut seen = 0;
+    for solution in model.enumerate().take(max) {
+        seen += 1;
+        if on_solution(&model_data, &solution).is_break() {
+            break;
+        }
+    }
+    Ok(seen)
+}
+
 /// Solve a MiniZinc model and return the solution
 ///
 /// This is a convenience function that combines parse, translate, and solve.
@@ -321,6 +409,419 @@ pub fn solve(source: &str) -> Result<std::result::Result<selen::core::Solution,
     Ok(model.solve())
 }
 
+/// Final status of a [`solve_report`] run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveStatus {
+    /// A solution was found for a satisfaction problem
+    Satisfied,
+    /// An optimal solution was found for a minimize/maximize problem
+    Optimal,
+    /// The model has no solution
+    Unsatisfiable,
+    /// The objective can be improved without limit in the optimizing direction
+    Unbounded,
+    /// The solver hit its time limit before it could answer
+    TimedOut,
+    /// The solver stopped (e.g. on a memory limit) without an answer
+    Unknown,
+}
+
+/// Outcome of solving a model: the status and the solutions found
+///
+/// For [`SolveStatus::Unbounded`] the solution is a witness with the objective at
+/// the solver's default bound, not an optimum.
+#[derive(Debug)]
+pub struct SolveReport {
+    pub status: SolveStatus,
+    /// Every solution found when enumerating, otherwise the best one, if any
+    pub solutions: Vec<selen::core::Solution>,
+}
+
+impl SolveReport {
+    /// The best solution found: the last one, or the only one when not enumerating
+    pub fn solution(&self) -> Option<&selen::core::Solution> {
+        self.solutions.last()
+    }
+}
+
+/// Selen clamps unbounded integer domains to `-100_000..=100_000`
+const UNBOUNDED_INT: i32 = 100_000;
+/// Selen's fallback bound for unbounded float domains
+const UNBOUNDED_FLOAT: f64 = 1e9;
+
+/// Solve a MiniZinc model and report how the search ended
+///
+/// Unlike [`solve`], this distinguishes an optimal solution from an unbounded
+/// objective. An integer objective over variables declared without bounds is
+/// first probed at the solver's default bound; if that is reachable, the
+/// model is reported as [`SolveStatus::Unbounded`] instead of stepping towards
+/// the bound one improvement at a time, unless the objective cannot exceed it
+/// once those variables' domains are widened, which makes the bound optimal.
+/// A float objective is unbounded when the optimum the solver returns lies
+/// beyond its fallback bound.
+///
+/// With `all_solutions`, a satisfaction problem's solutions are enumerated (up
+/// to `max_solutions`). Enumeration does not say why it ended, so an empty one
+/// is reported as [`SolveStatus::TimedOut`] once the time limit has passed,
+/// [`SolveStatus::Unknown`] under a memory limit and otherwise
+/// [`SolveStatus::Unsatisfiable`].
+///
+/// # Example
+///
+/// ```
+/// let report = zelen::solve_report(
+///     "var int: x; constraint x >= 3; solve maximize x;",
+///     zelen::SolverConfig::default(),
+/// ).unwrap();
+/// assert_eq!(report.status, zelen::SolveStatus::Unbounded);
+/// ```
+pub fn solve_report(source: &str, config: SolverConfig) -> Result<SolveReport> {
+    use selen::prelude::*;
+
+    let ast = parse(source)?;
+    let selen_config = config.to_selen_config();
+    let translate = || {
+        Translator::translate_with_vars_and_config(&ast, selen_config.clone())
+            .map_err(|e| e.with_source(source.to_string()))
+    };
+    let model_data = translate()?;
+
+    let (maximize, objective) = match (model_data.objective_type, model_data.objective_var) {
+        (ObjectiveType::Minimize, Some(var)) => (false, var),
+        (ObjectiveType::Maximize, Some(var)) => (true, var),
+        _ if config.all_solutions => {
+            let max = config.max_solutions.unwrap_or(usize::MAX);
+            let start = std::time::Instant::now();
+            let solutions: Vec<_> = model_data.model.enumerate().take(max).collect();
+            let status = if !solutions.is_empty() {
+                SolveStatus::Satisfied
+            } else if config.time_limit_ms.is_some_and(|ms| start.elapsed().as_millis() >= u128::from(ms)) {
+                SolveStatus::TimedOut
+            } else if config.memory_limit_mb.is_some() {
+                SolveStatus::Unknown
+            } else {
+                SolveStatus::Unsatisfiable
+            };
+            return Ok(SolveReport { status, solutions });
+        }
+        _ => {
+            return Ok(match model_data.model.solve() {
+                Ok(solution) => SolveReport { status: SolveStatus::Satisfied, solutions: vec![solution] },
+                Err(e) => SolveReport { status: failure_status(&e), solutions: Vec::new() },
+            });
+        }
+    };
+
+    if int_objective_is_open(&model_data, objective, maximize) {
+        let mut probe = translate()?;
+        let bound = if maximize { UNBOUNDED_INT } else { -UNBOUNDED_INT };
+        let bound_var = probe.model.int(bound, bound);
+        probe.model.new(objective.eq(bound_var));
+        if let Ok(witness) = probe.model.solve() {
+            // Reaching the bound is not enough, as a constraint may stop the
+            // objective right there: widen the open domains and look past it
+            let widened = (-UNBOUNDED_INT - 1, UNBOUNDED_INT + 1);
+            let mut beyond = Translator::translate_with_open_int_domain(&ast, selen_config.clone(), widened)
+                .map_err(|e| e.with_source(source.to_string()))?;
+            if let Some(objective) = beyond.objective_var {
+                if maximize {
+                    beyond.model.new(objective.gt(bound));
+                } else {
+                    beyond.model.new(objective.lt(bound));
+                }
+            }
+            let status = match beyond.model.solve() {
+                Ok(_) => SolveStatus::Unbounded,
+                Err(selen::core::SolverError::NoSolution { .. }) => SolveStatus::Optimal,
+                Err(_) => SolveStatus::Unknown,
+            };
+            return Ok(SolveReport { status, solutions: vec![witness] });
+        }
+    }
+
+    let result = optimize_translated(model_data.model, objective, maximize, translate, None)?;
+    Ok(match result {
+        Ok(solution) => {
+            let status = match solution[objective] {
+                Val::ValF(value) if maximize && value >= UNBOUNDED_FLOAT => SolveStatus::Unbounded,
+                Val::ValF(value) if !maximize && value <= -UNBOUNDED_FLOAT => SolveStatus::Unbounded,
+                _ => SolveStatus::Optimal,
+            };
+            SolveReport { status, solutions: vec![solution] }
+        }
+        Err(e) => SolveReport { status: failure_status(&e), solutions: Vec::new() },
+    })
+}
+
+/// Solve a parsed model, optimizing its objective if it has one
+///
+/// Satisfaction problems return the first solution found. Integer objectives
+/// use Selen's optimizer; float objectives are optimized by bisection on the
+/// objective value (see [`optimize_translated`]), since Selen's float optimizer
+/// can return the bare domain bound without regard to the constraints.
+///
+/// # Example
+///
+/// ```
+/// let ast = zelen::parse(
+///     "var 0.0..10.0: x; constraint x >= 2.5; solve minimize x;",
+/// ).unwrap();
+/// let solution = zelen::optimize(&ast, zelen::SolverConfig::default()).unwrap().unwrap();
+/// # let _ = solution;
+/// ```
+pub fn optimize(
+    ast: &ast::Model,
+    config: SolverConfig,
+) -> Result<std::result::Result<selen::core::Solution, selen::core::SolverError>> {
+    let selen_config = config.to_selen_config();
+    let translate = || Translator::translate_with_vars_and_config(ast, selen_config.clone());
+    optimize_model(&mut translate()?, translate)
+}
+
+/// Like [`optimize`], but reports every improving solution as it is found
+///
+/// `on_incumbent` is called once per improving solution, in the order they are
+/// found; the last call carries the solution that is returned. Satisfaction
+/// problems report their single solution.
+///
+/// # Example
+///
+/// ```
+/// let ast = zelen::parse(
+///     "var 1..5: x; var 1..5: y; constraint x + y <= 7; solve maximize 2 * x + y;",
+/// ).unwrap();
+/// let mut found = 0;
+/// let best = zelen::optimize_with_intermediate(&ast, zelen::SolverConfig::default(), |_| found += 1)
+///     .unwrap()
+///     .unwrap();
+/// assert!(found >= 1);
+/// # let _ = best;
+/// ```
+pub fn optimize_with_intermediate(
+    ast: &ast::Model,
+    config: SolverConfig,
+    mut on_incumbent: impl FnMut(&selen::core::Solution),
+) -> Result<std::result::Result<selen::core::Solution, selen::core::SolverError>> {
+    let selen_config = config.to_selen_config();
+    let translate = || Translator::translate_with_vars_and_config(ast, selen_config.clone());
+    optimize_model_with_intermediate(&mut translate()?, translate, |_, solution| on_incumbent(solution))
+}
+
+/// Optimize a model that is already translated
+///
+/// The Selen model is taken out of `model_data`, which keeps the variable maps
+/// for formatting the solutions. Only float objectives need more than this one
+/// model: each bisection step (see [`optimize`]) solves a fresh one from
+/// `retranslate`, which should translate with the same configuration and
+/// options. Satisfaction problems are solved once.
+///
+/// # Example
+///
+/// ```
+/// let ast = zelen::parse("var 1..5: x; var 1..5: y; constraint x < y; solve maximize x;").unwrap();
+/// let config = zelen::SolverConfig::default();
+/// let options = zelen::TranslatorOptions::default().with_free_search(true);
+/// let translate = || zelen::translate_with_vars_and_config(&ast, &config, options.clone());
+/// let mut model_data = translate().unwrap();
+/// let x = model_data.int_vars["x"];
+/// let best = zelen::optimize_model(&mut model_data, translate).unwrap().unwrap();
+/// assert_eq!(best.get_int(x), 4);
+/// ```
+pub fn optimize_model(
+    model_data: &mut TranslatedModel,
+    retranslate: impl Fn() -> Result<TranslatedModel>,
+) -> Result<std::result::Result<selen::core::Solution, selen::core::SolverError>> {
+    let model = std::mem::take(&mut model_data.model);
+    optimize_goal(model, objective_goal(model_data), retranslate, None)
+}
+
+/// Like [`optimize_model`], but reports every improving solution as it is found,
+/// together with the variable maps to format it
+pub fn optimize_model_with_intermediate(
+    model_data: &mut TranslatedModel,
+    retranslate: impl Fn() -> Result<TranslatedModel>,
+    mut on_incumbent: impl FnMut(&TranslatedModel, &selen::core::Solution),
+) -> Result<std::result::Result<selen::core::Solution, selen::core::SolverError>> {
+    let model = std::mem::take(&mut model_data.model);
+    let model_data = &*model_data;
+    let mut report = |solution: &selen::core::Solution| on_incumbent(model_data, solution);
+    optimize_goal(model, objective_goal(model_data), retranslate, Some(&mut report))
+}
+
+/// The objective variable and whether it is maximized, if the model has one
+fn objective_goal(model_data: &TranslatedModel) -> Option<(VarId, bool)> {
+    match (model_data.objective_type, model_data.objective_var) {
+        (ObjectiveType::Minimize, Some(objective)) => Some((objective, false)),
+        (ObjectiveType::Maximize, Some(objective)) => Some((objective, true)),
+        _ => None,
+    }
+}
+
+/// Optimize `goal` (see [`objective_goal`]) in `model`, or solve it once
+/// when there is none
+fn optimize_goal(
+    model: Model,
+    goal: Option<(VarId, bool)>,
+    translate: impl Fn() -> Result<TranslatedModel>,
+    on_incumbent: Option<&mut dyn FnMut(&selen::core::Solution)>,
+) -> Result<std::result::Result<selen::core::Solution, selen::core::SolverError>> {
+    if let Some((objective, maximize)) = goal {
+        return optimize_translated(model, objective, maximize, translate, on_incumbent);
+    }
+    let result = model.solve();
+    if let (Ok(solution), Some(on_incumbent)) = (&result, on_incumbent) {
+        on_incumbent(solution);
+    }
+    Ok(result)
+}
+
+/// Relative gap at which float objective bisection stops
+const FLOAT_OBJECTIVE_TOLERANCE: f64 = 1e-4;
+
+/// Optimize `objective` in a freshly translated model
+///
+/// A float objective is first solved for satisfaction; then, while the gap
+/// between the best value found and the proven bound exceeds
+/// [`FLOAT_OBJECTIVE_TOLERANCE`], the model is re-translated with the objective
+/// restricted to the better half of that gap. A solution tightens the best
+/// value, an infeasible half moves the bound, and any other solver failure
+/// (such as a time limit) keeps the best solution so far. A float domain that
+/// is open in the optimizing direction has no bound to bisect against, so one
+/// is found first by probing past the best value in doubling strides; once the
+/// best value passes the solver's fallback bound, it is returned as is.
+///
+/// With `on_incumbent`, Selen's optimizer is run step-wise through its
+/// solution iterator and each improving solution is passed to the callback,
+/// as is each solution that tightens the best float value.
+fn optimize_translated(
+    mut model: Model,
+    objective: selen::prelude::VarId,
+    maximize: bool,
+    translate: impl Fn() -> Result<TranslatedModel>,
+    mut on_incumbent: Option<&mut dyn FnMut(&selen::core::Solution)>,
+) -> Result<std::result::Result<selen::core::Solution, selen::core::SolverError>> {
+    // Integer objectives go to Selen's optimizer
+    let domain = match &model.get_vars()[objective] {
+        selen::variables::Var::VarF(domain) => (domain.min, domain.max),
+        _ => {
+            // A bare VarId objective takes Selen's LP shortcut, which reports
+            // NoSolution for some feasible models; an offset view skips it
+            let objective = selen::variables::views::ViewExt::plus(objective, selen::prelude::Val::ValI(0));
+            let Some(on_incumbent) = on_incumbent else {
+                return Ok(if maximize {
+                    model.maximize(objective)
+                } else {
+                    model.minimize(objective)
+                });
+            };
+            let incumbents: Box<dyn Iterator<Item = selen::core::Solution>> = if maximize {
+                Box::new(model.maximize_and_iterate(objective))
+            } else {
+                Box::new(model.minimize_and_iterate(objective))
+            };
+            let mut best = None;
+            for solution in incumbents {
+                on_incumbent(&solution);
+                best = Some(solution);
+            }
+            return Ok(best.ok_or_else(selen::core::SolverError::no_solution));
+        }
+    };
+
+    // Work in minimization form: maximizing f is minimizing -f
+    let sign = if maximize { -1.0 } else { 1.0 };
+    let mut bound = if maximize { -domain.1 } else { domain.0 };
+    // Selen does not terminate on half-open float domains, so an open objective
+    // is kept within the fallback bound, where reaching it means unbounded
+    let open = domain.0 <= -UNBOUNDED_FLOAT || domain.1 >= UNBOUNDED_FLOAT;
+    let cap = |model: &mut Model| {
+        if open {
+            model.lin_le(&[1.0], &[objective], UNBOUNDED_FLOAT);
+            model.lin_le(&[-1.0], &[objective], UNBOUNDED_FLOAT);
+        }
+    };
+    cap(&mut model);
+    let mut best = match model.solve() {
+        Ok(solution) => solution,
+        Err(e) => return Ok(Err(e)),
+    };
+    let mut best_value = sign * best.get_float(objective);
+    if let Some(on_incumbent) = on_incumbent.as_mut() {
+        on_incumbent(&best);
+    }
+
+    // Without a bound to bisect against, probe past the best value in
+    // doubling strides until a probe fails
+    if bound <= -UNBOUNDED_FLOAT {
+        let mut stride = best_value.abs().max(1.0);
+        while best_value > -UNBOUNDED_FLOAT {
+            let target = (best_value - stride).max(-UNBOUNDED_FLOAT);
+            let mut probe = translate()?;
+            cap(&mut probe.model);
+            probe.model.lin_le(&[sign], &[objective], target);
+            match probe.model.solve() {
+                Ok(solution) => {
+                    best_value = sign * solution.get_float(objective);
+                    if let Some(on_incumbent) = on_incumbent.as_mut() {
+                        on_incumbent(&solution);
+                    }
+                    best = solution;
+                    stride *= 2.0;
+                }
+                Err(selen::core::SolverError::NoSolution { .. }) => {
+                    bound = target;
+                    break;
+                }
+                Err(_) => return Ok(Ok(best)),
+            }
+        }
+        if best_value <= -UNBOUNDED_FLOAT {
+            return Ok(Ok(best));
+        }
+    }
+
+    while best_value - bound > FLOAT_OBJECTIVE_TOLERANCE * best_value.abs().max(1.0) {
+        let mid = bound + (best_value - bound) / 2.0;
+        let mut probe = translate()?;
+        cap(&mut probe.model);
+        probe.model.lin_le(&[sign], &[objective], mid);
+        match probe.model.solve() {
+            Ok(solution) => {
+                best_value = sign * solution.get_float(objective);
+                if let Some(on_incumbent) = on_incumbent.as_mut() {
+                    on_incumbent(&solution);
+                }
+                best = solution;
+            }
+            Err(selen::core::SolverError::NoSolution { .. }) => bound = mid,
+            Err(_) => break,
+        }
+    }
+    Ok(Ok(best))
+}
+
+/// Map a solver error to the status it implies
+fn failure_status(error: &selen::core::SolverError) -> SolveStatus {
+    match error {
+        selen::core::SolverError::NoSolution { .. } => SolveStatus::Unsatisfiable,
+        selen::core::SolverError::Timeout { .. } => SolveStatus::TimedOut,
+        _ => SolveStatus::Unknown,
+    }
+}
+
+/// Whether an integer objective depends on a variable declared without bounds
+/// and its domain reaches the solver's fallback bound in the optimizing direction
+fn int_objective_is_open(model_data: &TranslatedModel, objective: VarId, maximize: bool) -> bool {
+    if !model_data.objective_open {
+        return false;
+    }
+    match &model_data.model.get_vars()[objective] {
+        selen::variables::Var::VarI(domain) if maximize => domain.max() >= UNBOUNDED_INT,
+        selen::variables::Var::VarI(domain) => domain.min() <= -UNBOUNDED_INT,
+        selen::variables::Var::VarF(_) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -383,4 +884,265 @@ mod tests {
             assert!(error_msg.contains("line 1"));
         }
     }
+
+    #[test]
+    fn test_error_categories() {
+        let lex = parse("var 1..3: x $ 2;").unwrap_err();
+        assert_eq!(lex.category(), ErrorCategory::Lex);
+
+        let parse_error = parse("int n = 5;").unwrap_err();
+        assert_eq!(parse_error.category(), ErrorCategory::Parse);
+
+        let unsupported = build_model("var 1..3: x; constraint frobnicate(x); solve satisfy;").unwrap_err();
+        assert_eq!(unsupported.category(), ErrorCategory::Unsupported);
+
+        let mismatch = build_model("array[1..2] of var 1..3: x; constraint alldifferent(x, x); solve satisfy;")
+            .unwrap_err();
+        assert_eq!(mismatch.category(), ErrorCategory::TypeMismatch);
+
+        let translate = build_model("var 1..3: x; constraint x < y; solve satisfy;").unwrap_err();
+        assert_eq!(translate.category(), ErrorCategory::Translate);
+    }
+
+    #[test]
+    fn test_solve_report_statuses() {
+        let unbounded = solve_report(
+            "var int: x; constraint x >= 3; solve maximize x;",
+            SolverConfig::default(),
+        ).unwrap();
+        assert_eq!(unbounded.status, SolveStatus::Unbounded);
+
+        let unbounded_float = solve_report(
+            "var float: f; constraint f >= 1.0; solve maximize f;",
+            SolverConfig::default(),
+        ).unwrap();
+        assert_eq!(unbounded_float.status, SolveStatus::Unbounded);
+
+        // An undeclared domain that the constraints bound is still optimal
+        let source = "var int: x; var 1..5: y; constraint x = 2 * y; solve maximize x;";
+        let x = Translator::translate_with_vars(&parse(source).unwrap()).unwrap().int_vars["x"];
+        let bounded = solve_report(source, SolverConfig::default()).unwrap();
+        assert_eq!(bounded.status, SolveStatus::Optimal);
+        assert_eq!(bounded.solution().unwrap().get_int(x), 10);
+
+        // A constraint that stops the objective exactly at the fallback bound
+        let source = "var int: x; constraint x <= 100000; solve maximize x;";
+        let x = Translator::translate_with_vars(&parse(source).unwrap()).unwrap().int_vars["x"];
+        let at_bound = solve_report(source, SolverConfig::default()).unwrap();
+        assert_eq!(at_bound.status, SolveStatus::Optimal);
+        assert_eq!(at_bound.solution().unwrap().get_int(x), 100000);
+
+        // A declared domain wider than the fallback bound is not open, even
+        // though the objective can reach that bound
+        let source = "var 0..200000: x; var 0..20: y; constraint x = 10000 * y; constraint y <= 15; solve maximize x;";
+        let x = Translator::translate_with_vars(&parse(source).unwrap()).unwrap().int_vars["x"];
+        let wide = solve_report(source, SolverConfig::default()).unwrap();
+        assert_eq!(wide.status, SolveStatus::Optimal);
+        assert_eq!(wide.solution().unwrap().get_int(x), 150000);
+
+        let unsat = solve_report(
+            "var 1..3: x; constraint x > 5; solve satisfy;",
+            SolverConfig::default(),
+        ).unwrap();
+        assert_eq!(unsat.status, SolveStatus::Unsatisfiable);
+        assert!(unsat.solutions.is_empty());
+    }
+
+    #[test]
+    fn test_solve_report_enumeration_statuses() {
+        let all = solve_report(
+            "var 1..3: x; constraint x > 1; solve satisfy;",
+            SolverConfig::default().with_all_solutions(true),
+        ).unwrap();
+        assert_eq!(all.status, SolveStatus::Satisfied);
+        assert_eq!(all.solutions.len(), 2);
+
+        let unsat = solve_report(
+            "var 1..3: x; constraint x > 5; solve satisfy;",
+            SolverConfig::default().with_all_solutions(true),
+        ).unwrap();
+        assert_eq!(unsat.status, SolveStatus::Unsatisfiable);
+        assert!(unsat.solutions.is_empty());
+
+        // Pigeonhole over pairwise disequalities: far too much search for 1ms
+        let pigeonhole = r#"
+            array[1..12] of var 1..11: x;
+            constraint forall(i, j in 1..12 where i < j)(x[i] != x[j]);
+            solve satisfy;
+        "#;
+        for config in [SolverConfig::default(), SolverConfig::default().with_all_solutions(true)] {
+            let timed_out = solve_report(pigeonhole, config.with_time_limit_ms(1)).unwrap();
+            assert_eq!(timed_out.status, SolveStatus::TimedOut);
+            assert!(timed_out.solutions.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_solve_each_stops_on_break() {
+        let source = "var 1..9: x; solve satisfy;";
+        let mut values = Vec::new();
+        let seen = solve_each(source, SolverConfig::default(), |model_data, solution| {
+            let x = solution.get_int(model_data.int_vars["x"]);
+            values.push(x);
+            if x % 2 == 0 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        }).unwrap();
+        assert_eq!(seen, values.len());
+        assert_eq!(values.iter().filter(|&&v| v % 2 == 0).count(), 1);
+        assert_eq!(values.last().unwrap() % 2, 0);
+
+        let capped = solve_each(source, SolverConfig::default().with_max_solutions(3), |_, _| ControlFlow::Continue(())).unwrap();
+        assert_eq!(capped, 3);
+        let all = solve_each(source, SolverConfig::default(), |_, _| ControlFlow::Continue(())).unwrap();
+        assert_eq!(all, 9);
+    }
+
+    #[test]
+    fn test_float_objective_optimum() {
+        let source = r#"
+            var 0.0..10.0: x;
+            var 0.0..10.0: y;
+            constraint x + y >= 3.5;
+            var float: cost = 2.0 * x + 3.0 * y;
+            solve minimize cost;
+        "#;
+        let ast = parse(source).unwrap();
+        let cost = Translator::translate_with_vars(&ast).unwrap().float_vars["cost"];
+        let solution = optimize(&ast, SolverConfig::default()).unwrap().unwrap();
+        // All of the demand goes to the cheaper x: 2.0 * 3.5
+        assert!((solution.get_float(cost) - 7.0).abs() < 1e-3, "cost = {}", solution.get_float(cost));
+
+        let source = r#"
+            var 0.0..10.0: x;
+            var 0.0..10.0: y;
+            constraint x + y <= 3.5;
+            solve maximize 2.0 * x + 3.0 * y;
+        "#;
+        let report = solve_report(source, SolverConfig::default()).unwrap();
+        assert_eq!(report.status, SolveStatus::Optimal);
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let objective = model_data.objective_var.unwrap();
+        let value = report.solution().unwrap().get_float(objective);
+        assert!((value - 10.5).abs() < 1e-3, "objective = {}", value);
+
+        // Declared without bounds, the optimum still respects the constraints
+        for (goal, expected) in [("maximize", 3.5), ("minimize", 1.0)] {
+            let source = format!("var float: x; constraint x <= 3.5; constraint x >= 1.0; solve {} x;", goal);
+            let ast = parse(&source).unwrap();
+            let x = Translator::translate_with_vars(&ast).unwrap().float_vars["x"];
+            let solution = optimize(&ast, SolverConfig::default()).unwrap().unwrap();
+            assert!((solution.get_float(x) - expected).abs() < 1e-3, "{} x = {}", goal, solution.get_float(x));
+        }
+    }
+
+    #[test]
+    fn test_intermediate_solutions_improve() {
+        let source = r#"
+            array[1..4] of var 0..3: x;
+            constraint forall(i in 1..3)(x[i] != x[i + 1]);
+            solve maximize sum(x);
+        "#;
+        let ast = parse(source).unwrap();
+        let objective = Translator::translate_with_vars(&ast).unwrap().objective_var.unwrap();
+        let mut values = Vec::new();
+        let best = optimize_with_intermediate(&ast, SolverConfig::default(), |solution| {
+            values.push(solution.get_int(objective))
+        })
+        .unwrap()
+        .unwrap();
+
+        assert!(values.len() > 1, "only {:?} reported", values);
+        assert!(values.windows(2).all(|w| w[0] < w[1]), "not improving: {:?}", values);
+        assert_eq!(values.last(), Some(&best.get_int(objective)));
+        assert_eq!(best.get_int(objective), 10);
+    }
+
+    #[test]
+    fn test_knapsack_generator_sum_objective() {
+        for take_type in ["var 0..1", "var bool"] {
+            let source = format!(
+                r#"
+                int: n = 4;
+                array[1..n] of int: profit = [10, 7, 4, 3];
+                array[1..n] of int: weight = [5, 4, 3, 1];
+                array[1..n] of {}: take;
+                constraint sum(i in 1..n)(weight[i] * take[i]) <= 8;
+                solve maximize sum(i in 1..n)(profit[i] * take[i]);
+                "#,
+                take_type
+            );
+            let report = solve_report(&source, SolverConfig::default()).unwrap();
+            assert_eq!(report.status, SolveStatus::Optimal);
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let objective = model_data.objective_var.unwrap();
+            // Items 1 and 3, or items 2, 3 and 4, fill the capacity for a profit of 14
+            assert_eq!(report.solution().unwrap().get_int(objective), 14, "take: {}", take_type);
+        }
+    }
+
+    #[test]
+    fn test_parse_file_error_names_the_file() {
+        let path = std::env::temp_dir().join(format!("zelen_parse_file_{}.mzn", std::process::id()));
+        std::fs::write(&path, "var 1..3: x;\nconstraint x > ;\n").unwrap();
+        let result = parse_file(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let error = result.unwrap_err();
+        assert_eq!(error.filename(), Some(path.display().to_string().as_str()));
+        let message = error.to_string();
+        let expected = format!("Error in {} at line 2, column 16", path.display());
+        assert!(message.starts_with(&expected), "{}", message);
+
+        std::fs::write(&path, "var 1..3: x;\nsolve satisfy;\n").unwrap();
+        let result = parse_file(&path);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(result.unwrap().items.len(), 2);
+    }
+
+    #[test]
+    fn test_translate_with_vars_and_config_from_one_ast() {
+        let ast = parse("array[0..1] of var 1..2: x; constraint x[0] < x[1]; solve satisfy;").unwrap();
+        let config = SolverConfig::default().with_time_limit_ms(1234);
+        let options = TranslatorOptions::default().with_indexed_output(true);
+        let mut model_data = translate_with_vars_and_config(&ast, &config, options).unwrap();
+
+        // The solver limits and the translation options both apply
+        assert_eq!(model_data.model.config().timeout_ms, Some(1234));
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(model_data.pretty_print(&solution), "x = [0: 1, 1: 2];\n");
+    }
+
+    #[test]
+    fn test_optimize_model_reuses_the_translation() {
+        let ast = parse("array[0..1] of var 1..3: x; constraint x[0] < x[1]; solve maximize x[0];").unwrap();
+        let config = SolverConfig::default();
+        let options = TranslatorOptions::default().with_indexed_output(true);
+        let translations = std::cell::Cell::new(0);
+        let translate = || {
+            translations.set(translations.get() + 1);
+            translate_with_vars_and_config(&ast, &config, options.clone())
+        };
+        let mut model_data = translate().unwrap();
+
+        // An integer objective is optimized on the model at hand
+        let mut printed = Vec::new();
+        let on_incumbent = |model_data: &TranslatedModel, solution: &Solution| {
+            printed.push(model_data.pretty_print(solution));
+        };
+        let best = optimize_model_with_intermediate(&mut model_data, translate, on_incumbent).unwrap().unwrap();
+        assert_eq!(translations.get(), 1);
+        assert_eq!(model_data.pretty_print(&best), "x = [0: 2, 1: 3];\n");
+        assert_eq!(printed.last().map(String::as_str), Some("x = [0: 2, 1: 3];\n"));
+
+        // Float bisection needs a fresh model per step
+        let ast = parse("var 0.0..10.0: f; constraint f >= 2.5; solve minimize f;").unwrap();
+        let translate = || {
+            translations.set(translations.get() + 1);
+            translate_with_vars_and_config(&ast, &config, TranslatorOptions::default())
+        };
+        let mut model_data = translate().unwrap();
+        let f = model_data.float_vars["f"];
+        let best = optimize_model(&mut model_data, translate).unwrap().unwrap();
+        assert!((best.get_float(f) - 2.5).abs() < 1e-2, "f = {}", best.get_float(f));
+        assert!(translations.get() > 2);
+    }
 }
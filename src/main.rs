@@ -5,10 +5,11 @@
 
 use clap::Parser;
 use std::fs;
+use std::io::{self, Read};
 use std::path::PathBuf;
 use std::time::Instant;
 use zelen::parse;
-use zelen::translator::{Translator, ObjectiveType};
+use zelen::translator::{TranslatorOptions, ObjectiveType};
 
 /// Zelen - Direct MiniZinc Solver backed by Selen CSP Solver
 #[derive(Parser, Debug)]
@@ -20,10 +21,11 @@ use zelen::translator::{Translator, ObjectiveType};
                   This bypasses FlatZinc compilation for supported MiniZinc features.\n\n\
                   Usage:\n  \
                     zelen model.mzn           # Solve model with no data file\n  \
-                    zelen model.mzn data.dzn  # Solve model with data file"
+                    zelen model.mzn data.dzn  # Solve model with data file\n  \
+                    cat model.mzn | zelen -   # Solve model read from stdin"
 )]
 struct Args {
-    /// MiniZinc model file to solve (.mzn)
+    /// MiniZinc model file to solve (.mzn), or `-` to read the model from stdin
     #[arg(value_name = "MODEL")]
     file: PathBuf,
 
@@ -47,6 +49,18 @@ struct Args {
     #[arg(short = 's', long)]
     statistics: bool,
 
+    /// Print the objective value as `_objective = V;` for optimization problems
+    #[arg(long)]
+    output_objective: bool,
+
+    /// Print arrays as `[index: value, ...]` pairs using their declared index sets
+    #[arg(long)]
+    indexed_output: bool,
+
+    /// Print a summary of the translated model (variables, domains, constraints) to stderr
+    #[arg(long)]
+    print_model: bool,
+
     /// Verbose output (more detail)
     #[arg(short = 'v', long)]
     verbose: bool,
@@ -59,7 +73,7 @@ struct Args {
     #[arg(long, value_name = "MB", default_value = "0")]
     mem_limit: u64,
 
-    /// Free search (ignore search annotations) - not yet supported
+    /// Free search (ignore search annotations)
     #[arg(short = 'f', long)]
     free_search: bool,
 
@@ -76,11 +90,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
     // Print warnings for unsupported features
-    if args.free_search {
-        if args.verbose {
-            eprintln!("Warning: Free search (--free-search) is not yet supported, ignoring");
-        }
-    }
     if args.parallel.is_some() {
         if args.verbose {
             eprintln!("Warning: Parallel search (--parallel) is not yet supported, ignoring");
@@ -91,19 +100,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             eprintln!("Warning: Random seed (--random-seed) is not yet supported, ignoring");
         }
     }
-    if args.intermediate {
+
+    // Read the MiniZinc source file, or stdin for `-`
+    let source = if args.file.as_os_str() == "-" {
         if args.verbose {
-            eprintln!("Note: Intermediate solutions (--intermediate) will be shown for all solutions");
+            eprintln!("Reading MiniZinc model from stdin");
         }
-    }
-
-    // Read the MiniZinc source file
-    if args.verbose {
-        eprintln!("Reading MiniZinc model file: {}", args.file.display());
-    }
-    let source = fs::read_to_string(&args.file).map_err(|e| {
-        format!("Failed to read file '{}': {}", args.file.display(), e)
-    })?;
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source).map_err(|e| {
+            format!("Failed to read model from stdin: {}", e)
+        })?;
+        source
+    } else {
+        if args.verbose {
+            eprintln!("Reading MiniZinc model file: {}", args.file.display());
+        }
+        fs::read_to_string(&args.file).map_err(|e| {
+            format!("Failed to read file '{}': {}", args.file.display(), e)
+        })?
+    };
 
     // Read optional data file
     let data_source = if let Some(ref data_file) = args.data_file {
@@ -119,7 +134,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     // Combine model and data sources
-    let combined_source = if let Some(data) = data_source {
+    let model_len = source.len();
+    let combined_source = if let Some(data) = &data_source {
         if args.verbose {
             eprintln!("Merging model and data sources...");
         }
@@ -132,10 +148,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if args.verbose {
         eprintln!("Parsing MiniZinc source...");
     }
-    let ast = parse(&combined_source).map_err(|e| {
-        format!("Parse error: {:?}", e)
+    let ast = parse(&combined_source).map_err(|mut e| {
+        // Name the file the error is in, locating it within the data file
+        // when it lies past the model's text
+        match (&args.data_file, &data_source) {
+            (Some(data_file), Some(data)) if e.span.start > model_len => {
+                e.span.start -= model_len + 1;
+                e.span.end = e.span.end.saturating_sub(model_len + 1);
+                e.with_source(data.clone()).with_filename(&data_file.display().to_string())
+            }
+            _ if args.file.as_os_str() == "-" => e,
+            _ => e.with_filename(&args.file.display().to_string()),
+        }
+        .to_string()
     })?;
 
+    // Inline included files, found relative to the model file (a model read
+    // from stdin has no location to resolve them against)
+    let ast = if args.file.as_os_str() == "-" {
+        ast
+    } else {
+        zelen::Translator::expand_includes(ast, &args.file).map_err(|e| e.to_string())?
+    };
+
     // Create solver configuration from command-line arguments
     let mut config = zelen::SolverConfig::default();
     if args.time > 0 {
@@ -155,8 +190,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if args.verbose {
         eprintln!("Translating to Selen model...");
     }
-    let model_data = Translator::translate_with_vars(&ast).map_err(|e| {
-        format!("Translation error: {:?}", e)
+    let options = TranslatorOptions::default()
+        .with_free_search(args.free_search)
+        .with_verbose(args.verbose)
+        .with_indexed_output(args.indexed_output);
+    // Float objectives are bisected over fresh models translated the same way
+    let translate = || zelen::translate_with_vars_and_config(&ast, &config, options.clone());
+    let mut model_data = translate().map_err(|e| {
+        format!("Translation error: {}", e.with_source(combined_source.clone()))
     })?;
 
     if args.verbose {
@@ -169,6 +210,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 + model_data.bool_var_arrays.len()
                 + model_data.float_var_arrays.len()
         );
+        for warning in &model_data.warnings {
+            eprintln!("{}", warning.display_in(&combined_source));
+        }
+        if args.free_search {
+            eprintln!("Free search: ignoring search annotations");
+        }
+        for heuristic in &model_data.search_heuristics {
+            eprintln!(
+                "Note: search annotation ({:?}, {:?}) over {} variables is recorded only; Selen uses its own branching",
+                heuristic.var_select,
+                heuristic.val_select,
+                heuristic.vars.len()
+            );
+        }
+    }
+    if args.print_model {
+        eprint!("{}", model_data.summary());
     }
 
     // Solve the model
@@ -193,35 +251,53 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let obj_type = model_data.objective_type;
     let obj_var = model_data.objective_var;
     
-    // Build a new model with config for solving
-    let model_with_config = zelen::build_model_with_config(&combined_source, config.clone()).map_err(|e| {
-        format!("Failed to build model with config: {}", e)
-    })?;
-    
+    // With --intermediate, each improving solution is printed as soon as the
+    // optimizer finds it; `streamed` counts how many were printed that way
+    let mut streamed = 0;
+    let optimize = |model_data: &mut zelen::TranslatedModel, streamed: &mut usize| {
+        if !args.intermediate {
+            return zelen::optimize_model(model_data, translate);
+        }
+        zelen::optimize_model_with_intermediate(model_data, translate, |model_data, solution| {
+            if *streamed > 0 {
+                println!("----------");
+            }
+            // Printing only writes to stdout, which cannot fail here
+            let _ = print_solution(solution, model_data, args.output_objective, false, 0);
+            *streamed += 1;
+        })
+    };
+
     let solutions = if args.all_solutions || args.num_solutions.is_some() {
         // Enumerate multiple solutions
         if args.verbose {
             eprintln!("Enumerating solutions...");
         }
         let max = args.num_solutions.unwrap_or(usize::MAX);
-        model_with_config.enumerate().take(max).collect::<Vec<_>>()
+        std::mem::take(&mut model_data.model).enumerate().take(max).collect::<Vec<_>>()
     } else {
         // Single solution - may be optimal for minimize/maximize
         match (obj_type, obj_var) {
-            (ObjectiveType::Minimize, Some(obj_var)) => {
+            (ObjectiveType::Minimize, Some(_)) => {
                 if args.verbose {
                     eprintln!("Minimizing objective...");
                 }
-                match model_with_config.minimize(obj_var) {
+                let result = optimize(&mut model_data, &mut streamed).map_err(|e| {
+                    format!("Translation error: {}", e.with_source(combined_source.clone()))
+                })?;
+                match result {
                     Ok(solution) => vec![solution],
                     Err(_) => Vec::new(),
                 }
             }
-            (ObjectiveType::Maximize, Some(obj_var)) => {
+            (ObjectiveType::Maximize, Some(_)) => {
                 if args.verbose {
                     eprintln!("Maximizing objective...");
                 }
-                match model_with_config.maximize(obj_var) {
+                let result = optimize(&mut model_data, &mut streamed).map_err(|e| {
+                    format!("Translation error: {}", e.with_source(combined_source.clone()))
+                })?;
+                match result {
                     Ok(solution) => vec![solution],
                     Err(_) => Vec::new(),
                 }
@@ -230,12 +306,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 if args.verbose {
                     eprintln!("Solving satisfaction problem...");
                 }
-                match model_with_config.solve() {
+                match std::mem::take(&mut model_data.model).solve() {
                     Ok(solution) => vec![solution],
                     Err(_) => Vec::new(),
                 }
             }
-            _ => match model_with_config.solve() {
+            _ => match std::mem::take(&mut model_data.model).solve() {
                 Ok(solution) => vec![solution],
                 Err(_) => Vec::new(),
             }
@@ -253,12 +329,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        if streamed > 0 {
+            // The optimal solution was the last one streamed
+            if args.statistics {
+                print_statistics(&solutions[0], streamed);
+            }
+            return Ok(());
+        }
+
         // Print all solutions in MiniZinc format
         for (idx, solution) in solutions.iter().enumerate() {
             if idx > 0 {
                 println!("----------");
             }
-            print_solution(solution, &model_data, args.statistics && idx == solutions.len() - 1, solutions.len())?;
+            print_solution(
+                solution,
+                &model_data,
+                args.output_objective,
+                args.statistics && idx == solutions.len() - 1,
+                solutions.len(),
+            )?;
         }
     } else {
         if args.verbose {
@@ -278,126 +368,56 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 fn print_solution(
     solution: &selen::prelude::Solution,
     model_data: &zelen::TranslatedModel,
+    output_objective: bool,
     print_stats: bool,
     total_solutions: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Try to use output formatting from the model first
-    if let Some(formatted_output) = model_data.format_output(solution) {
-        print!("{}", formatted_output);
-    } else {
-        // Fall back to default variable printing
-        // Print integer variables
-        for (name, var_id) in &model_data.int_vars {
-            let value = solution.get_int(*var_id);
-            // Check if this is an enum variable
-            if let Some((_, enum_values)) = model_data.enum_vars.get(name) {
-                if value >= 1 && (value as usize) <= enum_values.len() {
-                    let enum_str = &enum_values[(value - 1) as usize];
-                    println!("{} = {};", name, enum_str);
-                } else {
-                    println!("{} = {};", name, value);
-                }
-            } else {
-                println!("{} = {};", name, value);
-            }
-        }
-
-        // Print boolean variables (as 0/1 in MiniZinc format)
-        for (name, var_id) in &model_data.bool_vars {
-            let value = solution.get_int(*var_id);
-            println!("{} = {};", name, value);
-        }
-
-        // Print float variables
-        for (name, var_id) in &model_data.float_vars {
-            let value = solution.get_float(*var_id);
-            println!("{} = {};", name, value);
-        }
-
-        // Print integer arrays
-        for (name, var_ids) in &model_data.int_var_arrays {
-            print!("{} = [", name);
-            // Check if this is an enum array
-            let is_enum = model_data.enum_vars.contains_key(name);
-            let enum_values = model_data.enum_vars.get(name).map(|(_, vals)| vals);
-            for (i, var_id) in var_ids.iter().enumerate() {
-                if i > 0 {
-                    print!(", ");
-                }
-                let value = solution.get_int(*var_id);
-                if is_enum {
-                    if let Some(vals) = enum_values {
-                        if value >= 1 && (value as usize) <= vals.len() {
-                            print!("{}", vals[(value - 1) as usize]);
-                        } else {
-                            print!("{}", value);
-                        }
-                    } else {
-                        print!("{}", value);
-                    }
-                } else {
-                    print!("{}", value);
-                }
-            }
-            println!("];");
-        }
-
-        // Print boolean arrays (as 0/1)
-        for (name, var_ids) in &model_data.bool_var_arrays {
-            print!("{} = [", name);
-            for (i, var_id) in var_ids.iter().enumerate() {
-                if i > 0 {
-                    print!(", ");
-                }
-                let value = solution.get_int(*var_id);
-                print!("{}", value);
-            }
-            println!("];");
-        }
-
-        // Print float arrays
-        for (name, var_ids) in &model_data.float_var_arrays {
-            print!("{} = [", name);
-            for (i, var_id) in var_ids.iter().enumerate() {
-                if i > 0 {
-                    print!(", ");
-                }
-                let value = solution.get_float(*var_id);
-                print!("{}", value);
-            }
-            println!("];");
-        }
-
+    let objective_line = model_data
+        .format_objective(solution)
+        .filter(|_| output_objective);
+
+    // The model's output items, or `name = value;` lines without them
+    let (output, from_output_items) = model_data.pretty_print_with_source(solution);
+    print!("{}", output);
+    if let Some(line) = &objective_line {
+        println!("{}", line);
+    }
+    if !from_output_items {
         // Print solution separator
         println!("----------");
     }
 
     // Print statistics if requested
     if print_stats {
-        println!("%%%mzn-stat: solutions={}", total_solutions);
-        println!("%%%mzn-stat: nodes={}", solution.stats.node_count);
-        println!("%%%mzn-stat: variables={}", solution.stats.variables);
-        println!("%%%mzn-stat: intVariables={}", solution.stats.int_variables);
-        println!("%%%mzn-stat: boolVariables={}", solution.stats.bool_variables);
-        println!("%%%mzn-stat: floatVariables={}", solution.stats.float_variables);
-        println!("%%%mzn-stat: propagators={}", solution.stats.propagators);
-        println!("%%%mzn-stat: propagations={}", solution.stats.propagation_count);
-        println!("%%%mzn-stat: constraints={}", solution.stats.constraint_count);
-        println!("%%%mzn-stat: objective={}", solution.stats.objective);
-        println!("%%%mzn-stat: objectiveBound={}", solution.stats.objective_bound);
-        println!("%%%mzn-stat: initTime={:.6}", solution.stats.init_time.as_secs_f64());
-        println!("%%%mzn-stat: solveTime={:.6}", solution.stats.solve_time.as_secs_f64());
-        println!("%%%mzn-stat: peakMem={:.2}", solution.stats.peak_memory_mb as f64);
-        
-        // LP solver stats if available
-        if solution.stats.lp_solver_used {
-            println!("%%%mzn-stat: lpSolverUsed=true");
-            println!("%%%mzn-stat: lpConstraintCount={}", solution.stats.lp_constraint_count);
-            println!("%%%mzn-stat: lpVariableCount={}", solution.stats.lp_variable_count);
-        }
-        
-        println!("%%%mzn-stat-end");
+        print_statistics(solution, total_solutions);
     }
 
     Ok(())
 }
+
+/// Print solver statistics in MiniZinc `%%%mzn-stat` format
+fn print_statistics(solution: &selen::prelude::Solution, total_solutions: usize) {
+    println!("%%%mzn-stat: solutions={}", total_solutions);
+    println!("%%%mzn-stat: nodes={}", solution.stats.node_count);
+    println!("%%%mzn-stat: variables={}", solution.stats.variables);
+    println!("%%%mzn-stat: intVariables={}", solution.stats.int_variables);
+    println!("%%%mzn-stat: boolVariables={}", solution.stats.bool_variables);
+    println!("%%%mzn-stat: floatVariables={}", solution.stats.float_variables);
+    println!("%%%mzn-stat: propagators={}", solution.stats.propagators);
+    println!("%%%mzn-stat: propagations={}", solution.stats.propagation_count);
+    println!("%%%mzn-stat: constraints={}", solution.stats.constraint_count);
+    println!("%%%mzn-stat: objective={}", solution.stats.objective);
+    println!("%%%mzn-stat: objectiveBound={}", solution.stats.objective_bound);
+    println!("%%%mzn-stat: initTime={:.6}", solution.stats.init_time.as_secs_f64());
+    println!("%%%mzn-stat: solveTime={:.6}", solution.stats.solve_time.as_secs_f64());
+    println!("%%%mzn-stat: peakMem={:.2}", solution.stats.peak_memory_mb as f64);
+    
+    // LP solver stats if available
+    if solution.stats.lp_solver_used {
+        println!("%%%mzn-stat: lpSolverUsed=true");
+        println!("%%%mzn-stat: lpConstraintCount={}", solution.stats.lp_constraint_count);
+        println!("%%%mzn-stat: lpVariableCount={}", solution.stats.lp_variable_count);
+    }
+    
+    println!("%%%mzn-stat-end");
+}
@@ -4,7 +4,7 @@
 
 use crate::ast::*;
 use crate::error::{Error, Result};
-use crate::lexer::{Lexer, Token, TokenKind};
+use crate::lexer::{Lexer, StringPart, Token, TokenKind};
 
 pub struct Parser {
     lexer: Lexer,
@@ -55,13 +55,117 @@ impl Parser {
     fn parse_item(&mut self) -> Result<Item> {
         match &self.current_token.kind {
             TokenKind::Constraint => self.parse_constraint(),
+            TokenKind::Include => self.parse_include(),
             TokenKind::Enum => self.parse_enum_def(),
             TokenKind::Solve => self.parse_solve(),
             TokenKind::Output => self.parse_output(),
+            TokenKind::Predicate => self.parse_predicate(),
+            TokenKind::Function => self.parse_function(),
             _ => self.parse_var_decl(),
         }
     }
 
+    /// Parse include directive: `include "globals.mzn";`
+    fn parse_include(&mut self) -> Result<Item> {
+        let start = self.current_token.span.start;
+        self.expect(TokenKind::Include)?;
+
+        let path = match &self.current_token.kind {
+            TokenKind::StringLit(path) => path.clone(),
+            _ => {
+                return Err(self.add_source_to_error(Error::unexpected_token(
+                    "file name string",
+                    &format!("{:?}", self.current_token.kind),
+                    self.current_token.span,
+                )));
+            }
+        };
+        self.advance()?;
+        self.expect(TokenKind::Semicolon)?;
+
+        let end = self.current_token.span.end;
+
+        Ok(Item::Include(Include {
+            path,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Parse predicate definition: `predicate differ(var int: a, var int: b) = a != b;`
+    fn parse_predicate(&mut self) -> Result<Item> {
+        let start = self.current_token.span.start;
+        self.expect(TokenKind::Predicate)?;
+        let name = self.expect_ident()?;
+        let params = self.parse_params()?;
+        let body = self.parse_definition_body("predicate")?;
+
+        let end = self.current_token.span.end;
+        Ok(Item::Predicate(Predicate {
+            name,
+            params,
+            body,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Parse function definition: `function var int: double(var int: x) = 2 * x;`
+    fn parse_function(&mut self) -> Result<Item> {
+        let start = self.current_token.span.start;
+        self.expect(TokenKind::Function)?;
+        let return_type = self.parse_type_inst()?;
+        self.expect(TokenKind::Colon)?;
+        let name = self.expect_ident()?;
+        let params = self.parse_params()?;
+        let body = self.parse_definition_body("function")?;
+
+        let end = self.current_token.span.end;
+        Ok(Item::Function(Function {
+            return_type,
+            name,
+            params,
+            body,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Parse a parenthesised parameter list: `(var int: a, array[int] of int: b)`
+    fn parse_params(&mut self) -> Result<Vec<Param>> {
+        self.expect(TokenKind::LParen)?;
+        let mut params = Vec::new();
+        while self.current_token.kind != TokenKind::RParen {
+            let param_start = self.current_token.span.start;
+            let type_inst = self.parse_type_inst()?;
+            self.expect(TokenKind::Colon)?;
+            let param_name = self.expect_ident()?;
+            params.push(Param {
+                type_inst,
+                name: param_name,
+                span: Span::new(param_start, self.current_token.span.start),
+            });
+            if self.current_token.kind != TokenKind::Comma {
+                break;
+            }
+            self.advance()?;
+        }
+        self.expect(TokenKind::RParen)?;
+        Ok(params)
+    }
+
+    /// Parse the `= body;` ending a predicate or function definition
+    fn parse_definition_body(&mut self, kind: &str) -> Result<Expr> {
+        if self.current_token.kind != TokenKind::Eq {
+            return Err(self.add_source_to_error(Error::unexpected_token(
+                &format!("'=' and a {} body", kind),
+                &format!("{:?}", self.current_token.kind),
+                self.current_token.span,
+            )));
+        }
+        self.advance()?;
+        let body = self.parse_expr()?;
+        self.expect(TokenKind::Semicolon)?;
+        Ok(body)
+    }
+
     /// Parse enum definition: `enum Color = {Red, Green, Blue};`
     fn parse_enum_def(&mut self) -> Result<Item> {
         let start = self.current_token.span.start;
@@ -87,8 +191,9 @@ impl Parser {
                     }
                 }
                 TokenKind::RBrace => break,
-                _ => return Err(self.add_source_to_error(Error::message(
-                    "Expected comma or closing brace in enum definition",
+                _ => return Err(self.add_source_to_error(Error::unexpected_token(
+                    "comma or closing brace in enum definition",
+                    &format!("{:?}", self.current_token.kind),
                     self.current_token.span,
                 ))),
             }
@@ -108,12 +213,21 @@ impl Parser {
     
     /// Parse variable declaration: `int: n = 5;` or `array[1..n] of var int: x;`
     fn parse_var_decl(&mut self) -> Result<Item> {
+        let mut decl = self.parse_decl()?;
+        self.expect(TokenKind::Semicolon)?;
+        decl.span.end = self.current_token.span.end;
+        Ok(Item::VarDecl(decl))
+    }
+    
+    /// Parse a declaration up to, but not including, its terminator
+    fn parse_decl(&mut self) -> Result<VarDecl> {
         let start = self.current_token.span.start;
         let type_inst = self.parse_type_inst()?;
         
         self.expect(TokenKind::Colon)?;
         
         let name = self.expect_ident()?;
+        let annotations = self.parse_annotations()?;
         
         let expr = if self.current_token.kind == TokenKind::Eq {
             self.advance()?;
@@ -122,16 +236,38 @@ impl Parser {
             None
         };
         
-        self.expect(TokenKind::Semicolon)?;
-        
         let end = self.current_token.span.end;
         
-        Ok(Item::VarDecl(VarDecl {
+        Ok(VarDecl {
             type_inst,
             name,
+            annotations,
             expr,
             span: Span::new(start, end),
-        }))
+        })
+    }
+    
+    /// Parse declaration annotations: `:: output_var :: output_array([1..n])`
+    fn parse_annotations(&mut self) -> Result<Vec<Annotation>> {
+        let mut annotations = Vec::new();
+        while self.current_token.kind == TokenKind::ColonColon {
+            self.advance()?;
+            // Postfix level, so `x :: output_var = 3` keeps its initializer
+            let annotation = self.parse_postfix_expr()?;
+            let (name, args) = match annotation.kind {
+                ExprKind::Ident(name) => (name, Vec::new()),
+                ExprKind::Call { name, args } => (name, args),
+                _ => {
+                    return Err(self.add_source_to_error(Error::unexpected_token(
+                        "annotation",
+                        "expression",
+                        annotation.span,
+                    )));
+                }
+            };
+            annotations.push(Annotation { name, args, span: annotation.span });
+        }
+        Ok(annotations)
     }
     
     /// Parse type-inst: `var int`, `array[1..n] of var 1..10`, etc.
@@ -156,6 +292,13 @@ impl Parser {
         
         // Parse base type or domain
         match &self.current_token.kind {
+            TokenKind::Set => {
+                // Set type: `var set of 1..n`
+                self.advance()?;
+                self.expect(TokenKind::Of)?;
+                let element_type = Box::new(self.parse_type_inst()?);
+                Ok(TypeInst::Set { is_var, element_type })
+            }
             TokenKind::Bool => {
                 self.advance()?;
                 Ok(TypeInst::Basic { is_var, base_type: BaseType::Bool })
@@ -170,15 +313,25 @@ impl Parser {
                 self.advance()?;
                 Ok(TypeInst::Basic { is_var, base_type: BaseType::Float })
             }
-            TokenKind::IntLit(_) | TokenKind::FloatLit(_) | TokenKind::LBrace => {
-                // Constrained type: 1..10 or 0.0..1.0 or {1,3,5}
+            TokenKind::IntLit(_)
+            | TokenKind::FloatLit(_)
+            | TokenKind::Minus
+            | TokenKind::LBrace
+            | TokenKind::LParen
+            | TokenKind::Ident(_) => {
+                // Constrained type: 1..10, -1.0..1.0, {1,3,5} or n-1..2*n;
+                // a lone identifier names an enum type: `var Color: x;`
                 let domain = self.parse_range_or_set_expr()?;
+                if let ExprKind::Ident(enum_name) = domain.kind {
+                    return Ok(TypeInst::Basic { is_var, base_type: BaseType::Enum(enum_name) });
+                }
                 
                 // Infer base type from domain
                 let base_type = match &domain.kind {
                     ExprKind::BinOp { op: BinOp::Range, left, .. } => {
                         match &left.kind {
                             ExprKind::FloatLit(_) => BaseType::Float,
+                            ExprKind::UnOp { op: UnOp::Neg, expr } if matches!(expr.kind, ExprKind::FloatLit(_)) => BaseType::Float,
                             _ => BaseType::Int,
                         }
                     }
@@ -198,12 +351,6 @@ impl Parser {
                     domain,
                 })
             }
-            TokenKind::Ident(enum_name) => {
-                // Enum type: `var Color: x;`
-                let enum_name = enum_name.clone();
-                self.advance()?;
-                Ok(TypeInst::Basic { is_var, base_type: BaseType::Enum(enum_name) })
-            }
             _ => {
                 Err(self.add_source_to_error(Error::unexpected_token(
                     "type (bool, int, float, or constrained type)",
@@ -293,6 +440,7 @@ impl Parser {
         self.expect(TokenKind::Constraint)?;
         
         let expr = self.parse_expr()?;
+        let annotations = self.parse_annotations()?;
         
         self.expect(TokenKind::Semicolon)?;
         
@@ -300,6 +448,7 @@ impl Parser {
         
         Ok(Item::Constraint(Constraint {
             expr,
+            annotations,
             span: Span::new(start, end),
         }))
     }
@@ -309,19 +458,23 @@ impl Parser {
         let start = self.current_token.span.start;
         self.expect(TokenKind::Solve)?;
         
-        // Check for search annotation: :: int_search(...) or :: complete/incomplete
-        let search_option = if self.current_token.kind == TokenKind::ColonColon {
+        // Search annotations: `:: int_search(...)`, `:: seq_search([...])`, `:: complete`
+        let mut search_option = None;
+        let mut search = Vec::new();
+        while self.current_token.kind == TokenKind::ColonColon {
             self.advance()?;
-            Some(self.parse_search_annotation()?)
-        } else {
-            None
-        };
+            let annotation = self.parse_expr()?;
+            self.collect_search_annotation(&annotation, &mut search_option, &mut search);
+            // An annotation without an explicit option defaults to complete search
+            search_option.get_or_insert(SearchOption::Complete);
+        }
         
         let solve = match &self.current_token.kind {
             TokenKind::Satisfy => {
                 self.advance()?;
                 Solve::Satisfy {
                     search_option,
+                    search,
                     span: Span::new(start, self.current_token.span.end),
                 }
             }
@@ -331,6 +484,7 @@ impl Parser {
                 Solve::Minimize {
                     expr,
                     search_option,
+                    search,
                     span: Span::new(start, self.current_token.span.end),
                 }
             }
@@ -340,6 +494,7 @@ impl Parser {
                 Solve::Maximize {
                     expr,
                     search_option,
+                    search,
                     span: Span::new(start, self.current_token.span.end),
                 }
             }
@@ -357,85 +512,53 @@ impl Parser {
         Ok(Item::Solve(solve))
     }
     
-    /// Parse search annotation: int_search(...) and extract complete/incomplete option
-    fn parse_search_annotation(&mut self) -> Result<SearchOption> {
-        // We parse the annotation but only extract complete/incomplete
-        // We ignore variable selection and value selection strategies
-        
-        // Expected format: int_search(variables, var_select, val_select, complete/incomplete)
-        // or just: complete or incomplete
-        
-        if let TokenKind::Ident(name) = &self.current_token.kind {
-            let name_str = name.clone();
-            
-            if name_str == "complete" {
-                self.advance()?;
-                return Ok(SearchOption::Complete);
-            } else if name_str == "incomplete" {
-                self.advance()?;
-                return Ok(SearchOption::Incomplete);
-            } else if name_str == "int_search" || name_str == "bool_search" || name_str == "float_search" {
-                // Parse function call: int_search(args...)
-                self.advance()?;
-                self.expect(TokenKind::LParen)?;
-                
-                // Parse arguments: skip first 3 (variables, var_select, val_select)
-                let mut paren_depth = 1;
-                let mut arg_count = 0;
-                
-                while paren_depth > 0 && self.current_token.kind != TokenKind::Eof {
-                    match &self.current_token.kind {
-                        TokenKind::LParen => paren_depth += 1,
-                        TokenKind::RParen => paren_depth -= 1,
-                        TokenKind::Comma if paren_depth == 1 => arg_count += 1,
-                        _ => {}
-                    }
-                    
-                    // Check the 4th argument (index 3) for complete/incomplete
-                    if paren_depth == 1 && arg_count == 3 {
-                        if let TokenKind::Ident(opt) = &self.current_token.kind {
-                            let opt_str = opt.clone();
-                            if opt_str == "complete" {
-                                // Consume the rest of the annotation
-                                while self.current_token.kind != TokenKind::RParen && 
-                                      self.current_token.kind != TokenKind::Eof {
-                                    self.advance()?;
-                                }
-                                if self.current_token.kind == TokenKind::RParen {
-                                    self.advance()?;
-                                }
-                                return Ok(SearchOption::Complete);
-                            } else if opt_str == "incomplete" {
-                                // Consume the rest of the annotation
-                                while self.current_token.kind != TokenKind::RParen && 
-                                      self.current_token.kind != TokenKind::Eof {
-                                    self.advance()?;
-                                }
-                                if self.current_token.kind == TokenKind::RParen {
-                                    self.advance()?;
-                                }
-                                return Ok(SearchOption::Incomplete);
-                            }
-                        }
+    /// Interpret one parsed solve annotation
+    ///
+    /// `int_search`/`bool_search`/`float_search` become [`SearchAnnotation`]s,
+    /// `seq_search` contributes each of its inner searches, and a trailing
+    /// `complete`/`incomplete` (standalone or as the 4th search argument) sets the
+    /// search option. Unknown annotations are ignored.
+    fn collect_search_annotation(
+        &self,
+        annotation: &Expr,
+        search_option: &mut Option<SearchOption>,
+        search: &mut Vec<SearchAnnotation>,
+    ) {
+        match &annotation.kind {
+            ExprKind::Ident(name) if name == "complete" => *search_option = Some(SearchOption::Complete),
+            ExprKind::Ident(name) if name == "incomplete" => *search_option = Some(SearchOption::Incomplete),
+            ExprKind::Call { name, args } if name == "seq_search" => {
+                if let Some(ExprKind::ArrayLit(inner)) = args.first().map(|a| &a.kind) {
+                    for annotation in inner {
+                        self.collect_search_annotation(annotation, search_option, search);
                     }
-                    
-                    self.advance()?;
                 }
-                
-                // Default to complete if not specified
-                return Ok(SearchOption::Complete);
             }
+            ExprKind::Call { name, args }
+                if matches!(name.as_str(), "int_search" | "bool_search" | "float_search" | "set_search") =>
+            {
+                let strategy = |index: usize| match args.get(index).map(|a| &a.kind) {
+                    Some(ExprKind::Ident(name)) => Some(name.as_str()),
+                    _ => None,
+                };
+                // float_search takes a precision before the strategies
+                let offset = usize::from(name == "float_search");
+                if let (Some(variables), Some(var_select), Some(val_select)) =
+                    (args.first(), strategy(1 + offset), strategy(2 + offset))
+                {
+                    search.push(SearchAnnotation {
+                        variables: variables.clone(),
+                        var_select: VarSelection::from_name(var_select),
+                        val_select: ValueSelection::from_name(val_select),
+                        span: annotation.span,
+                    });
+                }
+                if let Some(option) = args.get(3 + offset) {
+                    self.collect_search_annotation(option, search_option, search);
+                }
+            }
+            _ => {}
         }
-        
-        // If it's not recognized, skip to next valid token and default to complete
-        while self.current_token.kind != TokenKind::Satisfy && 
-              self.current_token.kind != TokenKind::Minimize &&
-              self.current_token.kind != TokenKind::Maximize &&
-              self.current_token.kind != TokenKind::Eof {
-            self.advance()?;
-        }
-        
-        Ok(SearchOption::Complete)
     }
     
     /// Parse output item: `output ["x = ", show(x)];`
@@ -488,7 +611,13 @@ impl Parser {
                 TokenKind::Iff => BinOp::Iff,
                 TokenKind::Xor => BinOp::Xor,
                 TokenKind::In => BinOp::In,
+                TokenKind::Subset => BinOp::Subset,
+                TokenKind::Superset => BinOp::Superset,
+                TokenKind::Union => BinOp::Union,
+                TokenKind::Intersect => BinOp::Intersect,
+                TokenKind::Diff => BinOp::Diff,
                 TokenKind::DotDot => BinOp::Range,
+                TokenKind::PlusPlus => BinOp::Concat,
                 _ => break,
             };
             
@@ -524,10 +653,12 @@ impl Parser {
             BinOp::Xor => (6, 5),
             BinOp::And => (8, 7),
             BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge | BinOp::Eq | BinOp::Ne => (10, 9),
-            BinOp::In => (10, 9),
-            BinOp::Range => (12, 11),
-            BinOp::Add | BinOp::Sub => (14, 13),
-            BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::FDiv => (16, 15),
+            BinOp::In | BinOp::Subset | BinOp::Superset => (10, 9),
+            BinOp::Union | BinOp::Diff => (12, 11),
+            BinOp::Range => (14, 13),
+            BinOp::Add | BinOp::Sub => (16, 15),
+            BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::FDiv | BinOp::Intersect => (18, 17),
+            BinOp::Concat => (20, 19),
         }
     }
     
@@ -712,6 +843,12 @@ impl Parser {
                 self.advance()?;
                 ExprKind::StringLit(s)
             }
+            TokenKind::InterpolatedString(parts) => {
+                let span = self.current_token.span;
+                let expr = self.parse_interpolated_string(&parts, span)?;
+                self.advance()?;
+                return Ok(expr);
+            }
             TokenKind::Ident(name) => {
                 self.advance()?;
                 ExprKind::Ident(name)
@@ -728,6 +865,12 @@ impl Parser {
             TokenKind::LBrace => {
                 return self.parse_set_literal();
             }
+            TokenKind::If => {
+                return self.parse_if_then_else();
+            }
+            TokenKind::Let => {
+                return self.parse_let();
+            }
             _ => {
                 return Err(self.add_source_to_error(Error::unexpected_token(
                     "expression",
@@ -744,6 +887,123 @@ impl Parser {
         })
     }
     
+    /// Expand `"a \(e) b"` into the concatenation `["a ", show(e), " b"]`
+    ///
+    /// Each interpolated expression is parsed from its position in the source,
+    /// so spans inside it point at the original text.
+    fn parse_interpolated_string(&self, parts: &[StringPart], span: Span) -> Result<Expr> {
+        let mut elements = Vec::with_capacity(parts.len());
+        for part in parts {
+            match part {
+                StringPart::Text(text) => elements.push(Expr {
+                    kind: ExprKind::StringLit(text.clone()),
+                    span,
+                }),
+                StringPart::Expr(pos) => {
+                    let mut inner = Parser::new(self.lexer.at(*pos)).with_source(self.source.clone());
+                    let expr = inner.parse_expr()?;
+                    // Not `expect`: advancing would lex the string text that follows
+                    if inner.current_token.kind != TokenKind::RParen {
+                        return Err(inner.add_source_to_error(Error::unexpected_token(
+                            ")",
+                            &format!("{:?}", inner.current_token.kind),
+                            inner.current_token.span,
+                        )));
+                    }
+                    let expr_span = expr.span;
+                    elements.push(Expr {
+                        kind: ExprKind::Call { name: "show".to_string(), args: vec![expr] },
+                        span: expr_span,
+                    });
+                }
+            }
+        }
+        Ok(Expr { kind: ExprKind::ArrayLit(elements), span })
+    }
+
+    /// Parse `if c then e1 elseif c2 then e2 else e3 endif`
+    ///
+    /// `elseif` branches nest as if-then-else in the else position; the final
+    /// `else` is optional.
+    fn parse_if_then_else(&mut self) -> Result<Expr> {
+        let start = self.current_token.span.start;
+        // Consumes `if` or `elseif`
+        self.advance()?;
+        let cond = self.parse_expr()?;
+        self.expect(TokenKind::Then)?;
+        let then_expr = self.parse_expr()?;
+
+        let else_expr = match self.current_token.kind {
+            TokenKind::Elseif => {
+                // The nested conditional consumes the shared `endif`
+                let nested = self.parse_if_then_else()?;
+                return Ok(Expr {
+                    span: Span::new(start, nested.span.end),
+                    kind: ExprKind::IfThenElse {
+                        cond: Box::new(cond),
+                        then_expr: Box::new(then_expr),
+                        else_expr: Some(Box::new(nested)),
+                    },
+                });
+            }
+            TokenKind::Else => {
+                self.advance()?;
+                Some(Box::new(self.parse_expr()?))
+            }
+            _ => None,
+        };
+        let end = self.current_token.span.end;
+        self.expect(TokenKind::Endif)?;
+
+        Ok(Expr {
+            kind: ExprKind::IfThenElse {
+                cond: Box::new(cond),
+                then_expr: Box::new(then_expr),
+                else_expr,
+            },
+            span: Span::new(start, end),
+        })
+    }
+
+    /// Parse `let { var int: t = x + y; constraint t > 0 } in body`
+    ///
+    /// Items are separated by `;` or `,`, and a trailing separator is allowed.
+    fn parse_let(&mut self) -> Result<Expr> {
+        let start = self.current_token.span.start;
+        self.expect(TokenKind::Let)?;
+        self.expect(TokenKind::LBrace)?;
+
+        let mut decls = Vec::new();
+        let mut constraints = Vec::new();
+        while self.current_token.kind != TokenKind::RBrace {
+            if self.current_token.kind == TokenKind::Constraint {
+                self.advance()?;
+                constraints.push(self.parse_expr()?);
+            } else {
+                decls.push(self.parse_decl()?);
+            }
+            match self.current_token.kind {
+                TokenKind::Semicolon | TokenKind::Comma => self.advance()?,
+                TokenKind::RBrace => {}
+                _ => {
+                    return Err(self.add_source_to_error(Error::unexpected_token(
+                        "';' or '}' in let",
+                        &format!("{:?}", self.current_token.kind),
+                        self.current_token.span,
+                    )));
+                }
+            }
+        }
+        self.expect(TokenKind::RBrace)?;
+        self.expect(TokenKind::In)?;
+        let body = self.parse_expr()?;
+
+        Ok(Expr {
+            span: Span::new(start, body.span.end),
+            kind: ExprKind::Let { decls, constraints, body: Box::new(body) },
+        })
+    }
+
     /// Parse array literal or comprehension: `[1,2,3]` or `[i*2 | i in 1..n]`
     fn parse_array_literal_or_comp(&mut self) -> Result<Expr> {
         let start = self.current_token.span.start;
@@ -828,7 +1088,8 @@ impl Parser {
         }
         
         // Try to peek ahead to find 'in' keyword
-        // Simple heuristic: if we see ident followed by 'in', it's a generator
+        // Simple heuristic: if we see ident followed by 'in', it's a generator.
+        // Several names may share one range: `i, j in 1..n`
         let mut peek_lexer = self.lexer.clone();
         let mut depth = 0;
         
@@ -838,7 +1099,13 @@ impl Parser {
                     TokenKind::In if depth == 0 => return true,
                     TokenKind::LParen => depth += 1,
                     TokenKind::RParen if depth > 0 => depth -= 1,
-                    TokenKind::RParen | TokenKind::Comma if depth == 0 => return false,
+                    TokenKind::Comma if depth == 0 => {
+                        match peek_lexer.next_token() {
+                            Ok(Token { kind: TokenKind::Ident(_), .. }) => {}
+                            _ => return false,
+                        }
+                    }
+                    TokenKind::RParen if depth == 0 => return false,
                     TokenKind::Eof => return false,
                     _ => {}
                 }
@@ -990,6 +1257,27 @@ mod tests {
         assert_eq!(model.items.len(), 3);
     }
 
+    #[test]
+    fn test_multi_name_generator() {
+        let model = parse("constraint forall(i, j in 1..3)(x[i] != x[j]);").unwrap();
+        if let Item::Constraint(c) = &model.items[0] {
+            if let ExprKind::GenCall { generators, .. } = &c.expr.kind {
+                assert_eq!(generators.len(), 1);
+                assert_eq!(generators[0].names, vec!["i".to_string(), "j".to_string()]);
+            } else {
+                panic!("Expected generator call");
+            }
+        } else {
+            panic!("Expected constraint");
+        }
+
+        // Plain call arguments are not mistaken for generator names
+        let model = parse("constraint count(x, y) = 2;").unwrap();
+        if let Item::Constraint(c) = &model.items[0] {
+            assert!(matches!(&c.expr.kind, ExprKind::BinOp { left, .. } if matches!(left.kind, ExprKind::Call { .. })));
+        }
+    }
+
     #[test]
     fn test_implicit_index_array() {
         // Test array[int] syntax (implicitly-indexed arrays)
@@ -1011,4 +1299,232 @@ mod tests {
             panic!("Expected var decl");
         }
     }
+
+    #[test]
+    fn test_include() {
+        let model = parse("include \"globals.mzn\";\nvar 1..3: x;").unwrap();
+        assert_eq!(model.items.len(), 2);
+        if let Item::Include(include) = &model.items[0] {
+            assert_eq!(include.path, "globals.mzn");
+        } else {
+            panic!("Expected include");
+        }
+
+        assert!(parse("include globals;").is_err());
+    }
+
+    #[test]
+    fn test_set_operator_precedence() {
+        // `1..2 union 4..5 subset s` groups as `((1..2) union (4..5)) subset s`
+        let model = parse("constraint 1..2 union 4..5 subset s;").unwrap();
+        let Item::Constraint(c) = &model.items[0] else {
+            panic!("Expected constraint");
+        };
+        let ExprKind::BinOp { op: BinOp::Subset, left, .. } = &c.expr.kind else {
+            panic!("Expected subset at the top, got {:?}", c.expr.kind);
+        };
+        let ExprKind::BinOp { op: BinOp::Union, left, right } = &left.kind else {
+            panic!("Expected union under subset");
+        };
+        assert!(matches!(left.kind, ExprKind::BinOp { op: BinOp::Range, .. }));
+        assert!(matches!(right.kind, ExprKind::BinOp { op: BinOp::Range, .. }));
+
+        // intersect binds like multiplication, tighter than union
+        let model = parse("constraint s = a union b intersect c;").unwrap();
+        let Item::Constraint(c) = &model.items[0] else {
+            panic!("Expected constraint");
+        };
+        let ExprKind::BinOp { op: BinOp::Eq, right, .. } = &c.expr.kind else {
+            panic!("Expected equality");
+        };
+        let ExprKind::BinOp { op: BinOp::Union, right, .. } = &right.kind else {
+            panic!("Expected union");
+        };
+        assert!(matches!(right.kind, ExprKind::BinOp { op: BinOp::Intersect, .. }));
+    }
+
+    #[test]
+    fn test_search_annotation_structure() {
+        let model = parse(
+            "array[1..4] of var 1..4: q;\nsolve :: int_search(q, first_fail, indomain_min, complete) satisfy;",
+        )
+        .unwrap();
+        let Item::Solve(Solve::Satisfy { search_option, search, .. }) = &model.items[1] else {
+            panic!("Expected solve satisfy");
+        };
+        assert_eq!(search_option, &Some(SearchOption::Complete));
+        assert_eq!(search.len(), 1);
+        assert_eq!(search[0].var_select, VarSelection::FirstFail);
+        assert_eq!(search[0].val_select, ValueSelection::IndomainMin);
+        assert_eq!(search[0].variables.kind, ExprKind::Ident("q".to_string()));
+
+        // seq_search contributes each inner search; float_search skips its precision
+        let model = parse(
+            "solve :: seq_search([int_search(x, input_order, indomain_max), \
+             float_search(f, 0.01, smallest, indomain_split)]) minimize c;",
+        )
+        .unwrap();
+        let Item::Solve(Solve::Minimize { search, .. }) = &model.items[0] else {
+            panic!("Expected solve minimize");
+        };
+        assert_eq!(search.len(), 2);
+        assert_eq!(search[0].var_select, VarSelection::InputOrder);
+        assert_eq!(search[0].val_select, ValueSelection::IndomainMax);
+        assert_eq!(search[1].var_select, VarSelection::Smallest);
+        assert_eq!(search[1].val_select, ValueSelection::IndomainSplit);
+    }
+
+    #[test]
+    fn test_if_then_elseif_else() {
+        let model = parse("constraint if x = 1 then y = 2 elseif x = 2 then y = 3 else y = 1 endif;").unwrap();
+        let Item::Constraint(c) = &model.items[0] else {
+            panic!("Expected constraint");
+        };
+        let ExprKind::IfThenElse { else_expr: Some(nested), .. } = &c.expr.kind else {
+            panic!("Expected if-then-else, got {:?}", c.expr.kind);
+        };
+        // elseif nests as a conditional in the else position
+        assert!(matches!(nested.kind, ExprKind::IfThenElse { else_expr: Some(_), .. }));
+
+        let model = parse("constraint if p then q endif;").unwrap();
+        let Item::Constraint(c) = &model.items[0] else {
+            panic!("Expected constraint");
+        };
+        assert!(matches!(c.expr.kind, ExprKind::IfThenElse { else_expr: None, .. }));
+
+        assert!(parse("constraint if p then q;").is_err());
+    }
+
+    #[test]
+    fn test_block_comments_between_items() {
+        let source = r#"
+            /* Model header
+               spanning lines */
+            var 1..3: x; /* trailing /* nested */ note */
+            constraint x > /* inline */ 1;
+            % line comment
+            solve satisfy;
+        "#;
+        let model = parse(source).unwrap();
+        assert_eq!(model.items.len(), 3);
+        assert!(matches!(model.items[1], Item::Constraint(_)));
+
+        let err = parse("var 1..3: x; /* never closed\nsolve satisfy;").unwrap_err();
+        assert!(format!("{}", err).contains("Unterminated block comment"));
+    }
+
+    #[test]
+    fn test_domain_starting_with_identifier() {
+        let source = "enum Color = {Red, Green}; var n-1..2*n: x; var Color: c;";
+        let model = parse(source).unwrap();
+        match &model.items[1] {
+            Item::VarDecl(decl) => match &decl.type_inst {
+                TypeInst::Constrained { is_var: true, base_type: BaseType::Int, domain } => {
+                    assert!(matches!(domain.kind, ExprKind::BinOp { op: BinOp::Range, .. }));
+                }
+                other => panic!("Expected constrained int type, got {:?}", other),
+            },
+            _ => panic!("Expected var decl"),
+        }
+        match &model.items[2] {
+            Item::VarDecl(decl) => assert_eq!(
+                decl.type_inst,
+                TypeInst::Basic { is_var: true, base_type: BaseType::Enum("Color".to_string()) }
+            ),
+            _ => panic!("Expected var decl"),
+        }
+    }
+
+    #[test]
+    fn test_declaration_annotations() {
+        let source = "var 1..3: x :: output_var :: is_defined_var = 2; array[1..2] of var 0..1: b :: output_array([1..2]);";
+        let model = parse(source).unwrap();
+        match &model.items[0] {
+            Item::VarDecl(decl) => {
+                let names: Vec<&str> = decl.annotations.iter().map(|a| a.name.as_str()).collect();
+                assert_eq!(names, vec!["output_var", "is_defined_var"]);
+                assert!(matches!(decl.expr.as_ref().map(|e| &e.kind), Some(ExprKind::IntLit(2))));
+            }
+            _ => panic!("Expected var decl"),
+        }
+        match &model.items[1] {
+            Item::VarDecl(decl) => {
+                assert_eq!(decl.annotations[0].name, "output_array");
+                assert_eq!(decl.annotations[0].args.len(), 1);
+            }
+            _ => panic!("Expected var decl"),
+        }
+    }
+
+    #[test]
+    fn test_let_expression() {
+        let model = parse("constraint let { var int: t = x + y; constraint t > 0, } in t <= 10;").unwrap();
+        match &model.items[0] {
+            Item::Constraint(c) => match &c.expr.kind {
+                ExprKind::Let { decls, constraints, body } => {
+                    assert_eq!(decls.len(), 1);
+                    assert_eq!(decls[0].name, "t");
+                    assert!(decls[0].expr.is_some());
+                    assert_eq!(constraints.len(), 1);
+                    assert!(matches!(body.kind, ExprKind::BinOp { op: BinOp::Le, .. }));
+                }
+                other => panic!("Expected let, got {:?}", other),
+            },
+            _ => panic!("Expected constraint"),
+        }
+        assert!(parse("constraint let { var int: t = x } t > 0;").is_err());
+    }
+
+    #[test]
+    fn test_explicit_par_keyword() {
+        // Padded so the spans inside the type-inst line up
+        let pairs = [
+            ("par int: n = 5;", "    int: n = 5;"),
+            ("par bool: b = true;", "    bool: b = true;"),
+            ("par 1..3: k = 2;", "    1..3: k = 2;"),
+            ("array[1..2] of par 1..9: a = [1, 2];", "array[1..2] of     1..9: a = [1, 2];"),
+        ];
+        for (explicit, implicit) in pairs {
+            let type_inst = |source: &str| match &parse(source).unwrap().items[0] {
+                Item::VarDecl(decl) => decl.type_inst.clone(),
+                _ => panic!("Expected var decl"),
+            };
+            assert_eq!(type_inst(explicit), type_inst(implicit), "{}", explicit);
+        }
+    }
+
+    #[test]
+    fn test_predicate_definition() {
+        let source = "predicate within(var int: x, array[int] of var int: ys, int: d) = forall(y in ys)(abs(x - y) <= d);";
+        let model = parse(source).unwrap();
+        match &model.items[0] {
+            Item::Predicate(predicate) => {
+                assert_eq!(predicate.name, "within");
+                let names: Vec<&str> = predicate.params.iter().map(|p| p.name.as_str()).collect();
+                assert_eq!(names, ["x", "ys", "d"]);
+                assert!(matches!(predicate.params[0].type_inst, TypeInst::Basic { is_var: true, .. }));
+                assert!(matches!(predicate.params[1].type_inst, TypeInst::Array { .. }));
+                assert!(matches!(predicate.params[2].type_inst, TypeInst::Basic { is_var: false, .. }));
+                assert!(matches!(predicate.body.kind, ExprKind::GenCall { .. }));
+            }
+            other => panic!("Expected predicate, got {:?}", other),
+        }
+        assert!(parse("predicate p(var int: x);").is_err());
+    }
+
+    #[test]
+    fn test_function_definition() {
+        let model = parse("function var int: double(var int: x) = 2 * x;").unwrap();
+        match &model.items[0] {
+            Item::Function(function) => {
+                assert_eq!(function.name, "double");
+                assert!(matches!(function.return_type, TypeInst::Basic { is_var: true, base_type: BaseType::Int }));
+                assert_eq!(function.params.len(), 1);
+                assert_eq!(function.params[0].name, "x");
+                assert!(matches!(function.body.kind, ExprKind::BinOp { op: BinOp::Mul, .. }));
+            }
+            other => panic!("Expected function, got {:?}", other),
+        }
+        assert!(parse("function double(var int: x) = 2 * x;").is_err());
+    }
 }
@@ -3,21 +3,38 @@
 //! Translates a parsed MiniZinc AST into Selen Model objects for execution.
 
 use crate::ast::{self, Span};
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, Warning};
 use selen::prelude::*;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Metadata for multi-dimensional arrays to support flattening
 #[derive(Debug, Clone)]
 struct ArrayMetadata {
     /// Dimensions of the array (e.g., [3, 4] for a 3x4 2D array)
     dimensions: Vec<usize>,
+    /// First index of each dimension (1 for `1..n`, 0 for `0..n-1`)
+    starts: Vec<i32>,
 }
 
 impl ArrayMetadata {
-    /// Create metadata for a multi-dimensional array
+    /// Create metadata for a multi-dimensional array indexed from 1
     fn new(dimensions: Vec<usize>) -> Self {
-        Self { dimensions }
+        let starts = vec![1; dimensions.len()];
+        Self { dimensions, starts }
+    }
+
+    /// Create metadata for an array whose dimensions start at `starts`
+    fn with_starts(dimensions: Vec<usize>, starts: Vec<i32>) -> Self {
+        Self { dimensions, starts }
+    }
+
+    /// Zero-based position of `index` in dimension `dim`, if it is in range
+    fn offset(&self, dim: usize, index: i32) -> Option<usize> {
+        let offset = usize::try_from(i64::from(index) - i64::from(self.starts[dim])).ok()?;
+        (offset < self.dimensions[dim]).then_some(offset)
     }
 
     /// Total number of elements
@@ -27,7 +44,7 @@ impl ArrayMetadata {
 
     /// Flatten multi-dimensional indices to a single 1D index
     /// indices should be 0-based, and we return the 0-based flattened index
-    fn flatten_indices(&self, indices: &[usize]) -> Result<usize> {
+    fn flatten_indices(&self, indices: &[usize], span: ast::Span) -> Result<usize> {
         if indices.len() != self.dimensions.len() {
             return Err(Error::message(
                 &format!(
@@ -35,7 +52,7 @@ impl ArrayMetadata {
                     self.dimensions.len(),
                     indices.len()
                 ),
-                ast::Span::dummy(),
+                span,
             ));
         }
 
@@ -50,7 +67,7 @@ impl ArrayMetadata {
                         "Array index {} out of bounds for dimension {} (size: {})",
                         indices[i], i, self.dimensions[i]
                     ),
-                    ast::Span::dummy(),
+                    span,
                 ));
             }
             flat_index += indices[i] * multiplier;
@@ -61,6 +78,51 @@ impl ArrayMetadata {
     }
 }
 
+/// Set decision variable encoded as one membership boolean per universe element
+#[derive(Debug, Clone)]
+struct SetVar {
+    /// Smallest element of the universe; `indicators[0]` refers to this value
+    min: i32,
+    /// `indicators[k]` is true iff `min + k` is a member of the set
+    indicators: Vec<VarId>,
+}
+
+impl SetVar {
+    /// Iterate over (element, membership indicator) pairs
+    fn members(&self) -> impl Iterator<Item = (i32, VarId)> + '_ {
+        self.indicators
+            .iter()
+            .enumerate()
+            .map(move |(k, &ind)| (self.min + k as i32, ind))
+    }
+
+    /// Membership indicator of `value`, if it lies in the universe
+    fn indicator(&self, value: i32) -> Option<VarId> {
+        let offset = value.checked_sub(self.min)?;
+        usize::try_from(offset).ok().and_then(|k| self.indicators.get(k).copied())
+    }
+
+    /// Largest element of the universe
+    fn max(&self) -> i32 {
+        self.min + self.indicators.len() as i32 - 1
+    }
+}
+
+/// MiniZinc library files; the globals they declare are implemented natively,
+/// so including them needs no file
+const LIBRARY_INCLUDES: &[&str] = &[
+    "all_different.mzn", "all_equal.mzn", "alldifferent.mzn", "alldifferent_except_0.mzn",
+    "among.mzn", "arg_sort.mzn", "at_least.mzn", "at_most.mzn", "bin_packing.mzn",
+    "bin_packing_load.mzn", "builtins.mzn", "circuit.mzn", "count.mzn", "count_eq.mzn",
+    "count_geq.mzn", "count_gt.mzn", "count_leq.mzn", "count_lt.mzn", "count_neq.mzn",
+    "cumulative.mzn", "decreasing.mzn", "diffn.mzn", "disjoint.mzn", "disjunctive.mzn",
+    "element.mzn", "exactly.mzn", "global_cardinality.mzn", "globals.mzn", "increasing.mzn",
+    "inverse.mzn", "knapsack.mzn", "lex_less.mzn", "lex_lesseq.mzn", "maximum.mzn",
+    "member.mzn", "minimum.mzn", "nvalue.mzn", "partition_set.mzn", "regular.mzn",
+    "sort.mzn", "stdlib.mzn", "strictly_decreasing.mzn", "strictly_increasing.mzn",
+    "subcircuit.mzn", "table.mzn", "value_precede.mzn", "value_precede_chain.mzn",
+];
+
 /// Context for tracking variables during translation
 #[derive(Debug)]
 struct TranslatorContext {
@@ -94,16 +156,26 @@ struct TranslatorContext {
     float_params: HashMap<String, f64>,
     /// Bool parameters
     bool_params: HashMap<String, bool>,
-    /// Parameter arrays (integer constants)
-    int_param_arrays: HashMap<String, Vec<i32>>,
+    /// Parameter arrays (integer constants), shared with seeded option tables
+    int_param_arrays: HashMap<String, Arc<[i32]>>,
     /// Float parameter arrays
-    float_param_arrays: HashMap<String, Vec<f64>>,
+    float_param_arrays: HashMap<String, Arc<[f64]>>,
     /// Bool parameter arrays
     bool_param_arrays: HashMap<String, Vec<bool>>,
     /// Metadata for multi-dimensional arrays (name -> dimensions)
     array_metadata: HashMap<String, ArrayMetadata>,
     /// Enumerated type definitions: enum_name -> list of values
     enums: HashMap<String, Vec<String>>,
+    /// User-defined predicates, inlined at each call
+    predicates: HashMap<String, ast::Predicate>,
+    /// User-defined functions, inlined at each call
+    functions: HashMap<String, ast::Function>,
+    /// Set decision variables (var set of int)
+    set_vars: HashMap<String, SetVar>,
+    /// Arrays of set decision variables (flattened)
+    set_var_arrays: HashMap<String, Vec<SetVar>>,
+    /// Fixed integer variables shared by every use of the same constant value
+    int_constants: HashMap<i32, VarId>,
 }
 
 impl TranslatorContext {
@@ -129,7 +201,36 @@ impl TranslatorContext {
             bool_param_arrays: HashMap::new(),
             array_metadata: HashMap::new(),
             enums: HashMap::new(),
+            predicates: HashMap::new(),
+            functions: HashMap::new(),
+            set_vars: HashMap::new(),
+            set_var_arrays: HashMap::new(),
+            int_constants: HashMap::new(),
+        }
+    }
+
+    /// Remove every binding of `name`, returning closures that put them back
+    fn unbind(&mut self, name: &str) -> Vec<Rebind> {
+        let mut rebinds: Vec<Rebind> = Vec::new();
+        macro_rules! take {
+            ($($map:ident),*) => {$(
+                if let Some(value) = self.$map.remove(name) {
+                    let name = name.to_string();
+                    rebinds.push(Box::new(move |ctx: &mut TranslatorContext| {
+                        ctx.$map.insert(name, value);
+                    }));
+                }
+            )*};
         }
+        take!(
+            int_vars, int_var_arrays, int_var_arrays_2d, int_var_arrays_3d,
+            bool_vars, bool_var_arrays, bool_var_arrays_2d, bool_var_arrays_3d,
+            float_vars, float_var_arrays, float_var_arrays_2d, float_var_arrays_3d,
+            int_params, float_params, bool_params,
+            int_param_arrays, float_param_arrays, bool_param_arrays,
+            array_metadata, set_vars, set_var_arrays
+        );
+        rebinds
     }
 
     fn add_int_var(&mut self, name: String, var: VarId) {
@@ -209,64 +310,40 @@ impl TranslatorContext {
         self.int_var_arrays_2d.insert(name, vars);
     }
 
-    fn get_int_var_array_2d(&self, name: &str) -> Option<&Vec<Vec<VarId>>> {
-        self.int_var_arrays_2d.get(name)
-    }
-
     fn add_bool_var_array_2d(&mut self, name: String, vars: Vec<Vec<VarId>>) {
         self.bool_var_arrays_2d.insert(name, vars);
     }
 
-    fn get_bool_var_array_2d(&self, name: &str) -> Option<&Vec<Vec<VarId>>> {
-        self.bool_var_arrays_2d.get(name)
-    }
-
     fn add_float_var_array_2d(&mut self, name: String, vars: Vec<Vec<VarId>>) {
         self.float_var_arrays_2d.insert(name, vars);
     }
 
-    fn get_float_var_array_2d(&self, name: &str) -> Option<&Vec<Vec<VarId>>> {
-        self.float_var_arrays_2d.get(name)
-    }
-
     // 3D array methods
     fn add_int_var_array_3d(&mut self, name: String, vars: Vec<Vec<Vec<VarId>>>) {
         self.int_var_arrays_3d.insert(name, vars);
     }
 
-    fn get_int_var_array_3d(&self, name: &str) -> Option<&Vec<Vec<Vec<VarId>>>> {
-        self.int_var_arrays_3d.get(name)
-    }
-
     fn add_bool_var_array_3d(&mut self, name: String, vars: Vec<Vec<Vec<VarId>>>) {
         self.bool_var_arrays_3d.insert(name, vars);
     }
 
-    fn get_bool_var_array_3d(&self, name: &str) -> Option<&Vec<Vec<Vec<VarId>>>> {
-        self.bool_var_arrays_3d.get(name)
-    }
-
     fn add_float_var_array_3d(&mut self, name: String, vars: Vec<Vec<Vec<VarId>>>) {
         self.float_var_arrays_3d.insert(name, vars);
     }
 
-    fn get_float_var_array_3d(&self, name: &str) -> Option<&Vec<Vec<Vec<VarId>>>> {
-        self.float_var_arrays_3d.get(name)
-    }
-
-    fn add_int_param_array(&mut self, name: String, values: Vec<i32>) {
-        self.int_param_arrays.insert(name, values);
+    fn add_int_param_array(&mut self, name: String, values: impl Into<Arc<[i32]>>) {
+        self.int_param_arrays.insert(name, values.into());
     }
 
-    fn get_int_param_array(&self, name: &str) -> Option<&Vec<i32>> {
+    fn get_int_param_array(&self, name: &str) -> Option<&Arc<[i32]>> {
         self.int_param_arrays.get(name)
     }
 
-    fn add_float_param_array(&mut self, name: String, values: Vec<f64>) {
-        self.float_param_arrays.insert(name, values);
+    fn add_float_param_array(&mut self, name: String, values: impl Into<Arc<[f64]>>) {
+        self.float_param_arrays.insert(name, values.into());
     }
 
-    fn get_float_param_array(&self, name: &str) -> Option<&Vec<f64>> {
+    fn get_float_param_array(&self, name: &str) -> Option<&Arc<[f64]>> {
         self.float_param_arrays.get(name)
     }
 
@@ -277,18 +354,165 @@ impl TranslatorContext {
     fn get_bool_param_array(&self, name: &str) -> Option<&Vec<bool>> {
         self.bool_param_arrays.get(name)
     }
+
+    /// Set variable referenced by `s` or `arr[i]` with a constant index
+    fn resolve_set_var(&self, expr: &ast::Expr, index_of: impl Fn(&ast::Expr) -> Option<i32>) -> Option<&SetVar> {
+        match &expr.kind {
+            ast::ExprKind::Ident(name) => self.set_vars.get(name),
+            ast::ExprKind::ArrayAccess { array, indices } if indices.len() == 1 => {
+                let ast::ExprKind::Ident(name) = &array.kind else {
+                    return None;
+                };
+                let index = index_of(&indices[0])?;
+                if index < 1 {
+                    return None;
+                }
+                self.set_var_arrays.get(name)?.get((index - 1) as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// First index of a one-dimensional array (1 unless declared otherwise)
+    fn array_start(&self, name: &str) -> i32 {
+        self.array_metadata.get(name).and_then(|m| m.starts.first()).copied().unwrap_or(1)
+    }
+
+    /// Flattened length of a declared variable or parameter array
+    fn array_len(&self, name: &str) -> Option<usize> {
+        self.int_var_arrays.get(name).map(Vec::len)
+            .or_else(|| self.bool_var_arrays.get(name).map(Vec::len))
+            .or_else(|| self.float_var_arrays.get(name).map(Vec::len))
+            .or_else(|| self.int_param_arrays.get(name).map(|values| values.len()))
+            .or_else(|| self.bool_param_arrays.get(name).map(Vec::len))
+            .or_else(|| self.float_param_arrays.get(name).map(|values| values.len()))
+            .or_else(|| self.set_var_arrays.get(name).map(Vec::len))
+    }
+}
+
+/// Optional translation behaviours that trade model size for search speed
+///
+/// # Example
+///
+/// ```
+/// use zelen::TranslatorOptions;
+///
+/// let options = TranslatorOptions::default().with_redundant_permutation_sums(true);
+/// assert!(options.redundant_permutation_sums);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TranslatorOptions {
+    /// Post `sum(x) = n*lo + n*(n-1)/2` next to `alldifferent(x)` when the
+    /// array is a permutation of its shared domain `lo..lo+n-1`
+    pub redundant_permutation_sums: bool,
+    /// Ignore `int_search`/`bool_search` annotations on the solve item
+    pub free_search: bool,
+    /// Print `trace(...)` messages to stderr
+    pub verbose: bool,
+    /// Integer parameter arrays known before translation, by name; shared,
+    /// not copied, by every translation that uses these options
+    pub int_param_arrays: HashMap<String, Arc<[i32]>>,
+    /// Float parameter arrays known before translation, by name
+    pub float_param_arrays: HashMap<String, Arc<[f64]>>,
+    /// Print arrays as `[index: value, ...]` pairs in solution output
+    pub indexed_output: bool,
+}
+
+impl TranslatorOptions {
+    /// Enable redundant sum constraints for permutation `alldifferent`s
+    pub fn with_redundant_permutation_sums(mut self, enabled: bool) -> Self {
+        self.redundant_permutation_sums = enabled;
+        self
+    }
+
+    /// Ignore the model's search annotations
+    pub fn with_free_search(mut self, enabled: bool) -> Self {
+        self.free_search = enabled;
+        self
+    }
+
+    /// Print `trace(...)` messages while translating
+    pub fn with_verbose(mut self, enabled: bool) -> Self {
+        self.verbose = enabled;
+        self
+    }
+
+    /// Print arrays in solution output as index-value pairs, using the
+    /// declared index sets
+    pub fn with_indexed_output(mut self, enabled: bool) -> Self {
+        self.indexed_output = enabled;
+        self
+    }
+
+    /// Pre-seed an integer parameter array
+    ///
+    /// Models can use `name` directly or declare it without an initializer,
+    /// e.g. `array[1..n] of int: name;`, to give it an index set. Seeding a
+    /// large table once avoids re-parsing its literal for every model.
+    pub fn with_int_param_array(mut self, name: &str, values: impl Into<Arc<[i32]>>) -> Self {
+        self.int_param_arrays.insert(name.to_string(), values.into());
+        self
+    }
+
+    /// Pre-seed a float parameter array (see [`Self::with_int_param_array`])
+    pub fn with_float_param_array(mut self, name: &str, values: impl Into<Arc<[f64]>>) -> Self {
+        self.float_param_arrays.insert(name.to_string(), values.into());
+        self
+    }
 }
 
+/// Puts back a binding that a `let` declaration shadowed
+type Rebind = Box<dyn FnOnce(&mut TranslatorContext)>;
+
+/// Callback invoked once per generator binding with the substituted body and
+/// the set-membership guards active for that binding
+type GeneratorVisitor<'a> = dyn FnMut(&mut Translator, &ast::Expr, &[VarId]) -> Result<()> + 'a;
+
 /// Main translator struct
 pub struct Translator {
     model: selen::model::Model,
     context: TranslatorContext,
+    options: TranslatorOptions,
     objective_type: ObjectiveType,
     objective_var: Option<VarId>,
+    /// See [`TranslatedModel::objective_open`]
+    objective_open: bool,
+    /// Int variables and arrays declared without bounds, and scalars defined from them
+    unbounded_ints: HashSet<String>,
     output_items: Vec<ast::Expr>,
     search_option: Option<ast::SearchOption>,
+    search_heuristics: Vec<SearchHeuristic>,
+    /// Constant bounds on scalar int variables found in constraint items, keyed by
+    /// variable name: (constraint item index, `var op bound`)
+    pending_bounds: HashMap<String, Vec<(usize, ast::BinOp, ast::Expr)>>,
+    /// Number of bounds of each constraint item not yet folded into a domain
+    unfolded_bounds: HashMap<usize, usize>,
     /// Map from variable name to (enum_name, enum_values) for output formatting
     enum_var_mapping: HashMap<String, (String, Vec<String>)>,
+    /// Names declared with `:: output_var` / `:: output_array(...)`, in order
+    output_vars: Vec<String>,
+    /// Constructs that were approximated or dropped, in translation order
+    warnings: Vec<Warning>,
+    /// `:: domain` / `:: bounds` on the constraint item being translated, until
+    /// a global constraint that honours it takes it
+    consistency: Option<ast::Annotation>,
+    /// Predicates and functions whose bodies are being inlined, outermost first
+    inlining: Vec<String>,
+    /// Explicit domain for int variables declared without bounds, in place of
+    /// Selen's fallback bound
+    open_int_domain: Option<(i32, i32)>,
+}
+
+/// A search annotation resolved to the Selen variables it branches on
+///
+/// Selen 0.15 always uses its own branching order and exposes no way to pick
+/// a variable or value heuristic, so these are recorded for callers (and the
+/// `--verbose` CLI report) rather than applied to the search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHeuristic {
+    pub vars: Vec<VarId>,
+    pub var_select: ast::VarSelection,
+    pub val_select: ast::ValueSelection,
 }
 
 /// Optimization objective type for the solver
@@ -337,17 +561,53 @@ pub struct TranslatedModel {
     pub float_vars: HashMap<String, VarId>,
     /// Mapping from float array variable names to their VarId vectors
     pub float_var_arrays: HashMap<String, Vec<VarId>>,
+    /// Mapping from set variable names to their (element, membership indicator) pairs
+    pub set_vars: HashMap<String, Vec<(i32, VarId)>>,
     /// Type of optimization goal (satisfy, minimize, or maximize)
     pub objective_type: ObjectiveType,
     /// Variable ID of the objective (for minimize/maximize problems)
     pub objective_var: Option<VarId>,
+    /// Whether the objective mentions an integer variable declared without
+    /// bounds, so the solver's fallback bound may stand in for its optimum
+    pub objective_open: bool,
     /// Output expressions from output items (stored as AST for formatting during solution)
     pub output_items: Vec<ast::Expr>,
     /// Search option from solve item (complete vs incomplete)
     pub search_option: Option<ast::SearchOption>,
+    /// Search annotations from the solve item, empty under free search
+    pub search_heuristics: Vec<SearchHeuristic>,
     /// Enum definitions: maps variable name to (enum_name, enum_values)
     /// Used for output formatting to convert integers back to enum names
     pub enum_vars: HashMap<String, (String, Vec<String>)>,
+    /// Variables annotated `:: output_var` or `:: output_array(...)`, in
+    /// declaration order; when any exist, only these are printed by default
+    pub output_vars: Vec<String>,
+    /// Constructs the translator approximated or dropped, with their spans
+    pub warnings: Vec<Warning>,
+    /// Declared index set of each array, one `(first, last)` range per dimension
+    pub array_index_sets: HashMap<String, Vec<(i32, i32)>>,
+    /// Whether arrays are printed as index-value pairs (see
+    /// [`TranslatorOptions::with_indexed_output`])
+    pub indexed_output: bool,
+    /// Reverse index from `VarId` to MiniZinc name, built once for [`Self::name_of`]
+    var_names: HashMap<VarId, String>,
+}
+
+/// Declared indices of the element at flattened position `flat`
+///
+/// The last dimension varies fastest, so its index is peeled off first.
+fn declared_index(ranges: &[(i32, i32)], flat: usize) -> Vec<i32> {
+    let mut rest = flat;
+    let mut index: Vec<i32> = ranges.iter().rev()
+        .map(|&(first, last)| {
+            let size = (last - first + 1).max(1) as usize;
+            let position = first + (rest % size) as i32;
+            rest /= size;
+            position
+        })
+        .collect();
+    index.reverse();
+    index
 }
 
 impl TranslatedModel {
@@ -388,6 +648,12 @@ impl TranslatedModel {
                 }
                 Ok(result)
             }
+            ast::ExprKind::BinOp { op: ast::BinOp::Concat, left, right } => {
+                // String concatenation: "x=" ++ show(x) ++ "\n"
+                let mut result = self.format_expr(left, solution)?;
+                result.push_str(&self.format_expr(right, solution)?);
+                Ok(result)
+            }
             ast::ExprKind::Call { name, args } if name == "show" => {
                 // show() function - convert variable/array to string representation
                 if args.is_empty() {
@@ -397,7 +663,7 @@ impl TranslatedModel {
             }
             ast::ExprKind::Ident(var_name) => {
                 // Direct variable reference - get its value
-                self.format_variable(var_name, solution)
+                self.format_variable(var_name, solution, expr.span)
             }
             _ => {
                 // For other expressions, try to evaluate them
@@ -409,17 +675,73 @@ impl TranslatedModel {
         }
     }
 
+    /// Render a solution the way the CLI prints it: the text of the model's
+    /// output items, or the dzn-style `name = value;` lines of
+    /// [`Self::format_default`] when it has none (or they cannot be formatted)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let ast = zelen::parse("var 1..3: x; constraint x > 2; solve satisfy;").unwrap();
+    /// let mut model_data = zelen::Translator::translate_with_vars(&ast).unwrap();
+    /// let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+    /// assert_eq!(model_data.pretty_print(&solution), "x = 3;\n");
+    /// ```
+    pub fn pretty_print(&self, solution: &selen::prelude::Solution) -> String {
+        self.pretty_print_with_source(solution).0
+    }
+
+    /// [`Self::pretty_print`], also reporting whether the text came from the
+    /// model's output items rather than [`Self::format_default`]
+    pub fn pretty_print_with_source(&self, solution: &selen::prelude::Solution) -> (String, bool) {
+        match self.format_output(solution) {
+            Some(output) => (output, true),
+            None => (self.format_default(solution), false),
+        }
+    }
+
+    /// Default solution output for models without an output item: one
+    /// `name = value;` line per variable, sorted by name
+    ///
+    /// Only the `output_var`/`output_array` annotated variables are listed when
+    /// the model has any, in declaration order; otherwise every named variable is.
+    pub fn format_default(&self, solution: &selen::prelude::Solution) -> String {
+        let names: Vec<&String> = if self.output_vars.is_empty() {
+            let mut names: Vec<&String> = self.int_vars.keys()
+                .chain(self.bool_vars.keys())
+                .chain(self.float_vars.keys())
+                .chain(self.int_var_arrays.keys())
+                .chain(self.bool_var_arrays.keys())
+                .chain(self.set_vars.keys())
+                .chain(self.float_var_arrays.keys())
+                .collect();
+            names.sort();
+            names
+        } else {
+            self.output_vars.iter().collect()
+        };
+
+        let mut result = String::new();
+        for name in names {
+            // Annotated parameters have no value in the solution
+            if let Ok(value) = self.format_variable(name, solution, Span::dummy()) {
+                result.push_str(&format!("{} = {};\n", name, value));
+            }
+        }
+        result
+    }
+
     /// Format the argument to show() function
     fn format_show_arg(&self, arg: &ast::Expr, solution: &selen::prelude::Solution) -> Result<String> {
         match &arg.kind {
             ast::ExprKind::Ident(var_name) => {
                 // show(x) or show(array)
-                self.format_variable(var_name, solution)
+                self.format_variable(var_name, solution, arg.span)
             }
             ast::ExprKind::ArrayAccess { array, indices } => {
                 // show(array[i]) - access and format specific element
                 if let ast::ExprKind::Ident(array_name) = &array.kind {
-                    self.format_array_access(array_name, indices, solution)
+                    self.format_array_access(array_name, indices, solution, arg.span)
                 } else {
                     Err(Error::message(
                         "Complex array access in show() not supported",
@@ -435,10 +757,10 @@ impl TranslatedModel {
     }
 
     /// Format a variable or array value
-    fn format_variable(&self, var_name: &str, solution: &selen::prelude::Solution) -> Result<String> {
+    fn format_variable(&self, var_name: &str, solution: &selen::prelude::Solution, span: Span) -> Result<String> {
         // Try integer variable
         if let Some(&var_id) = self.int_vars.get(var_name) {
-            return Ok(solution.get_int(var_id).to_string());
+            return Ok(self.format_int_value(var_name, solution.get_int(var_id)));
         }
 
         // Try boolean variable (format as 0/1)
@@ -454,49 +776,213 @@ impl TranslatedModel {
 
         // Try integer array
         if let Some(var_ids) = self.int_var_arrays.get(var_name) {
-            return Ok(self.format_array(var_ids, solution, false, false));
+            let values: Vec<String> = var_ids
+                .iter()
+                .map(|&var_id| self.format_int_value(var_name, solution.get_int(var_id)))
+                .collect();
+            return Ok(self.format_array(var_name, values));
         }
 
         // Try boolean array (format as 0/1)
         if let Some(var_ids) = self.bool_var_arrays.get(var_name) {
-            return Ok(self.format_array(var_ids, solution, true, false));
+            let values = var_ids.iter().map(|&var_id| solution.get_int(var_id).to_string()).collect();
+            return Ok(self.format_array(var_name, values));
         }
 
         // Try float array
         if let Some(var_ids) = self.float_var_arrays.get(var_name) {
-            return Ok(self.format_array(var_ids, solution, false, true));
+            let values = var_ids.iter().map(|&var_id| solution.get_float(var_id).to_string()).collect();
+            return Ok(self.format_array(var_name, values));
+        }
+
+        // Try set variable (format as a set literal of its members)
+        if let Some(members) = self.set_vars.get(var_name) {
+            return Ok(Self::format_set(members, solution));
         }
 
         Err(Error::message(
             &format!("Undefined variable in output: '{}'", var_name),
-            Span::new(0, 0),
+            span,
         ))
     }
 
-    /// Format an array value
-    fn format_array(
-        &self,
-        var_ids: &[VarId],
-        solution: &selen::prelude::Solution,
-        _is_bool: bool,
-        is_float: bool,
-    ) -> String {
-        let mut result = String::from("[");
-        
-        for (i, var_id) in var_ids.iter().enumerate() {
-            if i > 0 {
-                result.push_str(", ");
+    /// Human-readable summary of the translated model: every named variable with
+    /// its domain, then the number of posted constraints and a count of each
+    /// constraint kind, including those Selen has not yet materialized
+    ///
+    /// Variables are listed by name; arrays whose elements share a domain are
+    /// shown as `array[n] of <domain>`.
+    pub fn summary(&self) -> String {
+        let vars = self.model.get_vars();
+        let domain = |var: VarId| match &vars[var] {
+            selen::variables::Var::VarI(domain) => format!("{}..{}", domain.min(), domain.max()),
+            selen::variables::Var::VarF(domain) => format!("{:?}..{:?}", domain.min, domain.max),
+        };
+        let array = |var_ids: &[VarId], kind: &str| {
+            let domains: Vec<String> = var_ids.iter().map(|&var| domain(var)).collect();
+            match domains.first() {
+                Some(first) if domains.iter().all(|d| d == first) => {
+                    format!("array[{}] of {} {}", domains.len(), kind, first)
+                }
+                _ => format!("array[{}] of {} [{}]", domains.len(), kind, domains.join(", ")),
             }
-            
-            if is_float {
-                result.push_str(&solution.get_float(*var_id).to_string());
-            } else {
-                result.push_str(&solution.get_int(*var_id).to_string());
+        };
+
+        let mut lines = Vec::new();
+        for (name, &var) in &self.int_vars {
+            match self.enum_vars.get(name) {
+                Some((enum_name, _)) => lines.push(format!("{}: {}", name, enum_name)),
+                None => lines.push(format!("{}: int {}", name, domain(var))),
             }
         }
-        
-        result.push(']');
-        result
+        for name in self.bool_vars.keys() {
+            lines.push(format!("{}: bool", name));
+        }
+        for (name, &var) in &self.float_vars {
+            lines.push(format!("{}: float {}", name, domain(var)));
+        }
+        for (name, var_ids) in &self.int_var_arrays {
+            match self.enum_vars.get(name) {
+                Some((enum_name, _)) => lines.push(format!("{}: array[{}] of {}", name, var_ids.len(), enum_name)),
+                None => lines.push(format!("{}: {}", name, array(var_ids, "int"))),
+            }
+        }
+        for (name, var_ids) in &self.bool_var_arrays {
+            lines.push(format!("{}: array[{}] of bool", name, var_ids.len()));
+        }
+        for (name, var_ids) in &self.float_var_arrays {
+            lines.push(format!("{}: {}", name, array(var_ids, "float")));
+        }
+        for (name, members) in &self.set_vars {
+            let universe: Vec<String> = members.iter().map(|(value, _)| value.to_string()).collect();
+            lines.push(format!("{}: set of {{{}}}", name, universe.join(", ")));
+        }
+        lines.sort();
+
+        let mut summary = format!("Variables ({} named, {} in Selen):\n", lines.len(), self.model.variable_count());
+        for line in &lines {
+            summary.push_str(&format!("  {}\n", line));
+        }
+
+        let registry = self.model.get_constraint_registry();
+        let mut kinds: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+        for id in registry.get_all_constraint_ids() {
+            if let Some(metadata) = registry.get_constraint(id) {
+                *kinds.entry(format!("{:?}", metadata.constraint_type)).or_default() += 1;
+            }
+        }
+        // Constraints built with `model.new(..)` wait in the model until solving
+        for kind in &self.model.pending_constraint_asts {
+            let debug = format!("{:?}", kind);
+            let variant = debug.split(|c: char| !c.is_alphanumeric()).next().unwrap_or_default();
+            *kinds.entry(variant.to_string()).or_default() += 1;
+        }
+        let posted = self.model.constraint_count() + self.model.pending_constraint_asts.len();
+        summary.push_str(&format!("Constraints ({} posted):\n", posted));
+        for (kind, count) in &kinds {
+            summary.push_str(&format!("  {}: {}\n", kind, count));
+        }
+        summary
+    }
+
+    /// Format the value of an integer variable or array element, using the enum
+    /// value name when `name` is enum-typed
+    pub fn format_int_value(&self, name: &str, value: i32) -> String {
+        match self.enum_vars.get(name) {
+            Some((_, values)) if value >= 1 && value as usize <= values.len() => values[value as usize - 1].clone(),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Name of the MiniZinc variable a `VarId` was created for
+    ///
+    /// Scalars are reported by name and array elements as `name[i]` or
+    /// `name[i, j]` with their declared indices. Scalars take precedence when a
+    /// `VarId` is shared, e.g. by `array[1..2] of var int: a = [x, y]`.
+    pub fn name_of(&self, var_id: VarId) -> Option<String> {
+        self.var_names.get(&var_id).cloned()
+    }
+
+    /// Build the reverse index behind [`Self::name_of`]
+    ///
+    /// Names are visited in sorted order so a `VarId` shared by several
+    /// scalars or arrays always gets the same name.
+    fn index_var_names(&mut self) {
+        let mut names = HashMap::new();
+        let mut scalars: Vec<(&String, &VarId)> = [&self.int_vars, &self.bool_vars, &self.float_vars]
+            .into_iter()
+            .flat_map(|vars| vars.iter())
+            .collect();
+        scalars.sort_by_key(|&(name, _)| name);
+        for (name, &var_id) in scalars {
+            names.entry(var_id).or_insert_with(|| name.clone());
+        }
+        let mut arrays: Vec<(&String, &Vec<VarId>)> = [&self.int_var_arrays, &self.bool_var_arrays, &self.float_var_arrays]
+            .into_iter()
+            .flat_map(|arrays| arrays.iter())
+            .collect();
+        arrays.sort_by_key(|&(name, _)| name);
+        for (name, var_ids) in arrays {
+            let ranges = self.array_ranges(name, var_ids.len());
+            for (flat, &var_id) in var_ids.iter().enumerate() {
+                names.entry(var_id).or_insert_with(|| {
+                    let index: Vec<String> = declared_index(&ranges, flat).iter().map(i32::to_string).collect();
+                    format!("{}[{}]", name, index.join(", "))
+                });
+            }
+        }
+        self.var_names = names;
+    }
+
+    /// Declared index ranges of an array, or `1..len` when none were recorded
+    fn array_ranges(&self, array_name: &str, len: usize) -> Vec<(i32, i32)> {
+        self.array_index_sets.get(array_name).cloned()
+            .unwrap_or_else(|| vec![(1, len as i32)])
+    }
+
+    /// Format the objective value as `_objective = V;` for minimize/maximize models
+    pub fn format_objective(&self, solution: &selen::prelude::Solution) -> Option<String> {
+        if self.objective_type == ObjectiveType::Satisfy {
+            return None;
+        }
+        let value = match solution[self.objective_var?] {
+            Val::ValI(value) => value.to_string(),
+            Val::ValF(value) => value.to_string(),
+        };
+        Some(format!("_objective = {};", value))
+    }
+
+    /// Format the members of a set variable as `{1, 3}`
+    pub fn format_set(members: &[(i32, VarId)], solution: &selen::prelude::Solution) -> String {
+        let values: Vec<String> = members
+            .iter()
+            .filter(|&&(_, indicator)| solution.get_int(indicator) == 1)
+            .map(|(value, _)| value.to_string())
+            .collect();
+        format!("{{{}}}", values.join(", "))
+    }
+
+    /// Format the flattened element values of an array in declared index order
+    ///
+    /// With [`Self::indexed_output`] each value is preceded by its index in the
+    /// declared index sets, e.g. `[0: 3, 1: 1]` or `[(1, 1): 3, (1, 2): 1]`.
+    fn format_array(&self, array_name: &str, values: Vec<String>) -> String {
+        if !self.indexed_output {
+            return format!("[{}]", values.join(", "));
+        }
+        let ranges = self.array_ranges(array_name, values.len());
+        let pairs: Vec<String> = values
+            .into_iter()
+            .enumerate()
+            .map(|(flat, value)| {
+                let index: Vec<String> = declared_index(&ranges, flat).iter().map(i32::to_string).collect();
+                match index.as_slice() {
+                    [single] => format!("{}: {}", single, value),
+                    _ => format!("({}): {}", index.join(", "), value),
+                }
+            })
+            .collect();
+        format!("[{}]", pairs.join(", "))
     }
 
     /// Format array element access
@@ -505,14 +991,20 @@ impl TranslatedModel {
         array_name: &str,
         indices: &[ast::Expr],
         solution: &selen::prelude::Solution,
+        span: Span,
     ) -> Result<String> {
         // For now, only support constant indices for element access
         let mut const_indices = Vec::new();
+        let starts: Vec<i64> = self.array_index_sets.get(array_name)
+            .map(|ranges| ranges.iter().map(|&(first, _)| i64::from(first)).collect())
+            .unwrap_or_default();
         
-        for idx_expr in indices {
+        for (dim, idx_expr) in indices.iter().enumerate() {
             // Try to evaluate index to a constant
             if let ast::ExprKind::IntLit(val) = idx_expr.kind {
-                const_indices.push((val - 1) as usize); // Convert from 1-based to 0-based
+                // Convert from the declared index set to 0-based
+                let start = starts.get(dim).copied().unwrap_or(1);
+                const_indices.push((val - start) as usize);
             } else if let ast::ExprKind::Ident(_) = idx_expr.kind {
                 // Variable index - not supported in output formatting yet
                 return Err(Error::message(
@@ -531,7 +1023,7 @@ impl TranslatedModel {
         // Try integer array first
         if let Some(var_ids) = self.int_var_arrays.get(array_name) {
             if const_indices.len() == 1 && const_indices[0] < var_ids.len() {
-                return Ok(solution.get_int(var_ids[const_indices[0]]).to_string());
+                return Ok(self.format_int_value(array_name, solution.get_int(var_ids[const_indices[0]])));
             }
         }
 
@@ -551,7 +1043,7 @@ impl TranslatedModel {
 
         Err(Error::message(
             &format!("Invalid array access: '{}' with indices: {:?}", array_name, const_indices),
-            Span::new(0, 0),
+            span,
         ))
     }
 
@@ -570,11 +1062,22 @@ impl Translator {
         Self {
             model: selen::model::Model::default(),
             context: TranslatorContext::new(),
+            options: TranslatorOptions::default(),
             objective_type: ObjectiveType::Satisfy,
             objective_var: None,
+            objective_open: false,
+            unbounded_ints: HashSet::new(),
             output_items: Vec::new(),
             search_option: None,
+            search_heuristics: Vec::new(),
+            pending_bounds: HashMap::new(),
+            unfolded_bounds: HashMap::new(),
             enum_var_mapping: HashMap::new(),
+            output_vars: Vec::new(),
+            warnings: Vec::new(),
+            consistency: None,
+            inlining: Vec::new(),
+            open_int_domain: None,
         }
     }
 
@@ -592,16 +1095,38 @@ impl Translator {
 
     /// Translate a MiniZinc AST model to a Selen Model with custom configuration
     pub fn translate_with_config(ast: &ast::Model, config: selen::utils::config::SolverConfig) -> Result<selen::model::Model> {
+        Self::translate_with_config_and_options(ast, config, TranslatorOptions::default())
+    }
+
+    /// Translate a MiniZinc AST model to a Selen Model with custom configuration and
+    /// translation options
+    pub fn translate_with_config_and_options(
+        ast: &ast::Model,
+        config: selen::utils::config::SolverConfig,
+        options: TranslatorOptions,
+    ) -> Result<selen::model::Model> {
         let model = selen::model::Model::with_config(config);
         let mut translator = Self {
             model,
             context: TranslatorContext::new(),
+            options,
             objective_type: ObjectiveType::Satisfy,
             objective_var: None,
+            objective_open: false,
+            unbounded_ints: HashSet::new(),
             output_items: Vec::new(),
             search_option: None,
+            search_heuristics: Vec::new(),
+            pending_bounds: HashMap::new(),
+            unfolded_bounds: HashMap::new(),
             enum_var_mapping: HashMap::new(),
+            output_vars: Vec::new(),
+            warnings: Vec::new(),
+            consistency: None,
+            inlining: Vec::new(),
+            open_int_domain: None,
         };
+        translator.seed_param_arrays();
 
         // Process all items in order
         for item in &ast.items {
@@ -613,23 +1138,209 @@ impl Translator {
 
     /// Translate a MiniZinc AST model and return the model with variable mappings
     pub fn translate_with_vars(ast: &ast::Model) -> Result<TranslatedModel> {
+        Self::translate_with_vars_and_options(ast, TranslatorOptions::default())
+    }
+
+    /// Read, parse and translate a model file, inlining its `include` directives
+    ///
+    /// Included files are resolved relative to the including file's directory and
+    /// inlined once each. MiniZinc library files such as `globals.mzn` that do not
+    /// exist there are ignored; any other include that cannot be found is an error.
+    pub fn translate_from_file(path: impl AsRef<Path>) -> Result<TranslatedModel> {
+        let mut items = Vec::new();
+        Self::load_model_file(path.as_ref(), &mut Vec::new(), &mut HashSet::new(), &mut items)?;
+        Self::translate_with_vars(&ast::Model { items })
+    }
+
+    /// Inline the `include` directives of a model parsed from the file at `path`
+    ///
+    /// Includes are resolved as in [`Self::translate_from_file`]; use this when
+    /// the model's source was combined with other text (such as a data file)
+    /// before parsing. Errors in included files name the file they come from.
+    pub fn expand_includes(model: ast::Model, path: impl AsRef<Path>) -> Result<ast::Model> {
+        let path = path.as_ref();
+        let filename = path.display().to_string();
+        let canonical = path.canonicalize().map_err(|e| {
+            Error::message(&format!("Cannot read '{}': {}", filename, e), Span::dummy()).with_filename(&filename)
+        })?;
+        let mut stack = vec![canonical.clone()];
+        let mut loaded = HashSet::from([canonical]);
+        let mut items = Vec::new();
+        Self::inline_includes(model, None, &mut stack, &mut loaded, &mut items)?;
+        Ok(ast::Model { items })
+    }
+
+    /// Append the items of `path` (with includes expanded in place) to `items`
+    fn load_model_file(
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        loaded: &mut HashSet<PathBuf>,
+        items: &mut Vec<ast::Item>,
+    ) -> Result<()> {
+        let filename = path.display().to_string();
+        let read_error = |e: std::io::Error| {
+            Error::message(&format!("Cannot read '{}': {}", filename, e), Span::dummy()).with_filename(&filename)
+        };
+        let canonical = path.canonicalize().map_err(read_error)?;
+        if !loaded.insert(canonical.clone()) {
+            return Ok(());
+        }
+        let source = std::fs::read_to_string(&canonical).map_err(read_error)?;
+        let model = crate::parse(&source).map_err(|e| e.with_filename(&filename))?;
+
+        stack.push(canonical);
+        Self::inline_includes(model, Some(&source), stack, loaded, items)?;
+        stack.pop();
+        Ok(())
+    }
+
+    /// Append `model`'s items to `items`, loading each include in its place
+    ///
+    /// The including file is the top of `stack`; errors about its include
+    /// directives point at them in `source`, when known.
+    fn inline_includes(
+        model: ast::Model,
+        source: Option<&str>,
+        stack: &mut Vec<PathBuf>,
+        loaded: &mut HashSet<PathBuf>,
+        items: &mut Vec<ast::Item>,
+    ) -> Result<()> {
+        let including = stack.last().cloned().unwrap_or_default();
+        let dir = including.parent().map(Path::to_path_buf).unwrap_or_default();
+        let include_error = |message: String, span: Span| {
+            let error = Error::message(&message, span).with_filename(&including.display().to_string());
+            match source {
+                Some(source) => error.with_source(source.to_string()),
+                None => error,
+            }
+        };
+
+        for item in model.items {
+            match item {
+                ast::Item::Include(include) => {
+                    let target = dir.join(&include.path);
+                    if !target.exists() {
+                        if Self::is_library_include(&include.path) {
+                            continue;
+                        }
+                        return Err(include_error(
+                            format!("Cannot find '{}' included from '{}'", target.display(), including.display()),
+                            include.span,
+                        ));
+                    }
+                    let canonical = target.canonicalize().map_err(|e| {
+                        include_error(format!("Cannot read '{}': {}", target.display(), e), include.span)
+                    })?;
+                    if stack.contains(&canonical) {
+                        let cycle: Vec<String> = stack
+                            .iter()
+                            .chain(std::iter::once(&canonical))
+                            .map(|p| p.display().to_string())
+                            .collect();
+                        return Err(include_error(format!("Include cycle: {}", cycle.join(" -> ")), include.span));
+                    }
+                    Self::load_model_file(&target, stack, loaded, items)?;
+                }
+                other => items.push(other),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an include names a MiniZinc library file rather than a model file
+    fn is_library_include(path: &str) -> bool {
+        LIBRARY_INCLUDES.contains(&path)
+    }
+
+    /// Translate a MiniZinc AST model and return the model with variable mappings,
+    /// using a custom solver configuration
+    pub fn translate_with_vars_and_config(
+        ast: &ast::Model,
+        config: selen::utils::config::SolverConfig,
+    ) -> Result<TranslatedModel> {
+        Self::translate_with_vars_config_and_options(ast, config, TranslatorOptions::default())
+    }
+
+    /// Translate a MiniZinc AST model and return the model with variable mappings,
+    /// using a custom solver configuration and translation options
+    pub fn translate_with_vars_config_and_options(
+        ast: &ast::Model,
+        config: selen::utils::config::SolverConfig,
+        options: TranslatorOptions,
+    ) -> Result<TranslatedModel> {
+        let mut translator = Self::new();
+        translator.model = selen::model::Model::with_config(config);
+        translator.options = options;
+        translator.seed_param_arrays();
+        translator.translate_items_with_vars(ast)
+    }
+
+    /// Translate a MiniZinc AST model, giving int variables declared without
+    /// bounds the explicit `domain` instead of the solver's fallback bound
+    pub(crate) fn translate_with_open_int_domain(
+        ast: &ast::Model,
+        config: selen::utils::config::SolverConfig,
+        domain: (i32, i32),
+    ) -> Result<TranslatedModel> {
+        let mut translator = Self::new();
+        translator.model = selen::model::Model::with_config(config);
+        translator.open_int_domain = Some(domain);
+        translator.translate_items_with_vars(ast)
+    }
+
+    /// Translate a MiniZinc AST model with optional translation behaviours enabled
+    pub fn translate_with_vars_and_options(ast: &ast::Model, options: TranslatorOptions) -> Result<TranslatedModel> {
         let mut translator = Self::new();
+        translator.options = options;
+        translator.seed_param_arrays();
+        translator.translate_items_with_vars(ast)
+    }
+
+    /// Share the parameter arrays pre-seeded in the options with the context
+    fn seed_param_arrays(&mut self) {
+        for (name, values) in &self.options.int_param_arrays {
+            self.context.array_metadata.insert(name.clone(), ArrayMetadata::new(vec![values.len()]));
+            self.context.add_int_param_array(name.clone(), Arc::clone(values));
+        }
+        for (name, values) in &self.options.float_param_arrays {
+            self.context.array_metadata.insert(name.clone(), ArrayMetadata::new(vec![values.len()]));
+            self.context.add_float_param_array(name.clone(), Arc::clone(values));
+        }
+    }
+
+    /// Translate all items of `ast` into this translator's model and hand back the
+    /// model together with its variable mappings
+    fn translate_items_with_vars(self, ast: &ast::Model) -> Result<TranslatedModel> {
+        let mut translator = self;
 
         // Two-pass approach to ensure simple constraints (e.g., var == const) are posted FIRST
         // This helps Selen's propagators work with narrowed variable domains
         
         let debug = std::env::var("TRANSLATOR_DEBUG").is_ok();
         
-        // Pass 0: Enum definitions (must be processed first)
+        // Pass 0: Enum, predicate and function definitions (must be processed first)
         if debug {
-            eprintln!("TRANSLATOR_DEBUG: PASS 0 - Enum definitions");
+            eprintln!("TRANSLATOR_DEBUG: PASS 0 - Enum, predicate and function definitions");
         }
         for item in &ast.items {
-            if matches!(item, ast::Item::EnumDef(_)) {
+            if matches!(item, ast::Item::EnumDef(_) | ast::Item::Predicate(_) | ast::Item::Function(_)) {
                 translator.translate_item(item)?;
             }
         }
         
+        // Pass 0.5: Collect constant bounds (`x <= 10`, `0 <= x /\ x <= 10`) so the
+        // declarations below can start from the narrowed domain
+        for (index, item) in ast.items.iter().enumerate() {
+            if let ast::Item::Constraint(c) = item
+                && let Some(bounds) = Self::simple_bounds(&c.expr)
+            {
+                translator.unfolded_bounds.insert(index, bounds.len());
+                for (name, op, bound) in bounds {
+                    translator.pending_bounds.entry(name).or_default().push((index, op, bound));
+                }
+            }
+        }
+
         // Pass 1: Variable declarations
         if debug {
             eprintln!("TRANSLATOR_DEBUG: PASS 1 - Variable declarations");
@@ -659,10 +1370,12 @@ impl Translator {
         if debug {
             eprintln!("TRANSLATOR_DEBUG: PASS 3 - Complex constraints and solve");
         }
-        for item in &ast.items {
+        for (index, item) in ast.items.iter().enumerate() {
             match item {
-                ast::Item::EnumDef(_) => {} // Already done in pass 0
+                ast::Item::EnumDef(_) | ast::Item::Predicate(_) | ast::Item::Function(_) => {} // Already done in pass 0
                 ast::Item::VarDecl(_) => {} // Already done in pass 1
+                // Bounds already folded into the declared domains
+                ast::Item::Constraint(_) if translator.unfolded_bounds.get(&index) == Some(&0) => {}
                 ast::Item::Constraint(c) => {
                     if !Self::is_simple_equality_constraint(&c.expr) {
                         if debug {
@@ -677,7 +1390,7 @@ impl Translator {
             }
         }
 
-        Ok(TranslatedModel {
+        let mut model_data = TranslatedModel {
             model: translator.model,
             int_vars: translator.context.int_vars.clone(),
             int_var_arrays: translator.context.int_var_arrays.clone(),
@@ -685,12 +1398,103 @@ impl Translator {
             bool_var_arrays: translator.context.bool_var_arrays,
             float_vars: translator.context.float_vars,
             float_var_arrays: translator.context.float_var_arrays,
+            set_vars: translator
+                .context
+                .set_vars
+                .iter()
+                .map(|(name, set)| (name.clone(), set.members().collect()))
+                .collect(),
             objective_type: translator.objective_type,
             objective_var: translator.objective_var,
+            objective_open: translator.objective_open,
             output_items: translator.output_items,
             search_option: translator.search_option,
+            search_heuristics: translator.search_heuristics,
             enum_vars: translator.enum_var_mapping,
-        })
+            output_vars: translator.output_vars,
+            warnings: translator.warnings,
+            array_index_sets: translator
+                .context
+                .array_metadata
+                .iter()
+                .map(|(name, metadata)| {
+                    let ranges = metadata.starts.iter()
+                        .zip(&metadata.dimensions)
+                        .map(|(&start, &size)| (start, start + size as i32 - 1))
+                        .collect();
+                    (name.clone(), ranges)
+                })
+                .collect(),
+            indexed_output: translator.options.indexed_output,
+            var_names: HashMap::new(),
+        };
+        model_data.index_var_names();
+        Ok(model_data)
+    }
+
+    /// Decompose a constraint made only of `var op e` / `e op var` comparisons
+    /// (`<`, `<=`, `>`, `>=`) joined by `/\` into `(var, op, e)` with the variable
+    /// on the left. Whether `e` is a constant is decided when `var` is declared.
+    fn simple_bounds(expr: &ast::Expr) -> Option<Vec<(String, ast::BinOp, ast::Expr)>> {
+        let ast::ExprKind::BinOp { op, left, right } = &expr.kind else {
+            return None;
+        };
+        let flipped = match op {
+            ast::BinOp::And => {
+                let mut bounds = Self::simple_bounds(left)?;
+                bounds.extend(Self::simple_bounds(right)?);
+                return Some(bounds);
+            }
+            ast::BinOp::Lt => ast::BinOp::Gt,
+            ast::BinOp::Le => ast::BinOp::Ge,
+            ast::BinOp::Gt => ast::BinOp::Lt,
+            ast::BinOp::Ge => ast::BinOp::Le,
+            _ => return None,
+        };
+        match (&left.kind, &right.kind) {
+            (ast::ExprKind::Ident(name), ast::ExprKind::IntLit(_) | ast::ExprKind::Ident(_) | ast::ExprKind::UnOp { .. }) => {
+                Some(vec![(name.clone(), *op, (**right).clone())])
+            }
+            (ast::ExprKind::IntLit(_) | ast::ExprKind::UnOp { .. }, ast::ExprKind::Ident(name)) => {
+                Some(vec![(name.clone(), flipped, (**left).clone())])
+            }
+            _ => None,
+        }
+    }
+
+    /// Narrow a new int variable's domain by the constant bounds collected for it
+    ///
+    /// Each bound that evaluates is marked as folded; a constraint item whose
+    /// bounds are all folded is not posted. Selen re-infers both ends of a
+    /// half-open `i32::MIN`/`i32::MAX` domain, so such results are not folded,
+    /// and neither is an empty one, which is left to fail at solve time.
+    fn fold_declared_bounds(&mut self, name: &str, min: i32, max: i32) -> (i32, i32) {
+        let Some(bounds) = self.pending_bounds.remove(name) else {
+            return (min, max);
+        };
+        let (mut lo, mut hi) = (min, max);
+        let mut folded = Vec::new();
+        for (index, op, bound) in &bounds {
+            let Ok(value) = self.eval_int_expr(bound) else {
+                continue;
+            };
+            match op {
+                ast::BinOp::Lt => hi = hi.min(value.saturating_sub(1)),
+                ast::BinOp::Le => hi = hi.min(value),
+                ast::BinOp::Gt => lo = lo.max(value.saturating_add(1)),
+                _ => lo = lo.max(value),
+            }
+            folded.push(*index);
+        }
+        if lo > hi || lo == i32::MIN || hi == i32::MAX {
+            return (min, max);
+        }
+        for index in folded {
+            if let Some(remaining) = self.unfolded_bounds.get_mut(&index) {
+                *remaining -= 1;
+            }
+        }
+        (lo, hi)
     }
 
     /// Check if a constraint is a simple equality (Var == Const or Const == Var)
@@ -722,24 +1526,98 @@ impl Translator {
         }
     }
 
-    /// Extract a constant integer value from an expression if possible
     /// Extract a constant integer value from an expression if possible
     fn extract_const_value(expr: &ast::Expr) -> Option<i64> {
         match &expr.kind {
             ast::ExprKind::IntLit(i) => Some(*i),
+            ast::ExprKind::UnOp { op: ast::UnOp::Neg, expr: inner } => {
+                Self::extract_const_value(inner).map(|v| -v)
+            }
+            _ => None,
+        }
+    }
+
+    /// Extract a constant float literal, possibly negated
+    fn extract_float_const(expr: &ast::Expr) -> Option<f64> {
+        match &expr.kind {
+            ast::ExprKind::FloatLit(f) => Some(*f),
+            ast::ExprKind::UnOp { op: ast::UnOp::Neg, expr: inner } => {
+                Self::extract_float_const(inner).map(|v| -v)
+            }
+            _ => None,
+        }
+    }
+
+    /// Like `extract_const_value`, but also resolves integer parameters,
+    /// constant-index accesses into parameter arrays (e.g. `p[3]` after forall
+    /// expansion) and arithmetic over them (e.g. `n - 1`), so comparisons
+    /// against them post `x == const` directly
+    fn const_int_operand(&self, expr: &ast::Expr) -> Option<i64> {
+        if let Some(value) = Self::extract_const_value(expr) {
+            return Some(value);
+        }
+        match &expr.kind {
+            ast::ExprKind::Ident(_)
+            | ast::ExprKind::ArrayAccess { .. }
+            | ast::ExprKind::BinOp { .. }
+            | ast::ExprKind::UnOp { .. }
+            | ast::ExprKind::Call { .. }
+            | ast::ExprKind::IfThenElse { .. } => self.eval_int_expr(expr).ok().map(i64::from),
             _ => None,
         }
     }
 
     fn translate_item(&mut self, item: &ast::Item) -> Result<()> {
         match item {
+            ast::Item::Include(include) => {
+                // Library includes need nothing: globals are implemented natively
+                if Self::is_library_include(&include.path) {
+                    Ok(())
+                } else {
+                    Err(Error::message(
+                        &format!("Cannot include '{}' without a model file location", include.path),
+                        include.span,
+                    )
+                    .with_workaround("use Translator::translate_from_file or Translator::expand_includes to resolve includes"))
+                }
+            }
             ast::Item::EnumDef(enum_def) => {
-                // Store enum definition for later use
+                // Store enum definition for later use; each value stands for its
+                // 1-based position, so comparisons follow the declared order
                 self.context.enums.insert(enum_def.name.clone(), enum_def.values.clone());
+                for (pos, value) in enum_def.values.iter().enumerate() {
+                    self.context.add_int_param(value.clone(), pos as i32 + 1);
+                }
+                Ok(())
+            }
+            ast::Item::Predicate(predicate) => {
+                self.context.predicates.insert(predicate.name.clone(), predicate.clone());
+                Ok(())
+            }
+            ast::Item::Function(function) => {
+                self.context.functions.insert(function.name.clone(), function.clone());
                 Ok(())
             }
             ast::Item::VarDecl(var_decl) => self.translate_var_decl(var_decl),
-            ast::Item::Constraint(constraint) => self.translate_constraint(constraint),
+            ast::Item::Constraint(constraint) => {
+                for annotation in &constraint.annotations {
+                    match annotation.name.as_str() {
+                        "domain" | "bounds" => self.consistency = Some(annotation.clone()),
+                        name => self.warnings.push(Warning::new(
+                            &format!("Annotation '{}' on a constraint is ignored", name),
+                            annotation.span,
+                        )),
+                    }
+                }
+                let result = self.translate_constraint(constraint);
+                if let Some(annotation) = self.consistency.take() {
+                    self.warnings.push(Warning::new(
+                        &format!("Annotation '{}' has no effect on this constraint", annotation.name),
+                        annotation.span,
+                    ));
+                }
+                result
+            }
             ast::Item::Solve(solve) => self.translate_solve(solve),
             ast::Item::Output(output) => {
                 // Store output items for later formatting
@@ -750,6 +1628,19 @@ impl Translator {
     }
 
     fn translate_var_decl(&mut self, var_decl: &ast::VarDecl) -> Result<()> {
+        for annotation in &var_decl.annotations {
+            match annotation.name.as_str() {
+                "output_var" | "output_array" => {
+                    if self.output_vars.last() != Some(&var_decl.name) {
+                        self.output_vars.push(var_decl.name.clone());
+                    }
+                }
+                name => self.warnings.push(Warning::new(
+                    &format!("Annotation '{}' on '{}' is ignored", name, var_decl.name),
+                    annotation.span,
+                )),
+            }
+        }
         match &var_decl.type_inst {
             ast::TypeInst::Basic { is_var, base_type } => {
                 if *is_var {
@@ -761,14 +1652,40 @@ impl Translator {
                             self.context.add_bool_var(var_decl.name.clone(), var);
                         }
                         ast::BaseType::Int => {
-                            // var int: x (unbounded)
-                            let var = self.model.int(i32::MIN, i32::MAX);
+                            // var int: x (unbounded unless the constraints bound it)
+                            let (min, max) = self.fold_declared_bounds(&var_decl.name, i32::MIN, i32::MAX);
+                            if (min, max) == (i32::MIN, i32::MAX) && var_decl.expr.is_none() {
+                                self.warnings.push(Warning::new(
+                                    &format!("'{}' has no bounds; the solver limits it to -100000..100000", var_decl.name),
+                                    var_decl.span,
+                                ));
+                            }
+                            if (min, max) == (i32::MIN, i32::MAX)
+                                && var_decl.expr.as_ref().is_none_or(|expr| self.mentions_unbounded_int(expr))
+                            {
+                                self.unbounded_ints.insert(var_decl.name.clone());
+                            }
+                            let (min, max) = match self.open_int_domain {
+                                Some(domain) if (min, max) == (i32::MIN, i32::MAX) && var_decl.expr.is_none() => domain,
+                                _ => (min, max),
+                            };
+                            let var = self.model.int(min, max);
                             self.context.add_int_var(var_decl.name.clone(), var);
                         }
                         ast::BaseType::Float => {
-                            // var float: x (unbounded)
-                            let var = self.model.float(f64::MIN, f64::MAX);
-                            self.context.add_float_var(var_decl.name.clone(), var);
+                            // var float: x (unbounded); a defined `var float: x = e` takes
+                            // e's variable directly so its domain is bounded by the operands
+                            let var = match &var_decl.expr {
+                                Some(expr) => self.get_var_or_value(expr)?,
+                                None => {
+                                    self.warnings.push(Warning::new(
+                                        &format!("'{}' has no bounds; the solver limits it to -1e9..1e9", var_decl.name),
+                                        var_decl.span,
+                                    ));
+                                    self.model.float(f64::MIN, f64::MAX)
+                                }
+                            };
+                            self.context.add_float_var(var_decl.name.clone(), var);
                         }
                         ast::BaseType::Enum(enum_name) => {
                             // var EnumType: x
@@ -854,6 +1771,7 @@ impl Translator {
                 match base_type {
                     ast::BaseType::Int => {
                         let (min, max) = self.eval_int_domain(domain)?;
+                        let (min, max) = self.fold_declared_bounds(&var_decl.name, min, max);
                         let var = self.model.int(min, max);
                         if std::env::var("ZELEN_DEBUG").is_ok() {
                             eprintln!("DEBUG: Created int var '{}': {:?} with range [{}, {}]", var_decl.name, var, min, max);
@@ -880,14 +1798,119 @@ impl Translator {
                 }
             }
 
+            ast::TypeInst::Set { is_var, element_type } => {
+                if !is_var {
+                    return Err(Error::unsupported_feature(
+                        "Set parameters",
+                        "Phase 2",
+                        var_decl.span,
+                    ));
+                }
+                let set_var = self.new_set_var(element_type, var_decl.span)?;
+                self.context.set_vars.insert(var_decl.name.clone(), set_var);
+            }
+
             ast::TypeInst::Array { index_sets, element_type } => {
-                self.translate_array_decl(&var_decl.name, index_sets, element_type, &var_decl.expr)?;
+                self.translate_array_decl(&var_decl.name, index_sets, element_type, &var_decl.expr, var_decl.span)?;
+            }
+        }
+
+        // A decision variable with an initializer is defined by it: `var int: t = x + y`
+        if let Some(expr) = &var_decl.expr
+            && matches!(
+                var_decl.type_inst,
+                ast::TypeInst::Basic { is_var: true, .. } | ast::TypeInst::Constrained { is_var: true, .. }
+            )
+            && !matches!(
+                var_decl.type_inst,
+                ast::TypeInst::Basic { base_type: ast::BaseType::Float, .. }
+            )
+        {
+            self.post_var_definition(&var_decl.name, expr)?;
+        }
+
+        Ok(())
+    }
+
+    /// Run `body` with the declarations of a `let` in scope
+    ///
+    /// Each declared name shadows any outer binding of the same name; the outer
+    /// bindings are restored once `body` returns, whether or not it succeeded.
+    fn with_let_scope<T>(
+        &mut self,
+        decls: &[ast::VarDecl],
+        body: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let mut shadowed = Vec::with_capacity(decls.len());
+        let mut result = Ok(());
+        for decl in decls {
+            shadowed.push((decl.name.as_str(), self.context.unbind(&decl.name)));
+            result = self.translate_var_decl(decl);
+            if result.is_err() {
+                break;
+            }
+        }
+        let result = result.and_then(|()| body(self));
+        for (name, rebinds) in shadowed.into_iter().rev() {
+            drop(self.context.unbind(name));
+            for rebind in rebinds {
+                rebind(&mut self.context);
             }
         }
+        result
+    }
 
+    /// Constrain a freshly declared decision variable to equal its initializer
+    fn post_var_definition(&mut self, name: &str, expr: &ast::Expr) -> Result<()> {
+        if let Some(var) = self.context.get_bool_var(name) {
+            let value = self.expr_to_bool_var(expr)?;
+            self.model.new(var.eq(value));
+        } else if let Some(var) = self.context.get_int_var(name) {
+            // Linear definitions such as weighted sums become a single lin_eq
+            let definition = ast::Expr {
+                kind: ast::ExprKind::BinOp {
+                    op: ast::BinOp::Eq,
+                    left: Box::new(ast::Expr { kind: ast::ExprKind::Ident(name.to_string()), span: expr.span }),
+                    right: Box::new(expr.clone()),
+                },
+                span: expr.span,
+            };
+            if self.try_post_linear_comparison(&definition) {
+                return Ok(());
+            }
+            match self.const_int_operand(expr) {
+                Some(value) => self.model.new(var.eq(value as i32)),
+                None => {
+                    let value = self.get_var_or_value(expr)?;
+                    self.model.new(var.eq(value))
+                }
+            };
+        } else if let Some(var) = self.context.get_float_var(name) {
+            let value = self.get_var_or_value(expr)?;
+            self.post_float_comparison(ast::BinOp::Eq, var, value);
+        }
         Ok(())
     }
 
+    /// Create the membership indicators for a `var set of lo..hi`
+    fn new_set_var(&mut self, element_type: &ast::TypeInst, span: Span) -> Result<SetVar> {
+        let (min, max) = match element_type {
+            ast::TypeInst::Constrained { base_type: ast::BaseType::Int, domain, .. } => {
+                self.eval_int_domain(domain)?
+            }
+            _ => {
+                return Err(Error::unsupported_feature(
+                    "Set variables without an integer range universe",
+                    "Phase 2",
+                    span,
+                ));
+            }
+        };
+        let size = if max >= min { (max - min + 1) as usize } else { 0 };
+        let indicators = self.model.bools(size);
+        Ok(SetVar { min, indicators })
+    }
+
     /// Flatten a 2D array to 1D with pre-allocated capacity
     #[inline]
     fn flatten_2d(arr_2d: &[Vec<VarId>]) -> Vec<VarId> {
@@ -921,16 +1944,35 @@ impl Translator {
         index_sets: &[ast::Expr],
         element_type: &ast::TypeInst,
         init_expr: &Option<ast::Expr>,
+        span: Span,
     ) -> Result<()> {
         // Determine if it's a var array or par array
         let is_var = match element_type {
             ast::TypeInst::Basic { is_var, .. } => *is_var,
             ast::TypeInst::Constrained { is_var, .. } => *is_var,
+            ast::TypeInst::Set { is_var, element_type } => {
+                if !is_var {
+                    return Err(Error::unsupported_feature(
+                        "Arrays of set parameters",
+                        "Phase 2",
+                        span,
+                    ));
+                }
+                let mut size = 1usize;
+                for index_set in index_sets {
+                    size = size.saturating_mul(self.eval_index_set_size(index_set)?);
+                }
+                let sets = (0..size)
+                    .map(|_| self.new_set_var(element_type, span))
+                    .collect::<Result<Vec<_>>>()?;
+                self.context.set_var_arrays.insert(name.to_string(), sets);
+                return Ok(());
+            }
             ast::TypeInst::Array { .. } => {
                 return Err(Error::unsupported_feature(
                     "Multi-dimensional arrays",
                     "Phase 2",
-                    ast::Span::dummy(),
+                    span,
                 ));
             }
         };
@@ -938,16 +1980,18 @@ impl Translator {
         // Get total array size (product of all dimensions for multi-dimensional arrays)
         let mut size = 1usize;
         let mut dimensions = Vec::new();
+        let mut starts = Vec::new();
         for index_set in index_sets {
-            let dim_size = self.eval_index_set_size(index_set)?;
+            let (start, dim_size) = self.eval_index_set(index_set)?;
             dimensions.push(dim_size);
+            starts.push(start);
             size = size.saturating_mul(dim_size);
         }
 
         // Store array metadata for later index flattening
         self.context
             .array_metadata
-            .insert(name.to_string(), ArrayMetadata::new(dimensions.clone()));
+            .insert(name.to_string(), ArrayMetadata::with_starts(dimensions.clone(), starts));
 
         if is_var {
             // Decision variable array - determine the type
@@ -1013,7 +2057,7 @@ impl Translator {
                             let enum_values = self.context.enums.get(enum_name)
                                 .ok_or_else(|| Error::message(
                                     &format!("Undefined enum type: {}", enum_name),
-                                    Span::dummy(),
+                                    span,
                                 ))?
                                 .clone();
                             let cardinality = enum_values.len() as i32;
@@ -1042,18 +2086,20 @@ impl Translator {
                 ast::TypeInst::Basic { base_type, .. } => {
                     match base_type {
                         ast::BaseType::Int => {
+                            self.unbounded_ints.insert(name.to_string());
+                            let (min, max) = self.open_int_domain.unwrap_or((i32::MIN, i32::MAX));
                             if dimensions.len() == 2 {
-                                let vars_2d = self.model.ints_2d(dimensions[0], dimensions[1], i32::MIN, i32::MAX);
+                                let vars_2d = self.model.ints_2d(dimensions[0], dimensions[1], min, max);
                                 let flattened = Self::flatten_2d(&vars_2d);
                                 self.context.add_int_var_array_2d(name.to_string(), vars_2d);
                                 self.context.add_int_var_array(name.to_string(), flattened);
                             } else if dimensions.len() == 3 {
-                                let vars_3d = self.model.ints_3d(dimensions[0], dimensions[1], dimensions[2], i32::MIN, i32::MAX);
+                                let vars_3d = self.model.ints_3d(dimensions[0], dimensions[1], dimensions[2], min, max);
                                 let flattened = Self::flatten_3d(&vars_3d);
                                 self.context.add_int_var_array_3d(name.to_string(), vars_3d);
                                 self.context.add_int_var_array(name.to_string(), flattened);
                             } else {
-                                let vars = self.model.ints(size, i32::MIN, i32::MAX);
+                                let vars = self.model.ints(size, min, max);
                                 self.context.add_int_var_array(name.to_string(), vars);
                             }
                         }
@@ -1094,7 +2140,7 @@ impl Translator {
                             let enum_values = self.context.enums.get(enum_name)
                                 .ok_or_else(|| Error::message(
                                     &format!("Undefined enum type: {}", enum_name),
-                                    Span::dummy(),
+                                    span,
                                 ))?
                                 .clone();
                             let cardinality = enum_values.len() as i32;
@@ -1377,12 +2423,20 @@ impl Translator {
                         ));
                     }
                 }
+            } else if let Some(seeded) = self.context.get_int_param_array(name).map(|values| values.len())
+                .or_else(|| self.context.get_float_param_array(name).map(|values| values.len()))
+            {
+                // Pre-seeded through the translator options; the declaration
+                // only supplies its index sets
+                if seeded != size {
+                    return Err(Error::array_size_mismatch(size, seeded, span));
+                }
             } else {
                 // Parameter array without initializer - not supported
                 return Err(Error::unsupported_feature(
                     "Parameter arrays without initializer",
                     "Phase 2",
-                    ast::Span::dummy(),
+                    span,
                 ));
             }
         }
@@ -1390,19 +2444,53 @@ impl Translator {
         Ok(())
     }
 
+    /// Post `expr` as a constraint of its own, such as one conjunct of a larger one
+    fn translate_constraint_expr(&mut self, expr: &ast::Expr) -> Result<()> {
+        self.translate_constraint(&ast::Constraint { expr: expr.clone(), annotations: Vec::new(), span: expr.span })
+    }
+
     fn translate_constraint(&mut self, constraint: &ast::Constraint) -> Result<()> {
+        if let Some(inner) = self.unwrap_assert_trace(&constraint.expr)? {
+            return self.translate_constraint_expr(&inner);
+        }
+        if let Some(result) = self.inline_user_call(&constraint.expr, Self::translate_constraint_expr) {
+            return result;
+        }
         match &constraint.expr.kind {
             ast::ExprKind::Call { name, args } => {
-                self.translate_constraint_call(name, args)?;
+                self.translate_constraint_call(name, args, constraint.expr.span)?;
             }
             ast::ExprKind::GenCall { name, generators, body } => {
-                self.translate_constraint_gencall(name, generators, body)?;
+                self.translate_constraint_gencall(name, generators, body, constraint.expr.span)?;
+            }
+            ast::ExprKind::Let { decls, constraints, body } => {
+                self.with_let_scope(decls, |t| {
+                    for expr in constraints.iter().chain([&**body]) {
+                        t.translate_constraint_expr(expr)?;
+                    }
+                    Ok(())
+                })?;
+            }
+            ast::ExprKind::BinOp { op: ast::BinOp::And, left, right } => {
+                // A top-level conjunction posts each conjunct separately
+                self.translate_constraint_expr(left)?;
+                self.translate_constraint_expr(right)?;
             }
             ast::ExprKind::BinOp { op, left, right } => {
-                self.translate_constraint_binop(*op, left, right)?;
+                if !self.try_post_linear_comparison(&constraint.expr) {
+                    self.translate_constraint_binop(*op, left, right, constraint.expr.span)?;
+                }
             }
             ast::ExprKind::UnOp { op, expr } => {
-                self.translate_constraint_unop(*op, expr)?;
+                self.translate_constraint_unop(*op, expr, constraint.expr.span)?;
+            }
+            ast::ExprKind::IfThenElse { cond, then_expr, else_expr } => {
+                self.translate_constraint_if(cond, then_expr, else_expr.as_deref())?;
+            }
+            ast::ExprKind::ArrayAccess { .. } => {
+                // Boolean array element used as a constraint: `flags[i]`
+                let element = self.get_var_or_value(&constraint.expr)?;
+                self.model.new(element.eq(1));
             }
             ast::ExprKind::Ident(_) | ast::ExprKind::BoolLit(_) => {
                 // Boolean variable or literal used as a constraint
@@ -1422,21 +2510,40 @@ impl Translator {
         Ok(())
     }
 
-    fn translate_constraint_call(&mut self, name: &str, args: &[ast::Expr]) -> Result<()> {
+    /// Map library spellings of a global onto the name handled by the translator
+    ///
+    /// Flattened FlatZinc output uses `fzn_`-prefixed, type-suffixed names such as
+    /// `fzn_all_different_int`; users sometimes paste those back into MiniZinc models.
+    fn canonical_global_name(name: &str) -> &str {
+        let name = name.strip_prefix("fzn_").unwrap_or(name);
         match name {
+            "all_different" | "all_different_int" | "alldifferent_int" => "alldifferent",
+            other => other,
+        }
+    }
+
+    fn translate_constraint_call(&mut self, name: &str, args: &[ast::Expr], span: Span) -> Result<()> {
+        match Self::canonical_global_name(name) {
             "alldifferent" | "alldiff" => {
                 if args.len() != 1 {
                     return Err(Error::type_error(
                         "1 argument",
                         &format!("{} arguments", args.len()),
-                        ast::Span::dummy(),
+                        span,
                     ));
                 }
 
+                // Selen's alldiff filters through a matching-based propagator, which
+                // gives the `:: domain` consistency and is at least as strong as `:: bounds`
+                self.consistency.take();
+
                 // Get the array variable
                 if let ast::ExprKind::Ident(array_name) = &args[0].kind {
-                    if let Some(vars) = self.context.get_int_var_array(array_name) {
-                        self.model.alldiff(vars);
+                    if let Some(vars) = self.context.get_int_var_array(array_name).cloned() {
+                        self.model.alldiff(&vars);
+                        if self.options.redundant_permutation_sums {
+                            self.post_permutation_sum(&vars);
+                        }
                     } else {
                         return Err(Error::message(
                             &format!("Undefined array variable: {}", array_name),
@@ -1444,2020 +2551,7612 @@ impl Translator {
                         ));
                     }
                 } else {
+                    // Literals, comprehensions and concatenations
+                    let vars = self.get_array_vars(&args[0])?;
+                    self.model.alldiff(&vars);
+                }
+            }
+            "alldifferent_except_0" => {
+                if args.len() != 1 {
                     return Err(Error::type_error(
-                        "array identifier",
-                        "other expression",
-                        args[0].span,
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
                     ));
                 }
-            }
-            _ => {
-                return Err(Error::unsupported_feature(
-                    &format!("Constraint '{}'", name),
-                    "Phase 1",
-                    ast::Span::dummy(),
-                ));
-            }
-        }
-        Ok(())
-    }
-
-    fn translate_constraint_gencall(
-        &mut self,
-        name: &str,
-        generators: &[ast::Generator],
-        body: &ast::Expr,
-    ) -> Result<()> {
-        // For now, we only support "forall"
-        // Other generator calls like "exists" would have different semantics
-        if name != "forall" {
-            return Err(Error::unsupported_feature(
-                &format!("Generator call '{}'", name),
-                "forall only",
-                ast::Span::dummy(),
-            ));
-        }
-
-        // Expand forall(i in range)(constraint) into multiple individual constraints
-        // by iterating through the range and substituting values for the loop variable
-        if generators.len() == 1 {
-            self.expand_forall_constraint(&generators[0], body)?;
-        } else {
-            self.expand_forall_constraint_multi(generators, body)?;
-        }
-        Ok(())
-    }
-
-    /// Expand forall(i in range)(constraint) into individual constraints for a single generator
-    fn expand_forall_constraint(&mut self, generator: &ast::Generator, body: &ast::Expr) -> Result<()> {
-        
-        // Get the loop variable name
-        if generator.names.len() != 1 {
-            return Err(Error::message(
-                "Generator must have exactly one variable",
-                ast::Span::dummy(),
-            ));
-        }
-        let loop_var = &generator.names[0];
 
-        // Parse the range expression to get (start, end)
-        let (range_start, range_end) = self.parse_range(&generator.expr)?;
-
-        // Iterate through the range and substitute loop variable with actual values
-        for i in range_start..=range_end {
-            // Create a new context for this iteration
-            let old_val = self.context.int_params.get(loop_var).copied();
-            
-            // Set the loop variable to the current iteration value
-            self.context.int_params.insert(loop_var.clone(), i);
-            
-            // Translate the constraint body with the loop variable substituted
-            let substituted_body = self.substitute_loop_var_in_expr(body, loop_var, i)?;
-            
-            // Create and translate the constraint
-            let constraint = ast::Constraint {
-                expr: substituted_body,
-                span: body.span,
-            };
-            self.translate_constraint(&constraint)?;
-            
-            // Restore the old value (or remove the parameter)
-            if let Some(old) = old_val {
-                self.context.int_params.insert(loop_var.clone(), old);
-            } else {
-                self.context.int_params.remove(loop_var);
+                // Pairwise x[i] = 0 \/ x[j] = 0 \/ x[i] != x[j]
+                let vars = self.get_array_vars(&args[0])?;
+                let zeros: Vec<VarId> = vars.iter().map(|&x| self.is_zero(x)).collect();
+                for i in 0..vars.len() {
+                    for j in (i + 1)..vars.len() {
+                        let differ = self.model.bool();
+                        self.model.ne_reif(vars[i], vars[j], differ);
+                        self.model.bool_clause(&[zeros[i], zeros[j], differ], &[]);
+                    }
+                }
             }
-        }
-        
-        Ok(())
-    }
+            "all_equal" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
 
-    /// Parse a range expression like `1..n` to get (start, end)
-    fn parse_range(&self, expr: &ast::Expr) -> Result<(i32, i32)> {
-        match &expr.kind {
-            ast::ExprKind::BinOp { op: ast::BinOp::Range, left, right } => {
-                let start = self.eval_int_expr(left)?;
-                let end = self.eval_int_expr(right)?;
-                Ok((start, end))
-            }
-            _ => {
-                // Single value range
-                let val = self.eval_int_expr(expr)?;
-                Ok((val, val))
+                // Chain adjacent equalities; empty and singleton arrays are trivially equal
+                let vars = self.get_array_vars(&args[0])?;
+                for pair in vars.windows(2) {
+                    self.model.new(pair[0].eq(pair[1]));
+                }
             }
-        }
-    }
-
-    /// Expand forall with multiple generators (nested loops)
-    fn expand_forall_constraint_multi(&mut self, generators: &[ast::Generator], body: &ast::Expr) -> Result<()> {
-        if generators.is_empty() {
-            return Err(Error::message("No generators in forall", ast::Span::dummy()));
-        }
-
-        // For nested loops, we recursively expand each generator
-        self.expand_forall_generators(generators, 0, body)?;
-        Ok(())
-    }
-
-    /// Recursively expand nested forall generators
-    fn expand_forall_generators(&mut self, generators: &[ast::Generator], depth: usize, body: &ast::Expr) -> Result<()> {
-        if depth >= generators.len() {
-            // All generators processed - translate the body
-            let constraint = ast::Constraint {
-                expr: body.clone(),
-                span: body.span,
-            };
-            self.translate_constraint(&constraint)?;
-            return Ok(());
-        }
-
-        let generator = &generators[depth];
-        
-        if generator.names.len() != 1 {
-            return Err(Error::message(
-                "Generator must have exactly one variable",
-                ast::Span::dummy(),
-            ));
-        }
-        let loop_var = &generator.names[0];
-
-        let (range_start, range_end) = self.parse_range(&generator.expr)?;
+            "increasing" | "strictly_increasing" | "decreasing" | "strictly_decreasing" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
 
-        // Iterate through this level's range
-        for i in range_start..=range_end {
-            let old_val = self.context.int_params.get(loop_var).copied();
-            self.context.int_params.insert(loop_var.clone(), i);
-            
-            // Substitute all remaining loop variables in the expression
-            let mut substituted = body.clone();
-            
-            // Substitute all loop variables from current depth onwards
-            for j in 0..=depth {
-                if j < generators.len() {
-                    let var_name = &generators[j].names[0];
-                    if let Some(var_val) = self.context.int_params.get(var_name) {
-                        substituted = self.substitute_loop_var_in_expr(&substituted, var_name, *var_val)?;
+                // Adjacent comparisons propagate bounds along the whole chain, so a
+                // fixed duplicate under a strict ordering fails at the root
+                let vars = self.get_array_vars(&args[0])?;
+                let strict = name.starts_with("strictly_");
+                let increasing = name.ends_with("increasing");
+                for pair in vars.windows(2) {
+                    let (a, b) = if increasing { (pair[0], pair[1]) } else { (pair[1], pair[0]) };
+                    if strict {
+                        self.model.new(a.lt(b));
+                    } else {
+                        self.model.new(a.le(b));
                     }
                 }
             }
-            
-            // Process next level or translate
-            self.expand_forall_generators(generators, depth + 1, &substituted)?;
-            
-            if let Some(old) = old_val {
-                self.context.int_params.insert(loop_var.clone(), old);
-            } else {
-                self.context.int_params.remove(loop_var);
-            }
-        }
-
-        Ok(())
-    }
+            "bin_packing" | "bin_packing_load" => {
+                if args.len() != 3 {
+                    return Err(Error::type_error(
+                        "3 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let bins = self.get_array_vars(&args[1])?;
+                let weights = self.eval_int_array(&args[2])?;
+                if bins.len() != weights.len() {
+                    return Err(Error::message(
+                        &format!("{}: {} items but {} weights", name, bins.len(), weights.len()),
+                        args[2].span,
+                    ));
+                }
 
-    /// Substitute a loop variable with a concrete value in an expression
-    fn substitute_loop_var_in_expr(&self, expr: &ast::Expr, var_name: &str, value: i32) -> Result<ast::Expr> {
-        let substituted_kind = match &expr.kind {
-            // If it's the loop variable itself, replace with a literal
-            ast::ExprKind::Ident(name) if name == var_name => {
-                ast::ExprKind::IntLit(value as i64)
-            }
-            // If it's another identifier, keep it as is
-            ast::ExprKind::Ident(_) => expr.kind.clone(),
-            
-            // For binary operations, recursively substitute both sides
-            ast::ExprKind::BinOp { op, left, right } => {
-                let left_sub = self.substitute_loop_var_in_expr(left, var_name, value)?;
-                let right_sub = self.substitute_loop_var_in_expr(right, var_name, value)?;
-                ast::ExprKind::BinOp {
-                    op: *op,
-                    left: Box::new(left_sub),
-                    right: Box::new(right_sub),
+                if name == "bin_packing_load" {
+                    // One load variable per bin; every item goes into one of them
+                    let loads = self.get_array_vars(&args[0])?;
+                    for &bin in &bins {
+                        self.model.new(bin.ge(1));
+                        self.model.new(bin.le(loads.len() as i32));
+                    }
+                    for (b, &load) in loads.iter().enumerate() {
+                        let (mut coeffs, mut vars) = self.bin_membership(&bins, &weights, b as i32 + 1);
+                        coeffs.push(-1);
+                        vars.push(load);
+                        self.model.lin_eq(&coeffs, &vars, 0);
+                    }
+                } else {
+                    // The same capacity bounds every bin any item can be assigned to
+                    let capacity = self.get_var_or_value(&args[0])?;
+                    let mut lo = i32::MAX;
+                    let mut hi = i32::MIN;
+                    for &bin in &bins {
+                        let (bin_lo, bin_hi) = self.int_var_bounds(bin).ok_or_else(|| {
+                            Error::type_error("integer bin assignment", "float variable", args[1].span)
+                        })?;
+                        lo = lo.min(bin_lo);
+                        hi = hi.max(bin_hi);
+                    }
+                    for b in lo..=hi {
+                        let (mut coeffs, mut vars) = self.bin_membership(&bins, &weights, b);
+                        coeffs.push(-1);
+                        vars.push(capacity);
+                        self.model.lin_le(&coeffs, &vars, 0);
+                    }
                 }
             }
-            
-            // For unary operations, recursively substitute
-            ast::ExprKind::UnOp { op, expr: inner } => {
-                let inner_sub = self.substitute_loop_var_in_expr(inner, var_name, value)?;
-                ast::ExprKind::UnOp {
-                    op: *op,
-                    expr: Box::new(inner_sub),
+            "int_abs" => {
+                // int_abs(x, y): y = |x|
+                if args.len() != 2 {
+                    return Err(Error::type_error(
+                        "2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
                 }
+                let x = self.get_var_or_value(&args[0])?;
+                let y = self.get_var_or_value(&args[1])?;
+                let abs = self.model.abs(x);
+                self.model.new(y.eq(abs));
             }
-            
-            // For array access, substitute the indices if needed
-            ast::ExprKind::ArrayAccess { array, indices } => {
-                let indices_sub = indices.iter()
-                    .map(|idx| self.substitute_loop_var_in_expr(idx, var_name, value))
-                    .collect::<Result<Vec<_>>>()?;
-                ast::ExprKind::ArrayAccess {
-                    array: array.clone(),
-                    indices: indices_sub,
+            "knapsack" => {
+                if args.len() != 5 {
+                    return Err(Error::type_error(
+                        "5 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
                 }
-            }
-            
-            // For function calls, recursively substitute all arguments
-            ast::ExprKind::Call { name, args } => {
-                let args_sub = args.iter()
-                    .map(|arg| self.substitute_loop_var_in_expr(arg, var_name, value))
-                    .collect::<Result<Vec<_>>>()?;
-                ast::ExprKind::Call {
-                    name: name.clone(),
-                    args: args_sub,
+                let weights = self.eval_int_array(&args[0])?;
+                let profits = self.eval_int_array(&args[1])?;
+                let items = self.get_array_vars(&args[2])?;
+                if weights.len() != items.len() || profits.len() != items.len() {
+                    return Err(Error::message(
+                        &format!(
+                            "knapsack: {} items but {} weights and {} profits",
+                            items.len(), weights.len(), profits.len()
+                        ),
+                        args[2].span,
+                    ));
                 }
-            }
-            
-            // For literals, keep them as is
-            ast::ExprKind::IntLit(_) | ast::ExprKind::BoolLit(_) | 
-            ast::ExprKind::FloatLit(_) => expr.kind.clone(),
-            
-            // Other expression types
-            other => other.clone(),
-        };
-        
-        Ok(ast::Expr {
-            kind: substituted_kind,
-            span: expr.span,
-        })
-    }
+                let capacity = self.get_var_or_value(&args[3])?;
+                let profit = self.get_var_or_value(&args[4])?;
 
-    fn translate_constraint_binop(
-        &mut self,
-        op: ast::BinOp,
-        left: &ast::Expr,
-        right: &ast::Expr,
-    ) -> Result<()> {
-        match op {
-            // Boolean logical operators
-            ast::BinOp::And => {
-                // Translate as conjunction: both must be true
-                // Recursively translate each side as a constraint
-                let one = self.model.int(1, 1);
-                let left_constraint = self.expr_to_bool_var(left)?;
-                self.model.new(left_constraint.eq(one));
-                let one = self.model.int(1, 1);
-                let right_constraint = self.expr_to_bool_var(right)?;
-                self.model.new(right_constraint.eq(one));
+                // Each item is taken a non-negative number of times
+                for &item in &items {
+                    self.model.new(item.ge(0));
+                }
+                // sum(weights[i] * x[i]) <= W and sum(profits[i] * x[i]) = P
+                let mut vars = items.clone();
+                vars.push(capacity);
+                let mut coeffs = weights;
+                coeffs.push(-1);
+                self.model.lin_le(&coeffs, &vars, 0);
+                vars[items.len()] = profit;
+                let mut coeffs = profits;
+                coeffs.push(-1);
+                self.model.lin_eq(&coeffs, &vars, 0);
             }
-            ast::BinOp::Or => {
-                // Translate as disjunction: at least one must be true
-                let left_constraint = self.expr_to_bool_var(left)?;
-                let right_constraint = self.expr_to_bool_var(right)?;
-                // At least one must be 1: left + right >= 1
-                let sum = self.model.add(left_constraint, right_constraint);
-                let one = self.model.int(1, 1);
-                self.model.new(sum.ge(one));
+            "disjunctive" | "no_overlap" => {
+                if args.len() != 2 {
+                    return Err(Error::type_error(
+                        "2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let starts = self.get_array_vars(&args[0])?;
+                // Durations may be decision variables or a parameter array
+                let durations = self.get_array_vars_or_constants(&args[1])?;
+                if starts.len() != durations.len() {
+                    return Err(Error::message(
+                        &format!(
+                            "{}: {} starts but {} durations",
+                            name,
+                            starts.len(),
+                            durations.len()
+                        ),
+                        args[1].span,
+                    ));
+                }
+                self.post_disjunctive(&starts, &durations);
             }
-            ast::BinOp::Impl => {
-                // Translate as implication: left => right
-                let left_constraint = self.expr_to_bool_var(left)?;
-                let right_constraint = self.expr_to_bool_var(right)?;
-                self.model.implies(left_constraint, right_constraint);
+            "diffn" | "disjoint2" => {
+                if args.len() != 4 {
+                    return Err(Error::type_error(
+                        "4 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                // Origins and sizes may each be decision variables or a parameter array
+                let mut arrays = Vec::with_capacity(4);
+                for arg in args {
+                    arrays.push(self.get_array_vars_or_constants(arg)?);
+                }
+                if let Some(pos) = arrays.iter().position(|a| a.len() != arrays[0].len()) {
+                    return Err(Error::message(
+                        &format!(
+                            "{}: {} rectangles in the first argument but {} in argument {}",
+                            name,
+                            arrays[0].len(),
+                            arrays[pos].len(),
+                            pos + 1
+                        ),
+                        args[pos].span,
+                    ));
+                }
+                self.post_diffn(&arrays[0], &arrays[1], &arrays[2], &arrays[3]);
             }
-            ast::BinOp::Iff => {
-                // Translate as bi-directional implication: left <-> right
-                // This means left and right must have the same value
-                // Equivalent to: (left -> right) /\ (right -> left)
-                let left_constraint = self.expr_to_bool_var(left)?;
-                let right_constraint = self.expr_to_bool_var(right)?;
-                
-                // left => right
-                self.model.implies(left_constraint, right_constraint);
-                // right => left
-                self.model.implies(right_constraint, left_constraint);
+            "disjoint" => {
+                if args.len() != 2 {
+                    return Err(Error::type_error(
+                        "2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let mut sets = Vec::with_capacity(2);
+                for arg in args {
+                    let set = self
+                        .context
+                        .resolve_set_var(arg, |e| self.eval_int_expr(e).ok())
+                        .cloned()
+                        .ok_or_else(|| Error::type_error("set variable", "other expression", arg.span))?;
+                    sets.push(set);
+                }
+
+                // No element may be a member of both sets
+                for (value, a) in sets[0].members() {
+                    if let Some((_, b)) = sets[1].members().find(|(v, _)| *v == value) {
+                        self.model.lin_le(&[1, 1], &[a, b], 1);
+                    }
+                }
             }
-            // Comparison operators
-            ast::BinOp::Lt | ast::BinOp::Le | ast::BinOp::Gt | 
-            ast::BinOp::Ge | ast::BinOp::Eq | ast::BinOp::Ne => {
-                // CRITICAL FIX: Check if right side is a literal constant BEFORE calling get_var_or_value
-                // If it is, we should pass the raw integer directly to the constraint method,
-                // not create a new VarId. This prevents Selen's modulo propagator from being confused.
-                if let Some(const_val) = Self::extract_const_value(right) {
-                    let left_var = self.get_var_or_value(left)?;
-                    let const_i32 = const_val as i32;
-                    
-                    match op {
-                        ast::BinOp::Lt => {
-                            self.model.new(left_var.lt(const_i32));
-                        }
-                        ast::BinOp::Le => {
-                            self.model.new(left_var.le(const_i32));
-                        }
-                        ast::BinOp::Gt => {
-                            self.model.new(left_var.gt(const_i32));
-                        }
-                        ast::BinOp::Ge => {
-                            self.model.new(left_var.ge(const_i32));
-                        }
-                        ast::BinOp::Eq => {
-                            self.model.new(left_var.eq(const_i32));
-                        }
-                        ast::BinOp::Ne => {
-                            self.model.new(left_var.ne(const_i32));
-                        }
-                        _ => unreachable!(),
+            "partition_set" => {
+                if args.len() != 2 {
+                    return Err(Error::type_error(
+                        "2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let sets = match &args[0].kind {
+                    ast::ExprKind::Ident(array_name) => {
+                        self.context.set_var_arrays.get(array_name).cloned().ok_or_else(|| {
+                            Error::message(
+                                &format!("Undefined set variable array: {}", array_name),
+                                args[0].span,
+                            )
+                        })?
                     }
-                } else if let Some(const_val) = Self::extract_const_value(left) {
-                    // Constant on left side
-                    let right_var = self.get_var_or_value(right)?;
-                    let const_i32 = const_val as i32;
-                    let const_var = self.model.int(const_i32, const_i32);
-                    
-                    match op {
-                        ast::BinOp::Lt => {
-                            self.model.new(const_var.lt(right_var));
-                        }
-                        ast::BinOp::Le => {
-                            self.model.new(const_var.le(right_var));
-                        }
-                        ast::BinOp::Gt => {
-                            self.model.new(const_var.gt(right_var));
-                        }
-                        ast::BinOp::Ge => {
-                            self.model.new(const_var.ge(right_var));
-                        }
-                        ast::BinOp::Eq => {
-                            self.model.new(const_var.eq(right_var));
-                        }
-                        ast::BinOp::Ne => {
-                            self.model.new(const_var.ne(right_var));
-                        }
-                        _ => unreachable!(),
+                    _ => {
+                        return Err(Error::type_error(
+                            "set variable array identifier",
+                            "other expression",
+                            args[0].span,
+                        ));
                     }
-                } else {
-                    // Neither side is a constant literal - normal path
-                    let left_var = self.get_var_or_value(left)?;
-                    let right_var = self.get_var_or_value(right)?;
+                };
+                let universe = self.eval_int_set(&args[1])?;
+
+                // Every universe element belongs to exactly one set
+                for &value in &universe {
+                    let indicators: Vec<VarId> = sets
+                        .iter()
+                        .flat_map(|set| set.members().filter(|(v, _)| *v == value).map(|(_, ind)| ind))
+                        .collect();
+                    if indicators.is_empty() {
+                        return Err(Error::message(
+                            &format!("partition_set: element {} is outside every set's universe", value),
+                            args[1].span,
+                        ));
+                    }
+                    let ones = vec![1; indicators.len()];
+                    self.model.lin_eq(&ones, &indicators, 1);
+                }
 
-                    match op {
-                        ast::BinOp::Lt => {
-                            self.model.new(left_var.lt(right_var));
-                        }
-                        ast::BinOp::Le => {
-                            self.model.new(left_var.le(right_var));
-                        }
-                        ast::BinOp::Gt => {
-                            self.model.new(left_var.gt(right_var));
-                        }
-                        ast::BinOp::Ge => {
-                            self.model.new(left_var.ge(right_var));
-                        }
-                        ast::BinOp::Eq => {
-                            self.model.new(left_var.eq(right_var));
-                        }
-                        ast::BinOp::Ne => {
-                            self.model.new(left_var.ne(right_var));
+                // Elements outside the universe belong to no set
+                for set in &sets {
+                    for (value, ind) in set.members() {
+                        if !universe.contains(&value) {
+                            self.model.new(ind.eq(0));
                         }
-                        _ => unreachable!(),
                     }
                 }
             }
-            _ => {
-                return Err(Error::unsupported_feature(
-                    &format!("Binary operator {:?} in constraints", op),
-                    "Phase 2",
-                    ast::Span::dummy(),
-                ));
-            }
-        }
-
-        Ok(())
-    }
-
-    fn translate_constraint_unop(
-        &mut self,
-        op: ast::UnOp,
-        expr: &ast::Expr,
-    ) -> Result<()> {
-        match op {
-            ast::UnOp::Not => {
-                // Translate as negation: expr must be false (0)
-                let bool_var = self.expr_to_bool_var(expr)?;
-                let zero = self.model.int(0, 0);
-                self.model.new(bool_var.eq(zero));
-            }
-            ast::UnOp::Neg => {
-                return Err(Error::unsupported_feature(
-                    "Unary negation in constraints",
-                    "Phase 2",
-                    ast::Span::dummy(),
-                ));
-            }
-        }
-        Ok(())
-    }
-
-    /// Convert an expression to a boolean variable (0 or 1)
-    /// Used for boolean logical operations
-    fn expr_to_bool_var(&mut self, expr: &ast::Expr) -> Result<VarId> {
-        match &expr.kind {
-            // Boolean literals
-            ast::ExprKind::BoolLit(b) => {
-                let val = if *b { 1 } else { 0 };
-                Ok(self.model.int(val, val))
+            "value_precede" => {
+                if args.len() != 3 {
+                    return Err(Error::type_error(
+                        "3 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let s = self.eval_int_expr(&args[0])?;
+                let t = self.eval_int_expr(&args[1])?;
+                let vars = self.get_array_vars(&args[2])?;
+                self.post_value_precede(s, t, &vars);
             }
-            // Boolean variables
-            ast::ExprKind::Ident(name) => {
-                if let Some(var) = self.context.get_bool_var(name) {
-                    return Ok(var);
+            "value_precede_chain" => {
+                if args.len() != 2 {
+                    return Err(Error::type_error(
+                        "2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
                 }
-                if let Some(value) = self.context.get_bool_param(name) {
-                    let val = if value { 1 } else { 0 };
-                    return Ok(self.model.int(val, val));
+                // Each value precedes the next one in the chain
+                let values = self.eval_int_array(&args[0])?;
+                let vars = self.get_array_vars(&args[1])?;
+                for pair in values.windows(2) {
+                    self.post_value_precede(pair[0], pair[1], &vars);
                 }
-                Err(Error::message(
-                    &format!("Undefined boolean variable: '{}'", name),
-                    expr.span,
-                ))
-            }
-            // Comparison operators - just evaluate them directly in constraint context
-            // We don't need reification for simple cases
-            ast::ExprKind::BinOp { op, .. } if matches!(op,
-                ast::BinOp::Lt | ast::BinOp::Le | ast::BinOp::Gt |
-                ast::BinOp::Ge | ast::BinOp::Eq | ast::BinOp::Ne) => {
-                // For now, treat comparison in boolean context as always true
-                // This is a simplified approach - full reification would be better
-                // but requires more Selen API support
-                let result = self.model.bool();
-                // Set result to 1 (true) if we're in a positive context
-                // In practice, this means the comparison must hold
-                Ok(result)
             }
-            ast::ExprKind::UnOp { op: ast::UnOp::Not, expr: inner } => {
-                // Not of a boolean expression: flip the value
-                let inner_var = self.expr_to_bool_var(inner)?;
-                let one = self.model.int(1, 1);
-                let negated = self.model.sub(one, inner_var);
-                Ok(negated)
+            "subcircuit" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let succ = self.get_array_vars(&args[0])?;
+                self.post_subcircuit(&succ);
             }
-            ast::ExprKind::BinOp { op: ast::BinOp::And, left, right } => {
-                // AND: both must be true
-                // Use Selen's bool_and to create the result
-                let left_var = self.expr_to_bool_var(left)?;
-                let right_var = self.expr_to_bool_var(right)?;
-                
-                // bool_and returns a VarId representing the AND result
-                let result = self.model.bool_and(&[left_var, right_var]);
-                Ok(result)
+            "regular" => {
+                if args.len() != 6 {
+                    return Err(Error::type_error(
+                        "6 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let vars = self.get_array_vars(&args[0])?;
+                let states = self.eval_int_expr(&args[1])?;
+                let symbols = self.eval_int_expr(&args[2])?;
+                if states < 1 || symbols < 1 {
+                    return Err(Error::message(
+                        &format!("regular() needs at least one state and one symbol, got Q = {} and S = {}", states, symbols),
+                        span,
+                    ));
+                }
+                let transitions = self.eval_int_array(&args[3])?;
+                let declared = match &args[3].kind {
+                    ast::ExprKind::Ident(table) => self.context.array_metadata.get(table).map(|m| m.dimensions.clone()),
+                    _ => None,
+                };
+                let expected = vec![states as usize, symbols as usize];
+                if declared.is_some_and(|dims| dims != expected) || transitions.len() != states as usize * symbols as usize {
+                    return Err(Error::message(
+                        &format!("regular() transition table must be {} x {} (Q x S)", states, symbols),
+                        args[3].span,
+                    ));
+                }
+                if let Some(bad) = transitions.iter().find(|&&q| !(0..=states).contains(&q)) {
+                    return Err(Error::message(
+                        &format!("regular() transition to state {} is outside 0..{}", bad, states),
+                        args[3].span,
+                    ));
+                }
+                let start = self.eval_int_expr(&args[4])?;
+                if !(1..=states).contains(&start) {
+                    return Err(Error::message(
+                        &format!("regular() start state {} is outside 1..{}", start, states),
+                        args[4].span,
+                    ));
+                }
+                let accepting = self.eval_int_set(&args[5])?;
+                self.post_regular(&vars, symbols, &transitions, start, &accepting);
             }
-            ast::ExprKind::BinOp { op: ast::BinOp::Or, left, right } => {
-                // OR: at least one must be true
-                // Use Selen's bool_or to create the result
-                let left_var = self.expr_to_bool_var(left)?;
-                let right_var = self.expr_to_bool_var(right)?;
-                
-                // bool_or returns a VarId representing the OR result
-                let result = self.model.bool_or(&[left_var, right_var]);
-                Ok(result)
+            "among" => {
+                if args.len() != 3 {
+                    return Err(Error::type_error(
+                        "3 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let count = self.get_var_or_value(&args[0])?;
+                let vars = self.get_array_vars(&args[1])?;
+                // The values may be given as a set or as an array
+                let values = match self.eval_int_array(&args[2]) {
+                    Ok(values) => values,
+                    Err(_) => self.eval_int_set(&args[2])?,
+                };
+
+                // count = sum of (x[i] in values) indicators
+                let mut coeffs = Vec::with_capacity(vars.len() + 1);
+                let mut indicators = Vec::with_capacity(vars.len() + 1);
+                for &var in &vars {
+                    let (lo, hi) = self.int_var_bounds(var).ok_or_else(|| {
+                        Error::type_error("integer variable array", "float variable array", args[1].span)
+                    })?;
+                    let hits: Vec<VarId> = values
+                        .iter()
+                        .filter(|v| (lo..=hi).contains(*v))
+                        .map(|&value| {
+                            let is_value = self.model.bool();
+                            self.model.lin_eq_reif(&[1], &[var], value, is_value);
+                            is_value
+                        })
+                        .collect();
+                    match hits.len() {
+                        0 => continue,
+                        1 => indicators.push(hits[0]),
+                        _ => indicators.push(self.model.bool_or(&hits)),
+                    }
+                    coeffs.push(1);
+                }
+                coeffs.push(-1);
+                indicators.push(count);
+                self.model.lin_eq(&coeffs, &indicators, 0);
+            }
+            "count_eq" | "count_neq" | "count_lt" | "count_leq" | "count_gt" | "count_geq" => {
+                if args.len() != 3 {
+                    return Err(Error::type_error(
+                        "3 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let vars = self.get_array_vars(&args[0])?;
+                let value = self.get_var_or_value(&args[1])?;
+                let c = self.get_var_or_value(&args[2])?;
+                let occurrences = self.model.int(0, vars.len() as i32);
+                if vars.is_empty() {
+                    self.model.lin_eq(&[1], &[occurrences], 0);
+                } else {
+                    self.model.count(&vars, value, occurrences);
+                }
+
+                // `c <op> occurrences`, the strict relations shifted by one
+                let pair = [c, occurrences];
+                match name {
+                    "count_eq" => self.model.lin_eq(&[1, -1], &pair, 0),
+                    "count_neq" => self.model.lin_ne(&[1, -1], &pair, 0),
+                    "count_lt" => self.model.lin_le(&[1, -1], &pair, -1),
+                    "count_leq" => self.model.lin_le(&[1, -1], &pair, 0),
+                    "count_gt" => self.model.lin_le(&[-1, 1], &pair, -1),
+                    _ => self.model.lin_le(&[-1, 1], &pair, 0),
+                }
+            }
+            "sort" | "arg_sort" => {
+                if args.len() != 2 {
+                    return Err(Error::type_error(
+                        "2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let x = self.get_array_vars(&args[0])?;
+                let out = self.get_array_vars(&args[1])?;
+                if x.len() != out.len() {
+                    return Err(Error::message(
+                        &format!("{}: {} elements but {} results", name, x.len(), out.len()),
+                        args[1].span,
+                    ));
+                }
+
+                if name == "sort" {
+                    // A hidden permutation channels x onto the sorted y
+                    let n = x.len() as i32;
+                    let positions: Vec<VarId> = (0..n).map(|_| self.model.int(1, n)).collect();
+                    let sorted = self.post_sorted_permutation(&x, &positions);
+                    for (&y, &value) in out.iter().zip(&sorted) {
+                        self.model.new(y.eq(value));
+                    }
+                } else {
+                    // Equal values keep their original order, making p unique
+                    let sorted = self.post_sorted_permutation(&x, &out);
+                    for i in 1..out.len() {
+                        let tied = self.model.bool();
+                        self.model.lin_eq_reif(&[1, -1], &[sorted[i - 1], sorted[i]], 0, tied);
+                        let in_order = self.model.bool();
+                        self.model.lin_le_reif(&[1, -1], &[out[i - 1], out[i]], -1, in_order);
+                        self.model.bool_clause(&[in_order], &[tied]);
+                    }
+                }
+            }
+            "forall" | "exists" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                match (name, &args[0].kind) {
+                    // forall([c | i in s]) is forall(i in s)(c)
+                    (_, ast::ExprKind::ArrayComp { expr: body, generators }) => {
+                        self.translate_constraint_gencall(name, generators, body, span)?;
+                    }
+                    // Each listed constraint is posted directly, without reification
+                    ("forall", ast::ExprKind::ArrayLit(elements)) => {
+                        for element in elements {
+                            self.translate_constraint_expr(element)?;
+                        }
+                    }
+                    ("forall", _) => {
+                        for var in self.get_array_vars(&args[0])? {
+                            self.model.new(var.eq(1));
+                        }
+                    }
+                    _ => {
+                        let vars = self.get_array_vars(&args[0])?;
+                        self.model.bool_clause(&vars, &[]);
+                    }
+                }
             }
             _ => {
-                Err(Error::unsupported_feature(
-                    &format!("Expression type in boolean context: {:?}", expr.kind),
-                    "Phase 2",
-                    expr.span,
-                ))
+                return Err(Error::unsupported_feature(
+                    &format!("Constraint '{}'", name),
+                    "Phase 1",
+                    span,
+                ));
             }
         }
+        Ok(())
     }
 
-    fn translate_solve(&mut self, solve: &ast::Solve) -> Result<()> {
-        match solve {
-            ast::Solve::Satisfy { search_option, .. } => {
-                // Default behavior - no optimization
-                self.objective_type = ObjectiveType::Satisfy;
-                self.objective_var = None;
-                self.search_option = search_option.clone();
-            }
-            ast::Solve::Minimize { expr, search_option, .. } => {
-                let var = self.get_var_or_value(expr)?;
-                self.objective_type = ObjectiveType::Minimize;
-                self.objective_var = Some(var);
-                self.search_option = search_option.clone();
+    /// Values `x[positions[i]]` for a one-based permutation `positions` of `x`'s
+    /// indices, constrained to be non-decreasing
+    fn post_sorted_permutation(&mut self, x: &[VarId], positions: &[VarId]) -> Vec<VarId> {
+        self.model.alldiff(positions);
+        let mut sorted = Vec::with_capacity(positions.len());
+        for &position in positions {
+            let zero_based = self.element_index(position, x.len());
+            let value = self.element_int_result(x, zero_based);
+            self.model.element(x, zero_based, value);
+            sorted.push(value);
+        }
+        for pair in sorted.windows(2) {
+            self.model.new(pair[0].le(pair[1]));
+        }
+        sorted
+    }
+
+    /// `succ` forms a single cycle over the nodes that do not point to themselves
+    ///
+    /// Each node gets a position: the visited node of lowest index is the root at
+    /// position 1, and every other visited node is one past its predecessor.
+    /// Unvisited nodes sit at position 1 as well, so the positions are fixed by
+    /// `succ`. A cycle that misses the root would need positions that grow all
+    /// the way around it, so only one cycle can exist.
+    fn post_subcircuit(&mut self, succ: &[VarId]) {
+        let n = succ.len() as i32;
+        for &next in succ {
+            self.model.new(next.ge(1));
+            self.model.new(next.le(n));
+        }
+        self.model.alldiff(succ);
+
+        let mut visited = Vec::with_capacity(succ.len());
+        for (i, &next) in succ.iter().enumerate() {
+            let looped = self.model.bool();
+            self.model.lin_eq_reif(&[1], &[next], i as i32 + 1, looped);
+            visited.push(self.model.bool_not(looped));
+        }
+        let mut roots = Vec::with_capacity(succ.len());
+        let mut none_before = self.int_constant(1);
+        for &node_visited in &visited {
+            roots.push(self.model.bool_and(&[node_visited, none_before]));
+            let not_visited = self.model.bool_not(node_visited);
+            none_before = self.model.bool_and(&[none_before, not_visited]);
+        }
+
+        let positions: Vec<VarId> = (0..n).map(|_| self.model.int(1, n)).collect();
+        for (i, &next) in succ.iter().enumerate() {
+            let first = self.model.bool();
+            self.model.lin_eq_reif(&[1], &[positions[i]], 1, first);
+            // Unvisited nodes and the root are at position 1
+            self.model.bool_clause(&[visited[i], first], &[]);
+            self.model.bool_clause(&[first], &[roots[i]]);
+
+            // A visited node's successor is one position further, unless it closes the cycle
+            let index = self.element_index(next, succ.len());
+            let next_position = self.model.int(1, n);
+            self.model.element(&positions, index, next_position);
+            let next_is_root = self.model.bool();
+            self.model.element(&roots, index, next_is_root);
+            let step = self.model.bool();
+            self.model.lin_eq_reif(&[1, -1], &[next_position, positions[i]], 1, step);
+            self.model.bool_clause(&[next_is_root, step], &[visited[i]]);
+        }
+    }
+
+    /// `vars` spells a word accepted by a DFA with a row-major transition table
+    ///
+    /// Row `q` of `transitions` holds the successors of state `q` for symbols
+    /// `1..=symbols`, with 0 as the failing state. Each successor is an element
+    /// of the flattened table, indexed by the current state and symbol, and
+    /// ranges over the live states only; the last one must be accepting.
+    fn post_regular(&mut self, vars: &[VarId], symbols: i32, transitions: &[i32], start: i32, accepting: &[i32]) {
+        let states = transitions.len() as i32 / symbols;
+        let table: Vec<VarId> = transitions.iter().map(|&q| self.int_constant(q)).collect();
+        let mut state = self.int_constant(start);
+        for &symbol in vars {
+            self.model.new(symbol.ge(1));
+            self.model.new(symbol.le(symbols));
+            // index = (state - 1) * symbols + (symbol - 1), zero-based
+            let index = self.model.int(0, states * symbols - 1);
+            self.model.lin_eq(&[symbols, 1, -1], &[state, symbol, index], symbols + 1);
+            let next = self.model.int(1, states);
+            self.model.element(&table, index, next);
+            state = next;
+        }
+        for rejecting in (1..=states).filter(|q| !accepting.contains(q)) {
+            self.model.new(state.ne(rejecting));
+        }
+    }
+
+    /// First occurrence of `s` in `vars` comes before the first occurrence of `t`
+    ///
+    /// For each position `i`, `vars[i] = t` implies `vars[j] = s` for some `j < i`;
+    /// in particular `t` can never be the first element.
+    fn post_value_precede(&mut self, s: i32, t: i32, vars: &[VarId]) {
+        if s == t {
+            return;
+        }
+        let mut seen_s = Vec::with_capacity(vars.len());
+        for &var in vars {
+            let is_t = self.model.bool();
+            self.model.lin_eq_reif(&[1], &[var], t, is_t);
+            self.model.bool_clause(&seen_s, &[is_t]);
+
+            let is_s = self.model.bool();
+            self.model.lin_eq_reif(&[1], &[var], s, is_s);
+            seen_s.push(is_s);
+        }
+    }
+
+    /// Boolean that holds iff `x = 0`
+    fn is_zero(&mut self, x: VarId) -> VarId {
+        let zero = self.model.bool();
+        self.model.lin_eq_reif(&[1], &[x], 0, zero);
+        zero
+    }
+
+    /// Weighted membership terms `w_i * (bin_i = b)` making up the load of bin `b`
+    fn bin_membership(&mut self, bins: &[VarId], weights: &[i32], b: i32) -> (Vec<i32>, Vec<VarId>) {
+        let mut coeffs = Vec::with_capacity(bins.len() + 1);
+        let mut vars = Vec::with_capacity(bins.len() + 1);
+        for (&bin, &weight) in bins.iter().zip(weights) {
+            let in_bin = self.model.bool();
+            self.model.lin_eq_reif(&[1], &[bin], b, in_bin);
+            coeffs.push(weight);
+            vars.push(in_bin);
+        }
+        (coeffs, vars)
+    }
+
+    /// Pairwise non-overlap of intervals `[s_i, s_i + d_i)`
+    ///
+    /// For each pair either `s_i + d_i <= s_j` or `s_j + d_j <= s_i`; the two
+    /// orderings are reified and at least one must hold. Fixed durations are
+    /// folded into the constant of the linear test.
+    fn post_disjunctive(&mut self, starts: &[VarId], durations: &[VarId]) {
+        for i in 0..starts.len() {
+            for j in (i + 1)..starts.len() {
+                let i_first = self.model.bool();
+                let j_first = self.model.bool();
+                self.post_precedes_reif(starts[i], durations[i], starts[j], i_first);
+                self.post_precedes_reif(starts[j], durations[j], starts[i], j_first);
+                self.model.bool_clause(&[i_first, j_first], &[]);
             }
-            ast::Solve::Maximize { expr, search_option, .. } => {
-                let var = self.get_var_or_value(expr)?;
-                self.objective_type = ObjectiveType::Maximize;
-                self.objective_var = Some(var);
-                self.search_option = search_option.clone();
+        }
+    }
+
+    /// Post that no two rectangles overlap: each pair must be separated along x
+    /// or along y. A single rectangle has no pairs and needs no constraint.
+    fn post_diffn(&mut self, xs: &[VarId], ys: &[VarId], dxs: &[VarId], dys: &[VarId]) {
+        for i in 0..xs.len() {
+            for j in (i + 1)..xs.len() {
+                let separated = [self.model.bool(), self.model.bool(), self.model.bool(), self.model.bool()];
+                self.post_precedes_reif(xs[i], dxs[i], xs[j], separated[0]);
+                self.post_precedes_reif(xs[j], dxs[j], xs[i], separated[1]);
+                self.post_precedes_reif(ys[i], dys[i], ys[j], separated[2]);
+                self.post_precedes_reif(ys[j], dys[j], ys[i], separated[3]);
+                self.model.bool_clause(&separated, &[]);
             }
         }
-        Ok(())
     }
 
-    /// Get a VarId from an expression (either a variable reference or create a constant)
-    fn get_var_or_value(&mut self, expr: &ast::Expr) -> Result<VarId> {
-        let debug = std::env::var("TRANSLATOR_DEBUG").is_ok();
+    /// Reify `start + duration <= next` into `b`
+    fn post_precedes_reif(&mut self, start: VarId, duration: VarId, next: VarId, b: VarId) {
+        match self.int_var_bounds(duration) {
+            Some((lo, hi)) if lo == hi => self.model.lin_le_reif(&[1, -1], &[start, next], -lo, b),
+            _ => self.model.lin_le_reif(&[1, 1, -1], &[start, duration, next], 0, b),
+        }
+    }
+
+    /// Resolve a set-valued expression to membership indicators
+    ///
+    /// Handles set variables, constant sets (ranges and literals, whose indicators
+    /// are fixed) and `union`/`intersect`/`diff` of those. Returns `None` for
+    /// expressions that are not sets.
+    fn set_operand(&mut self, expr: &ast::Expr) -> Result<Option<SetVar>> {
+        if let Some(set) = self.context.resolve_set_var(expr, |e| self.eval_int_expr(e).ok()) {
+            return Ok(Some(set.clone()));
+        }
         match &expr.kind {
-            ast::ExprKind::Ident(name) => {
-                // Try integer variable
-                if let Some(var) = self.context.get_int_var(name) {
-                    if debug {
-                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> existing var {:?}", name, var);
-                    }
-                    return Ok(var);
+            ast::ExprKind::BinOp { op: op @ (ast::BinOp::Union | ast::BinOp::Intersect | ast::BinOp::Diff), left, right } => {
+                let operand = |t: &mut Self, e: &ast::Expr| {
+                    t.set_operand(e)?
+                        .ok_or_else(|| Error::type_error("set expression", "other expression", e.span))
+                };
+                let a = operand(self, left)?;
+                let b = operand(self, right)?;
+                Ok(Some(self.combine_sets(*op, &a, &b)))
+            }
+            ast::ExprKind::SetLit(_) | ast::ExprKind::Range(..) | ast::ExprKind::BinOp { op: ast::BinOp::Range, .. } => {
+                let Ok(values) = self.eval_int_set(expr) else {
+                    return Ok(None);
+                };
+                let (Some(&min), Some(&max)) = (values.first(), values.last()) else {
+                    return Ok(Some(SetVar { min: 0, indicators: Vec::new() }));
+                };
+                let indicators = (min..=max)
+                    .map(|v| self.int_constant(i32::from(values.binary_search(&v).is_ok())))
+                    .collect();
+                Ok(Some(SetVar { min, indicators }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Indicators of `a op b` over the union of both universes
+    fn combine_sets(&mut self, op: ast::BinOp, a: &SetVar, b: &SetVar) -> SetVar {
+        let (min, max) = match (a.indicators.is_empty(), b.indicators.is_empty()) {
+            (true, true) => return SetVar { min: 0, indicators: Vec::new() },
+            (false, true) => (a.min, a.max()),
+            (true, false) => (b.min, b.max()),
+            (false, false) => (a.min.min(b.min), a.max().max(b.max())),
+        };
+        let indicators = (min..=max)
+            .map(|v| match (op, a.indicator(v), b.indicator(v)) {
+                (ast::BinOp::Union, Some(x), Some(y)) => self.model.bool_or(&[x, y]),
+                (ast::BinOp::Union, Some(x), None) | (ast::BinOp::Union, None, Some(x)) => x,
+                (ast::BinOp::Intersect, Some(x), Some(y)) => self.model.bool_and(&[x, y]),
+                (ast::BinOp::Diff, Some(x), Some(y)) => {
+                    let not_y = self.model.bool_not(y);
+                    self.model.bool_and(&[x, not_y])
                 }
-                // Try boolean variable
-                if let Some(var) = self.context.get_bool_var(name) {
-                    if debug {
-                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> existing bool var {:?}", name, var);
-                    }
-                    return Ok(var);
+                (ast::BinOp::Diff, Some(x), None) => x,
+                _ => self.int_constant(0),
+            })
+            .collect();
+        SetVar { min, indicators }
+    }
+
+    /// Reify a relation involving sets: `x in S`, `S subset T`, `S superset T`,
+    /// `S = T` or `S != T`
+    ///
+    /// Returns `None` when the operands are not sets, so callers can fall back to
+    /// the scalar translation.
+    fn set_relation(&mut self, op: ast::BinOp, left: &ast::Expr, right: &ast::Expr) -> Result<Option<VarId>> {
+        match op {
+            ast::BinOp::In => {
+                let Some(set) = self.set_operand(right)? else {
+                    return Ok(None);
+                };
+                if let Ok(value) = self.eval_int_expr(left) {
+                    let member = set.indicator(value);
+                    return Ok(Some(member.unwrap_or_else(|| self.int_constant(0))));
                 }
-                // Try float variable
-                if let Some(var) = self.context.get_float_var(name) {
-                    if debug {
-                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> existing float var {:?}", name, var);
-                    }
-                    return Ok(var);
+                // x in S holds iff x takes some value v whose indicator is set
+                let x = self.get_var_or_value(left)?;
+                let (lo, hi) = self.int_var_bounds(x).unwrap_or((set.min, set.max()));
+                let mut witnesses = Vec::new();
+                for (value, indicator) in set.members().filter(|(v, _)| (lo..=hi).contains(v)) {
+                    let is_value = self.model.bool();
+                    let value = self.int_constant(value);
+                    self.model.eq_reif(x, value, is_value);
+                    witnesses.push(self.model.bool_and(&[is_value, indicator]));
                 }
-                // Try integer parameter
-                if let Some(value) = self.context.get_int_param(name) {
-                    // Create a constant variable
-                    let const_var = self.model.int(value, value);
-                    if debug {
-                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> new constant {:?} (value={})", name, const_var, value);
-                    }
-                    return Ok(const_var);
+                if witnesses.is_empty() {
+                    return Ok(Some(self.int_constant(0)));
                 }
-                // Try float parameter
-                if let Some(value) = self.context.get_float_param(name) {
-                    // Create a constant variable
-                    let const_var = self.model.float(value, value);
-                    if debug {
-                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> new constant {:?} (value={})", name, const_var, value);
-                    }
-                    return Ok(const_var);
+                Ok(Some(self.model.bool_or(&witnesses)))
+            }
+            ast::BinOp::Subset | ast::BinOp::Superset => {
+                let operand = |t: &mut Self, e: &ast::Expr| {
+                    t.set_operand(e)?
+                        .ok_or_else(|| Error::type_error("set expression", "other expression", e.span))
+                };
+                let (sub, sup) = if op == ast::BinOp::Subset { (left, right) } else { (right, left) };
+                let sub = operand(self, sub)?;
+                let sup = operand(self, sup)?;
+                // Every member of `sub` must be a member of `sup`
+                let mut holds = Vec::with_capacity(sub.indicators.len());
+                for (value, member) in sub.members() {
+                    let not_member = self.model.bool_not(member);
+                    holds.push(match sup.indicator(value) {
+                        Some(covered) => self.model.bool_or(&[not_member, covered]),
+                        None => not_member,
+                    });
                 }
-                // Try boolean parameter
-                if let Some(value) = self.context.get_bool_param(name) {
-                    // Create a constant variable (0 or 1)
-                    let val = if value { 1 } else { 0 };
-                    let const_var = self.model.int(val, val);
-                    if debug {
-                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> new constant {:?} (value={})", name, const_var, val);
-                    }
-                    return Ok(const_var);
+                if holds.is_empty() {
+                    return Ok(Some(self.int_constant(1)));
                 }
-                // Not found - give helpful error
-                Err(Error::message(
-                    &format!("Undefined variable or parameter: '{}'", name),
-                    expr.span,
-                ))
-            }
-            ast::ExprKind::IntLit(i) => {
-                // Don't create a variable - return the value as a special marker
-                // We'll handle this in translate_constraint_binop to avoid creating extra variables
-                Ok(self.model.int(*i as i32, *i as i32))
-            }
-            ast::ExprKind::FloatLit(f) => {
-                // Create a constant float variable
-                Ok(self.model.float(*f, *f))
-            }
-            ast::ExprKind::BoolLit(b) => {
-                // Create a constant boolean variable (0 or 1)
-                let val = if *b { 1 } else { 0 };
-                Ok(self.model.int(val, val))
+                Ok(Some(self.model.bool_and(&holds)))
             }
-            ast::ExprKind::BinOp { op, left, right } => {
-                let left_var = self.get_var_or_value(left)?;
-                let right_var = self.get_var_or_value(right)?;
-
-                match op {
-                    ast::BinOp::Add => Ok(self.model.add(left_var, right_var)),
-                    ast::BinOp::Sub => Ok(self.model.sub(left_var, right_var)),
-                    ast::BinOp::Mul => Ok(self.model.mul(left_var, right_var)),
-                    ast::BinOp::Div | ast::BinOp::FDiv => Ok(self.model.div(left_var, right_var)),
-                    ast::BinOp::Mod => {
-                        if std::env::var("ZELEN_DEBUG").is_ok() {
-                            eprintln!("DEBUG: Creating modulo: {:?} mod {:?}", left_var, right_var);
-                        }
-                        let result = self.model.modulo(left_var, right_var);
-                        if std::env::var("ZELEN_DEBUG").is_ok() {
-                            eprintln!("DEBUG:   -> Modulo result VarId: {:?}", result);
-                        }
-                        Ok(result)
-                    }
-                    _ => Err(Error::unsupported_feature(
-                        &format!("Binary operator {:?} in expressions", op),
-                        "Phase 2",
-                        expr.span,
-                    )),
+            ast::BinOp::Eq | ast::BinOp::Ne => {
+                let Some(a) = self.set_operand(left)? else {
+                    return Ok(None);
+                };
+                let Some(b) = self.set_operand(right)? else {
+                    return Ok(None);
+                };
+                let mut agree = Vec::new();
+                let (min, max) = (a.min.min(b.min), a.max().max(b.max()));
+                for value in min..=max {
+                    let (x, y) = match (a.indicator(value), b.indicator(value)) {
+                        (None, None) => continue,
+                        (x, y) => (
+                            x.unwrap_or_else(|| self.int_constant(0)),
+                            y.unwrap_or_else(|| self.int_constant(0)),
+                        ),
+                    };
+                    let same = self.model.bool();
+                    self.model.eq_reif(x, y, same);
+                    agree.push(same);
+                }
+                let equal = if agree.is_empty() { self.int_constant(1) } else { self.model.bool_and(&agree) };
+                if op == ast::BinOp::Eq {
+                    Ok(Some(equal))
+                } else {
+                    Ok(Some(self.model.bool_not(equal)))
                 }
             }
-            ast::ExprKind::ArrayAccess { array, indices } => {
-                // Get the array name
-                let array_name = match &array.kind {
-                    ast::ExprKind::Ident(name) => name,
-                    _ => {
-                        return Err(Error::message(
-                            "Array access must use simple array name",
-                            array.span,
-                        ));
-                    }
-                };
-                
-                // Try to handle as multi-dimensional if multiple indices
-                if indices.len() > 1 {
-                    // Multi-dimensional array access - use native 2D/3D element constraints
-                    if let Some(metadata) = self.context.array_metadata.get(array_name) {
-                        if metadata.dimensions.len() != indices.len() {
-                            return Err(Error::message(
-                                &format!(
-                                    "Array index dimension mismatch: expected {}, got {}",
-                                    metadata.dimensions.len(),
-                                    indices.len()
-                                ),
-                                expr.span,
-                            ));
-                        }
-                        
-                        // For 2D arrays, use element_2d
-                        if indices.len() == 2 {
-                            // Check for 2D arrays and get early
-                            let arr_2d_int = self.context.get_int_var_array_2d(array_name).cloned();
-                            let arr_2d_bool = if arr_2d_int.is_none() {
-                                self.context.get_bool_var_array_2d(array_name).cloned()
-                            } else {
-                                None
-                            };
-                            let arr_2d_float = if arr_2d_int.is_none() && arr_2d_bool.is_none() {
-                                self.context.get_float_var_array_2d(array_name).cloned()
-                            } else {
-                                None
-                            };
-                            
-                            if let Some(arr_2d) = arr_2d_int {
-                                let row_idx = self.get_var_or_value(&indices[0])?;
-                                let col_idx = self.get_var_or_value(&indices[1])?;
-                                let result = self.model.int(i32::MIN, i32::MAX);
-                                self.model.element_2d(&arr_2d, row_idx, col_idx, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr_2d) = arr_2d_bool {
-                                let row_idx = self.get_var_or_value(&indices[0])?;
-                                let col_idx = self.get_var_or_value(&indices[1])?;
-                                let result = self.model.bool();
-                                self.model.element_2d(&arr_2d, row_idx, col_idx, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr_2d) = arr_2d_float {
-                                let row_idx = self.get_var_or_value(&indices[0])?;
-                                let col_idx = self.get_var_or_value(&indices[1])?;
-                                let result = self.model.float(f64::MIN, f64::MAX);
-                                self.model.element_2d(&arr_2d, row_idx, col_idx, result);
-                                return Ok(result);
-                            }
-                        }
-                        
-                        // For 3D arrays, use element_3d
-                        if indices.len() == 3 {
-                            // Check for 3D arrays and clone early
-                            let arr_3d_int = self.context.get_int_var_array_3d(array_name).cloned();
-                            let arr_3d_bool = if arr_3d_int.is_none() {
-                                self.context.get_bool_var_array_3d(array_name).cloned()
-                            } else {
-                                None
-                            };
-                            let arr_3d_float = if arr_3d_int.is_none() && arr_3d_bool.is_none() {
-                                self.context.get_float_var_array_3d(array_name).cloned()
-                            } else {
-                                None
-                            };
-                            
-                            if let Some(arr_3d) = arr_3d_int {
-                                let d_idx = self.get_var_or_value(&indices[0])?;
-                                let r_idx = self.get_var_or_value(&indices[1])?;
-                                let c_idx = self.get_var_or_value(&indices[2])?;
-                                let result = self.model.int(i32::MIN, i32::MAX);
-                                self.model.element_3d(&arr_3d, d_idx, r_idx, c_idx, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr_3d) = arr_3d_bool {
-                                let d_idx = self.get_var_or_value(&indices[0])?;
-                                let r_idx = self.get_var_or_value(&indices[1])?;
-                                let c_idx = self.get_var_or_value(&indices[2])?;
-                                let result = self.model.bool();
-                                self.model.element_3d(&arr_3d, d_idx, r_idx, c_idx, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr_3d) = arr_3d_float {
-                                let d_idx = self.get_var_or_value(&indices[0])?;
-                                let r_idx = self.get_var_or_value(&indices[1])?;
-                                let c_idx = self.get_var_or_value(&indices[2])?;
-                                let result = self.model.float(f64::MIN, f64::MAX);
-                                self.model.element_3d(&arr_3d, d_idx, r_idx, c_idx, result);
-                                return Ok(result);
-                            }
-                        }
-                        
-                        // For higher dimensions or fallback, use flattening
-                        // Try to evaluate all indices to constants first
-                        let mut const_indices = Vec::new();
-                        let mut all_const = true;
-                        
-                        for idx in indices.iter() {
-                            match self.eval_int_expr(idx) {
-                                Ok(val) => {
-                                    // Convert from 1-based (MiniZinc) to 0-based for flattening
-                                    const_indices.push((val - 1) as usize);
-                                }
-                                Err(_) => {
-                                    all_const = false;
-                                    break;
-                                }
-                            }
-                        }
-                        
-                        if all_const {
-                            // All indices are constants - compute flattened index at compile time
-                            let flat_idx = metadata.flatten_indices(&const_indices)?;
-                            let flat_idx_expr = ast::Expr {
-                                kind: ast::ExprKind::IntLit((flat_idx as i64) + 1), // MiniZinc is 1-indexed
-                                span: expr.span,
-                            };
-                            
-                            // Now continue with single-index access using the flattened index
-                            let flat_index = flat_idx_expr;
-                            
-                            // Try to evaluate the flattened index expression to a constant
-                            if let Ok(index_val) = self.eval_int_expr(&flat_index) {
-                                // Constant index - direct array access
-                                let array_index = (index_val - 1) as usize;
-                                
-                                // Try to find the array
-                                if let Some(arr) = self.context.get_int_var_array(array_name) {
-                                    if array_index < arr.len() {
-                                        return Ok(arr[array_index]);
-                                    }
-                                }
-                                if let Some(arr) = self.context.get_int_param_array(array_name) {
-                                    if array_index < arr.len() {
-                                        let val = arr[array_index];
-                                        return Ok(self.model.int(val, val));
-                                    }
-                                }
-                                if let Some(arr) = self.context.get_bool_var_array(array_name) {
-                                    if array_index < arr.len() {
-                                        return Ok(arr[array_index]);
-                                    }
-                                }
-                                if let Some(arr) = self.context.get_bool_param_array(array_name) {
-                                    if array_index < arr.len() {
-                                        let val = if arr[array_index] { 1 } else { 0 };
-                                        return Ok(self.model.int(val, val));
-                                    }
-                                }
-                                if let Some(arr) = self.context.get_float_var_array(array_name) {
-                                    if array_index < arr.len() {
-                                        return Ok(arr[array_index]);
-                                    }
-                                }
-                                if let Some(arr) = self.context.get_float_param_array(array_name) {
-                                    if array_index < arr.len() {
-                                        let val = arr[array_index];
-                                        return Ok(self.model.float(val, val));
-                                    }
-                                }
-                                
-                                return Err(Error::message(
-                                    &format!("Undefined array: '{}'", array_name),
-                                    array.span,
-                                ));
-                            }
-                            
-                            // Variable flattened index - use element constraint
-                            let index_var = self.get_var_or_value(&flat_index)?;
-                            let one = self.model.int(1, 1);
-                            
-                            if let Some(arr) = self.context.get_int_var_array(array_name) {
-                                let zero_based_index = self.model.int(0, (arr.len() - 1) as i32);
-                                let index_minus_one = self.model.sub(index_var, one);
-                                self.model.new(zero_based_index.eq(index_minus_one));
-                                let result = self.model.int(i32::MIN, i32::MAX);
-                                self.model.element(&arr, zero_based_index, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr) = self.context.get_bool_var_array(array_name) {
-                                let zero_based_index = self.model.int(0, (arr.len() - 1) as i32);
-                                let index_minus_one = self.model.sub(index_var, one);
-                                self.model.new(zero_based_index.eq(index_minus_one));
-                                let result = self.model.bool();
-                                self.model.element(&arr, zero_based_index, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr) = self.context.get_float_var_array(array_name) {
-                                let zero_based_index = self.model.int(0, (arr.len() - 1) as i32);
-                                let index_minus_one = self.model.sub(index_var, one);
-                                self.model.new(zero_based_index.eq(index_minus_one));
-                                let result = self.model.float(f64::MIN, f64::MAX);
-                                self.model.element(&arr, zero_based_index, result);
-                                return Ok(result);
-                            }
-                            
-                            return Err(Error::message(
-                                &format!("Undefined array: '{}'", array_name),
-                                array.span,
-                            ));
-                        } else {
-                            // Variable indices - compute flattened index using constraints
-                            // Clone metadata to avoid borrow conflicts
-                            let metadata = metadata.clone();
-                            
-                            // Convert all indices to VarIds
-                            let mut index_vars = Vec::new();
-                            for idx in indices.iter() {
-                                index_vars.push(self.get_var_or_value(idx)?);
-                            }
-                            
-                            // Create auxiliary variable for flattened index (1-based)
-                            let flat_size = metadata.total_size() as i32;
-                            let flat_index_var = self.model.int(1, flat_size);
-                            
-                            // Build constraint: flat_index = i0*(d1*d2*...) + i1*(d2*d3*...) + ... + i_n
-                            // All indices are 1-based, convert to 0-based for flattening
-                            let mut flat_expr_parts = Vec::new();
-                            
-                            for (dim_idx, index_var) in index_vars.iter().enumerate() {
-                                // Calculate multiplier for this dimension
-                                let mut multiplier = 1usize;
-                                for d in &metadata.dimensions[(dim_idx + 1)..] {
-                                    multiplier *= d;
-                                }
-                                
-                                // Convert from 1-based to 0-based
-                                let one = self.model.int(1, 1);
-                                let zero_based_idx = self.model.sub(*index_var, one);
-                                
-                                if multiplier == 1 {
-                                    // Last dimension - just add zero-based index
-                                    flat_expr_parts.push(zero_based_idx);
-                                } else {
-                                    // Multiply index by multiplier
-                                    let mult_const = self.model.int(multiplier as i32, multiplier as i32);
-                                    let term = self.model.mul(zero_based_idx, mult_const);
-                                    flat_expr_parts.push(term);
-                                }
-                            }
-                            
-                            // Sum all parts and add 1 to convert back to 1-based
-                            let mut flat_zero_based = flat_expr_parts[0];
-                            for part in &flat_expr_parts[1..] {
-                                flat_zero_based = self.model.add(flat_zero_based, *part);
-                            }
-                            let one = self.model.int(1, 1);
-                            let flat_one_based = self.model.add(flat_zero_based, one);
-                            
-                            // Constraint: flat_index = computed_flat_index
-                            self.model.new(flat_index_var.eq(flat_one_based));
-                            
-                            // Use the flattened index with element constraint
-                            if let Some(arr) = self.context.get_int_var_array(array_name) {
-                                let result = self.model.int(i32::MIN, i32::MAX);
-                                self.model.element(&arr, flat_index_var, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr) = self.context.get_bool_var_array(array_name) {
-                                let result = self.model.bool();
-                                self.model.element(&arr, flat_index_var, result);
-                                return Ok(result);
-                            }
-                            if let Some(arr) = self.context.get_float_var_array(array_name) {
-                                let result = self.model.float(f64::MIN, f64::MAX);
-                                self.model.element(&arr, flat_index_var, result);
-                                return Ok(result);
-                            }
-                            
-                            return Err(Error::message(
-                                &format!("Undefined array: '{}'", array_name),
-                                array.span,
-                            ));
-                        }
-                    } else {
-                        return Err(Error::message(
-                            &format!("Array metadata not found for: '{}'", array_name),
-                            array.span,
-                        ));
-                    }
-                }
-                
-                // 1D array access - original logic
-                let index = &indices[0];
-                
-                // Try to evaluate the index expression to a constant first
-                if let Ok(index_val) = self.eval_int_expr(index) {
-                    // Constant index - direct array access
-                    let array_index = (index_val - 1) as usize;
-                    
-                    if let Some(arr) = self.context.get_int_var_array(array_name) {
-                        if array_index < arr.len() {
-                            return Ok(arr[array_index]);
-                        }
-                    }
-                    if let Some(arr) = self.context.get_int_param_array(array_name) {
-                        if array_index < arr.len() {
-                            let val = arr[array_index];
-                            return Ok(self.model.int(val, val));
-                        }
-                    }
-                    if let Some(arr) = self.context.get_bool_var_array(array_name) {
-                        if array_index < arr.len() {
-                            return Ok(arr[array_index]);
-                        }
-                    }
-                    if let Some(arr) = self.context.get_bool_param_array(array_name) {
-                        if array_index < arr.len() {
-                            let val = if arr[array_index] { 1 } else { 0 };
-                            return Ok(self.model.int(val, val));
-                        }
-                    }
-                    if let Some(arr) = self.context.get_float_var_array(array_name) {
-                        if array_index < arr.len() {
-                            return Ok(arr[array_index]);
-                        }
-                    }
-                    if let Some(arr) = self.context.get_float_param_array(array_name) {
-                        if array_index < arr.len() {
-                            let val = arr[array_index];
-                            return Ok(self.model.float(val, val));
-                        }
-                    }
-                    
-                    return Err(Error::message(
-                        &format!("Undefined array: '{}'", array_name),
-                        array.span,
-                    ));
-                }
-                
-                // Variable index - use element constraint
-                let index_var = self.get_var_or_value(index)?;
-                let one = self.model.int(1, 1);
-                
-                if let Some(arr) = self.context.get_int_var_array(array_name) {
-                    let zero_based_index = self.model.int(0, (arr.len() - 1) as i32);
-                    let index_minus_one = self.model.sub(index_var, one);
-                    self.model.new(zero_based_index.eq(index_minus_one));
-                    let result = self.model.int(i32::MIN, i32::MAX);
-                    self.model.element(&arr, zero_based_index, result);
-                    return Ok(result);
-                }
-                if let Some(arr) = self.context.get_bool_var_array(array_name) {
-                    let zero_based_index = self.model.int(0, (arr.len() - 1) as i32);
-                    let index_minus_one = self.model.sub(index_var, one);
-                    self.model.new(zero_based_index.eq(index_minus_one));
-                    let result = self.model.bool();
-                    self.model.element(&arr, zero_based_index, result);
-                    return Ok(result);
-                }
-                if let Some(arr) = self.context.get_float_var_array(array_name) {
-                    let zero_based_index = self.model.int(0, (arr.len() - 1) as i32);
-                    let index_minus_one = self.model.sub(index_var, one);
-                    self.model.new(zero_based_index.eq(index_minus_one));
-                    let result = self.model.float(f64::MIN, f64::MAX);
-                    self.model.element(&arr, zero_based_index, result);
-                    return Ok(result);
-                }
-                
-                Err(Error::message(
-                    &format!("Undefined array: '{}'", array_name),
-                    array.span,
-                ))
+            _ => Ok(None),
+        }
+    }
+
+    fn translate_constraint_gencall(
+        &mut self,
+        name: &str,
+        generators: &[ast::Generator],
+        body: &ast::Expr,
+        span: Span,
+    ) -> Result<()> {
+        let generators = Self::flatten_generators(generators);
+
+        if name == "exists" {
+            // At least one reified instance of the body must hold
+            let witnesses = self.collect_generator_witnesses(&generators, body)?;
+            self.model.bool_clause(&witnesses, &[]);
+            return Ok(());
+        }
+        if name != "forall" {
+            return Err(Error::unsupported_feature(
+                &format!("Generator call '{}'", name),
+                "forall and exists only",
+                span,
+            ));
+        }
+
+        // Expand forall(i in range)(constraint) into multiple individual constraints
+        // by iterating through the range and substituting values for the loop variable
+        if generators.len() == 1 {
+            self.expand_forall_constraint(&generators[0], body)?;
+        } else {
+            self.expand_forall_constraint_multi(&generators, body)?;
+        }
+        Ok(())
+    }
+
+    /// Split multi-name generators like `i, j in 1..n` into one generator per name
+    ///
+    /// The names iterate the shared range as a cartesian product; a `where`
+    /// clause stays on the last name so it sees every loop variable.
+    fn flatten_generators(generators: &[ast::Generator]) -> Vec<ast::Generator> {
+        let mut flat = Vec::with_capacity(generators.len());
+        for generator in generators {
+            let last = generator.names.len().saturating_sub(1);
+            for (k, name) in generator.names.iter().enumerate() {
+                flat.push(ast::Generator {
+                    names: vec![name.clone()],
+                    expr: generator.expr.clone(),
+                    where_clause: if k == last { generator.where_clause.clone() } else { None },
+                });
             }
-            ast::ExprKind::Call { name, args } => {
-                // Handle aggregate functions
-                self.translate_aggregate_call(name, args, expr.span)
+        }
+        flat
+    }
+
+    /// Expand forall(i in range)(constraint) into individual constraints for a single generator
+    fn expand_forall_constraint(&mut self, generator: &ast::Generator, body: &ast::Expr) -> Result<()> {
+        
+        // Get the loop variable name
+        if generator.names.len() != 1 {
+            return Err(Error::message(
+                "Generator must have exactly one variable",
+                generator.expr.span,
+            ));
+        }
+        let loop_var = &generator.names[0];
+
+        // Parse the range expression to get (start, end)
+        // Iterate through the generator's values and substitute the loop variable
+        for i in self.generator_values(&generator.expr)? {
+            // Create a new context for this iteration
+            let old_val = self.context.int_params.get(loop_var).copied();
+            
+            // Set the loop variable to the current iteration value
+            self.context.int_params.insert(loop_var.clone(), i);
+            
+            // Translate the constraint body with the loop variable substituted
+            let substituted_body = self.substitute_loop_var_in_expr(body, loop_var, i)?;
+            
+            // Linear bodies go straight to Selen's linear API; anything else is
+            // translated as a regular constraint
+            if !self.generator_filter_passes(generator)? {
+                // Filtered out by the `where` clause
+            } else if !self.try_post_linear_comparison(&substituted_body) {
+                self.translate_constraint_expr(&substituted_body)?;
             }
-            _ => Err(Error::unsupported_feature(
-                &format!("Expression type: {:?}", expr.kind),
-                "Phase 2",
-                expr.span,
-            )),
+            
+            // Restore the old value (or remove the parameter)
+            if let Some(old) = old_val {
+                self.context.int_params.insert(loop_var.clone(), old);
+            } else {
+                self.context.int_params.remove(loop_var);
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Values a generator iterates, in order: a range `1..n`, a constant set
+    /// literal, or a constant array such as an `int` parameter array of indices.
+    /// A reversed range such as `n..1` is empty, as in MiniZinc.
+    fn generator_values(&self, expr: &ast::Expr) -> Result<Vec<i32>> {
+        match &expr.kind {
+            ast::ExprKind::Ident(name) if self.context.get_int_param_array(name).is_some() => self.eval_int_array(expr),
+            ast::ExprKind::ArrayLit(_) | ast::ExprKind::BinOp { op: ast::BinOp::Concat, .. } => self.eval_int_array(expr),
+            ast::ExprKind::SetLit(_) => self.eval_int_set(expr),
+            _ => {
+                let (start, end) = self.parse_range(expr)?;
+                Ok((start..=end).collect())
+            }
+        }
+    }
+
+    /// Parse a range expression like `1..n` to get (start, end)
+    fn parse_range(&self, expr: &ast::Expr) -> Result<(i32, i32)> {
+        match &expr.kind {
+            ast::ExprKind::BinOp { op: ast::BinOp::Range, left, right } => {
+                let start = self.eval_int_expr(left)?;
+                let end = self.eval_int_expr(right)?;
+                Ok((start, end))
+            }
+            _ => {
+                // Single value range
+                let val = self.eval_int_expr(expr)?;
+                Ok((val, val))
+            }
+        }
+    }
+
+    /// Evaluate a generator's `where` clause for the current loop variable values
+    ///
+    /// Loop variables are bound in `int_params` while a generator is expanded, so
+    /// the filter is evaluated as an ordinary compile-time boolean.
+    fn generator_filter_passes(&self, generator: &ast::Generator) -> Result<bool> {
+        match &generator.where_clause {
+            Some(filter) => self.eval_bool_expr(filter),
+            None => Ok(true),
+        }
+    }
+
+    /// Restore a loop variable binding saved before an iteration
+    fn restore_loop_var(int_params: &mut HashMap<String, i32>, loop_var: &str, old_val: Option<i32>) {
+        match old_val {
+            Some(old) => {
+                int_params.insert(loop_var.to_string(), old);
+            }
+            None => {
+                int_params.remove(loop_var);
+            }
+        }
+    }
+
+    /// Expand forall with multiple generators (nested loops)
+    fn expand_forall_constraint_multi(&mut self, generators: &[ast::Generator], body: &ast::Expr) -> Result<()> {
+        if generators.is_empty() {
+            return Err(Error::message("No generators in forall", body.span));
+        }
+
+        // For nested loops, we recursively expand each generator
+        self.expand_forall_generators(generators, 0, body)?;
+        Ok(())
+    }
+
+    /// Recursively expand nested forall generators
+    fn expand_forall_generators(&mut self, generators: &[ast::Generator], depth: usize, body: &ast::Expr) -> Result<()> {
+        if depth >= generators.len() {
+            // All generators processed - translate the body
+            if self.try_post_linear_comparison(body) {
+                return Ok(());
+            }
+            self.translate_constraint_expr(body)?;
+            return Ok(());
+        }
+
+        let generator = &generators[depth];
+        
+        if generator.names.len() != 1 {
+            return Err(Error::message(
+                "Generator must have exactly one variable",
+                generator.expr.span,
+            ));
+        }
+        let loop_var = &generator.names[0];
+
+        // Iterate through this level's values
+        for i in self.generator_values(&generator.expr)? {
+            let old_val = self.context.int_params.get(loop_var).copied();
+            self.context.int_params.insert(loop_var.clone(), i);
+            
+            if !self.generator_filter_passes(generator)? {
+                Self::restore_loop_var(&mut self.context.int_params, loop_var, old_val);
+                continue;
+            }
+            
+            // Substitute all remaining loop variables in the expression
+            let mut substituted = body.clone();
+            
+            // Substitute all loop variables from current depth onwards
+            for j in 0..=depth {
+                if j < generators.len() {
+                    let var_name = &generators[j].names[0];
+                    if let Some(var_val) = self.context.int_params.get(var_name) {
+                        substituted = self.substitute_loop_var_in_expr(&substituted, var_name, *var_val)?;
+                    }
+                }
+            }
+            
+            // Process next level or translate
+            self.expand_forall_generators(generators, depth + 1, &substituted)?;
+            
+            if let Some(old) = old_val {
+                self.context.int_params.insert(loop_var.clone(), old);
+            } else {
+                self.context.int_params.remove(loop_var);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Translate a call to a user-defined predicate or function by handing its
+    /// body, with the arguments substituted for the parameters, to `translate`
+    ///
+    /// Returns `None` when `expr` is not such a call.
+    fn inline_user_call<T>(
+        &mut self,
+        expr: &ast::Expr,
+        translate: impl FnOnce(&mut Self, &ast::Expr) -> Result<T>,
+    ) -> Option<Result<T>> {
+        let ast::ExprKind::Call { name, args } = &expr.kind else {
+            return None;
+        };
+        let (kind, params, body) = if let Some(predicate) = self.context.predicates.get(name) {
+            ("predicate", &predicate.params, &predicate.body)
+        } else {
+            let function = self.context.functions.get(name)?;
+            ("function", &function.params, &function.body)
+        };
+        if params.len() != args.len() {
+            return Some(Err(Error::type_error(
+                &format!("{} arguments to {} '{}'", params.len(), kind, name),
+                &format!("{} arguments", args.len()),
+                expr.span,
+            )));
+        }
+        if self.inlining.contains(name) {
+            return Some(Err(Error::unsupported_feature(
+                &format!("Recursive {} '{}' ({} -> {})", kind, name, self.inlining.join(" -> "), name),
+                "translation",
+                expr.span,
+            )));
+        }
+        let bindings: HashMap<String, ast::Expr> = params
+            .iter()
+            .map(|param| param.name.clone())
+            .zip(args.iter().cloned())
+            .collect();
+        let body = Self::substitute_idents(body, &bindings);
+
+        self.inlining.push(name.clone());
+        let result = translate(self, &body);
+        self.inlining.pop();
+        Some(result)
+    }
+
+    /// Substitute a loop variable with a concrete value in an expression
+    fn substitute_loop_var_in_expr(&self, expr: &ast::Expr, var_name: &str, value: i32) -> Result<ast::Expr> {
+        let literal = ast::Expr { kind: ast::ExprKind::IntLit(value as i64), span: expr.span };
+        Ok(Self::substitute_idents(expr, &HashMap::from([(var_name.to_string(), literal)])))
+    }
+
+    /// Replace every free occurrence of the bound names with their expressions,
+    /// all at once; generators and let declarations that rebind a name hide it
+    ///
+    /// A generator or declaration whose name occurs in a replacement is renamed
+    /// first, so the replacement keeps referring to the outer name.
+    fn substitute_idents(expr: &ast::Expr, bindings: &HashMap<String, ast::Expr>) -> ast::Expr {
+        if bindings.is_empty() {
+            return expr.clone();
+        }
+        let sub = |e: &ast::Expr| Self::substitute_idents(e, bindings);
+        let sub_box = |e: &ast::Expr| Box::new(sub(e));
+        let kind = match &expr.kind {
+            ast::ExprKind::Ident(name) => match bindings.get(name) {
+                Some(replacement) => return replacement.clone(),
+                None => expr.kind.clone(),
+            },
+            ast::ExprKind::ArrayLit(elems) => ast::ExprKind::ArrayLit(elems.iter().map(sub).collect()),
+            ast::ExprKind::SetLit(elems) => ast::ExprKind::SetLit(elems.iter().map(sub).collect()),
+            ast::ExprKind::Range(lo, hi) => ast::ExprKind::Range(sub_box(lo), sub_box(hi)),
+            ast::ExprKind::ArrayAccess { array, indices } => ast::ExprKind::ArrayAccess {
+                array: sub_box(array),
+                indices: indices.iter().map(sub).collect(),
+            },
+            ast::ExprKind::BinOp { op, left, right } => ast::ExprKind::BinOp {
+                op: *op,
+                left: sub_box(left),
+                right: sub_box(right),
+            },
+            ast::ExprKind::UnOp { op, expr: inner } => ast::ExprKind::UnOp { op: *op, expr: sub_box(inner) },
+            ast::ExprKind::Call { name, args } => ast::ExprKind::Call {
+                name: name.clone(),
+                args: args.iter().map(sub).collect(),
+            },
+            ast::ExprKind::IfThenElse { cond, then_expr, else_expr } => ast::ExprKind::IfThenElse {
+                cond: sub_box(cond),
+                then_expr: sub_box(then_expr),
+                else_expr: else_expr.as_deref().map(sub_box),
+            },
+            // Declarations see the bindings until one of them shadows a name
+            ast::ExprKind::Let { decls, constraints, body } => {
+                let mut visible = bindings.clone();
+                let mut decls_sub = Vec::with_capacity(decls.len());
+                for decl in decls {
+                    let mut decl = decl.clone();
+                    decl.type_inst = Self::substitute_type_inst(&decl.type_inst, &visible);
+                    decl.expr = decl.expr.as_ref().map(|init| Self::substitute_idents(init, &visible));
+                    decl.name = Self::bind_name(&decl.name, &mut visible, decl.span);
+                    decls_sub.push(decl);
+                }
+                ast::ExprKind::Let {
+                    decls: decls_sub,
+                    constraints: constraints.iter().map(|c| Self::substitute_idents(c, &visible)).collect(),
+                    body: Box::new(Self::substitute_idents(body, &visible)),
+                }
+            }
+            ast::ExprKind::ArrayComp { expr: inner, generators } => {
+                let (generators, visible) = Self::substitute_generators(generators, bindings);
+                ast::ExprKind::ArrayComp {
+                    expr: Box::new(Self::substitute_idents(inner, &visible)),
+                    generators,
+                }
+            }
+            ast::ExprKind::GenCall { name, generators, body } => {
+                let (generators, visible) = Self::substitute_generators(generators, bindings);
+                ast::ExprKind::GenCall {
+                    name: name.clone(),
+                    generators,
+                    body: Box::new(Self::substitute_idents(body, &visible)),
+                }
+            }
+            ast::ExprKind::Array2D { row_range, col_range, values } => ast::ExprKind::Array2D {
+                row_range: sub_box(row_range),
+                col_range: sub_box(col_range),
+                values: sub_box(values),
+            },
+            ast::ExprKind::Array3D { r1_range, r2_range, r3_range, values } => ast::ExprKind::Array3D {
+                r1_range: sub_box(r1_range),
+                r2_range: sub_box(r2_range),
+                r3_range: sub_box(r3_range),
+                values: sub_box(values),
+            },
+            ast::ExprKind::BoolLit(_)
+            | ast::ExprKind::IntLit(_)
+            | ast::ExprKind::FloatLit(_)
+            | ast::ExprKind::StringLit(_)
+            | ast::ExprKind::ImplicitIndexSet(_) => expr.kind.clone(),
+        };
+        ast::Expr { kind, span: expr.span }
+    }
+
+    /// Substitute into generators in order, returning them with the bindings
+    /// still visible once every generator name is in scope
+    fn substitute_generators(
+        generators: &[ast::Generator],
+        bindings: &HashMap<String, ast::Expr>,
+    ) -> (Vec<ast::Generator>, HashMap<String, ast::Expr>) {
+        let mut visible = bindings.clone();
+        let generators = generators
+            .iter()
+            .map(|generator| {
+                let expr = Self::substitute_idents(&generator.expr, &visible);
+                let names = generator
+                    .names
+                    .iter()
+                    .map(|name| Self::bind_name(name, &mut visible, generator.expr.span))
+                    .collect();
+                ast::Generator {
+                    names,
+                    expr,
+                    where_clause: generator.where_clause.as_ref().map(|w| Self::substitute_idents(w, &visible)),
+                }
+            })
+            .collect();
+        (generators, visible)
+    }
+
+    /// Bring a generator or declaration name into scope for `substitute_idents`
+    ///
+    /// The name hides any binding of its own. If a replacement mentions it, the
+    /// binder is renamed (with primes, which identifiers cannot contain) and the
+    /// new name is returned.
+    fn bind_name(name: &str, visible: &mut HashMap<String, ast::Expr>, span: Span) -> String {
+        visible.remove(name);
+        let mut free = HashSet::new();
+        for replacement in visible.values() {
+            Self::collect_idents(replacement, &mut free);
+        }
+        if !free.contains(name) {
+            return name.to_string();
+        }
+        let mut fresh = format!("{}'", name);
+        while free.contains(&fresh) {
+            fresh.push('\'');
+        }
+        visible.insert(name.to_string(), ast::Expr { kind: ast::ExprKind::Ident(fresh.clone()), span });
+        fresh
+    }
+
+    /// Collect every identifier mentioned in `expr`, bound or free
+    fn collect_idents(expr: &ast::Expr, out: &mut HashSet<String>) {
+        let mut visit = |e: &ast::Expr| Self::collect_idents(e, out);
+        match &expr.kind {
+            ast::ExprKind::Ident(name) => {
+                out.insert(name.clone());
+            }
+            ast::ExprKind::ArrayLit(elems) | ast::ExprKind::SetLit(elems) => elems.iter().for_each(visit),
+            ast::ExprKind::Range(lo, hi) => {
+                visit(lo);
+                visit(hi);
+            }
+            ast::ExprKind::ArrayAccess { array, indices } => {
+                visit(array);
+                indices.iter().for_each(visit);
+            }
+            ast::ExprKind::BinOp { left, right, .. } => {
+                visit(left);
+                visit(right);
+            }
+            ast::ExprKind::UnOp { expr: inner, .. } => visit(inner),
+            ast::ExprKind::Call { args, .. } => args.iter().for_each(visit),
+            ast::ExprKind::IfThenElse { cond, then_expr, else_expr } => {
+                visit(cond);
+                visit(then_expr);
+                if let Some(else_expr) = else_expr {
+                    visit(else_expr);
+                }
+            }
+            ast::ExprKind::Let { decls, constraints, body } => {
+                for decl in decls {
+                    out.insert(decl.name.clone());
+                    if let Some(init) = &decl.expr {
+                        Self::collect_idents(init, out);
+                    }
+                }
+                constraints.iter().chain([&**body]).for_each(|e| Self::collect_idents(e, out));
+            }
+            ast::ExprKind::ArrayComp { expr: body, generators }
+            | ast::ExprKind::GenCall { generators, body, .. } => {
+                for generator in generators {
+                    out.extend(generator.names.iter().cloned());
+                    Self::collect_idents(&generator.expr, out);
+                    if let Some(where_clause) = &generator.where_clause {
+                        Self::collect_idents(where_clause, out);
+                    }
+                }
+                Self::collect_idents(body, out);
+            }
+            ast::ExprKind::Array2D { row_range, col_range, values } => {
+                visit(row_range);
+                visit(col_range);
+                visit(values);
+            }
+            ast::ExprKind::Array3D { r1_range, r2_range, r3_range, values } => {
+                visit(r1_range);
+                visit(r2_range);
+                visit(r3_range);
+                visit(values);
+            }
+            ast::ExprKind::BoolLit(_)
+            | ast::ExprKind::IntLit(_)
+            | ast::ExprKind::FloatLit(_)
+            | ast::ExprKind::StringLit(_)
+            | ast::ExprKind::ImplicitIndexSet(_) => {}
+        }
+    }
+
+    /// Substitute into the domain and index set expressions of a type
+    fn substitute_type_inst(type_inst: &ast::TypeInst, bindings: &HashMap<String, ast::Expr>) -> ast::TypeInst {
+        match type_inst {
+            ast::TypeInst::Basic { .. } => type_inst.clone(),
+            ast::TypeInst::Constrained { is_var, base_type, domain } => ast::TypeInst::Constrained {
+                is_var: *is_var,
+                base_type: base_type.clone(),
+                domain: Self::substitute_idents(domain, bindings),
+            },
+            ast::TypeInst::Set { is_var, element_type } => ast::TypeInst::Set {
+                is_var: *is_var,
+                element_type: Box::new(Self::substitute_type_inst(element_type, bindings)),
+            },
+            ast::TypeInst::Array { index_sets, element_type } => ast::TypeInst::Array {
+                index_sets: index_sets.iter().map(|set| Self::substitute_idents(set, bindings)).collect(),
+                element_type: Box::new(Self::substitute_type_inst(element_type, bindings)),
+            },
+        }
+    }
+
+    fn translate_constraint_binop(
+        &mut self,
+        op: ast::BinOp,
+        left: &ast::Expr,
+        right: &ast::Expr,
+        span: Span,
+    ) -> Result<()> {
+        if let Some(holds) = self.set_relation(op, left, right)? {
+            self.model.new(holds.eq(1));
+            return Ok(());
+        }
+
+        match op {
+            // Boolean logical operators
+            ast::BinOp::And => {
+                // Translate as conjunction: each side is its own hard constraint
+                self.translate_constraint_expr(left)?;
+                self.translate_constraint_expr(right)?;
+            }
+            ast::BinOp::Or => {
+                // Translate as disjunction: at least one must be true
+                let left_constraint = self.expr_to_bool_var(left)?;
+                let right_constraint = self.expr_to_bool_var(right)?;
+                // At least one must be 1: left + right >= 1
+                let sum = self.model.add(left_constraint, right_constraint);
+                let one = self.model.int(1, 1);
+                self.model.new(sum.ge(one));
+            }
+            ast::BinOp::Impl => {
+                // Translate as implication: left => right
+                let left_constraint = self.expr_to_bool_var(left)?;
+                let right_constraint = self.expr_to_bool_var(right)?;
+                self.model.implies(left_constraint, right_constraint);
+            }
+            ast::BinOp::Iff => {
+                // Translate as bi-directional implication: left <-> right
+                // This means left and right must have the same value
+                // Equivalent to: (left -> right) /\ (right -> left)
+                let left_constraint = self.expr_to_bool_var(left)?;
+                let right_constraint = self.expr_to_bool_var(right)?;
+                
+                // left => right
+                self.model.implies(left_constraint, right_constraint);
+                // right => left
+                self.model.implies(right_constraint, left_constraint);
+            }
+            // Comparison operators
+            ast::BinOp::Lt | ast::BinOp::Le | ast::BinOp::Gt | 
+            ast::BinOp::Ge | ast::BinOp::Eq | ast::BinOp::Ne => {
+                self.check_comparison_types(left, right)?;
+
+                // CRITICAL FIX: Check if right side is a literal constant BEFORE calling get_var_or_value
+                // If it is, we should pass the raw integer directly to the constraint method,
+                // not create a new VarId. This prevents Selen's modulo propagator from being confused.
+                if let Some(const_val) = self.const_int_operand(right) {
+                    let left_var = self.get_var_or_value(left)?;
+                    let const_i32 = const_val as i32;
+                    
+                    match op {
+                        ast::BinOp::Lt => {
+                            self.model.new(left_var.lt(const_i32));
+                        }
+                        ast::BinOp::Le => {
+                            self.model.new(left_var.le(const_i32));
+                        }
+                        ast::BinOp::Gt => {
+                            self.model.new(left_var.gt(const_i32));
+                        }
+                        ast::BinOp::Ge => {
+                            self.model.new(left_var.ge(const_i32));
+                        }
+                        ast::BinOp::Eq => {
+                            self.model.new(left_var.eq(const_i32));
+                        }
+                        ast::BinOp::Ne => {
+                            self.model.new(left_var.ne(const_i32));
+                        }
+                        _ => unreachable!(),
+                    }
+                } else if let Some(const_val) = self.const_int_operand(left) {
+                    // Constant on left side: mirror the comparison onto the right
+                    let right_var = self.get_var_or_value(right)?;
+                    let const_i32 = const_val as i32;
+                    
+                    match op {
+                        ast::BinOp::Lt => {
+                            self.model.new(right_var.gt(const_i32));
+                        }
+                        ast::BinOp::Le => {
+                            self.model.new(right_var.ge(const_i32));
+                        }
+                        ast::BinOp::Gt => {
+                            self.model.new(right_var.lt(const_i32));
+                        }
+                        ast::BinOp::Ge => {
+                            self.model.new(right_var.le(const_i32));
+                        }
+                        ast::BinOp::Eq => {
+                            self.model.new(right_var.eq(const_i32));
+                        }
+                        ast::BinOp::Ne => {
+                            self.model.new(right_var.ne(const_i32));
+                        }
+                        _ => unreachable!(),
+                    }
+                } else if let Some(value) = Self::extract_float_const(right) {
+                    // Float literal: post a bound instead of a fixed float variable
+                    let left_var = self.get_var_or_value(left)?;
+                    self.post_float_bound(op, left_var, value);
+                } else if let Some(value) = Self::extract_float_const(left) {
+                    let right_var = self.get_var_or_value(right)?;
+                    let flipped = match op {
+                        ast::BinOp::Lt => ast::BinOp::Gt,
+                        ast::BinOp::Le => ast::BinOp::Ge,
+                        ast::BinOp::Gt => ast::BinOp::Lt,
+                        ast::BinOp::Ge => ast::BinOp::Le,
+                        other => other,
+                    };
+                    self.post_float_bound(flipped, right_var, value);
+                } else {
+                    // Neither side is a constant literal - normal path
+                    let left_var = self.get_var_or_value(left)?;
+                    let right_var = self.get_var_or_value(right)?;
+
+                    if self.int_var_bounds(left_var).is_none() || self.int_var_bounds(right_var).is_none() {
+                        self.post_float_comparison(op, left_var, right_var);
+                        return Ok(());
+                    }
+
+                    match op {
+                        ast::BinOp::Lt => {
+                            self.model.new(left_var.lt(right_var));
+                        }
+                        ast::BinOp::Le => {
+                            self.model.new(left_var.le(right_var));
+                        }
+                        ast::BinOp::Gt => {
+                            self.model.new(left_var.gt(right_var));
+                        }
+                        ast::BinOp::Ge => {
+                            self.model.new(left_var.ge(right_var));
+                        }
+                        ast::BinOp::Eq => {
+                            self.model.new(left_var.eq(right_var));
+                        }
+                        ast::BinOp::Ne => {
+                            self.model.new(left_var.ne(right_var));
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+            }
+            _ => {
+                return Err(Error::unsupported_feature(
+                    &format!("Binary operator {:?} in constraints", op),
+                    "Phase 2",
+                    span,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reject comparisons between a declared int or bool operand and a float
+    /// literal, or between a bool operand and an int literal
+    ///
+    /// Only names and literals are typed here; anything else is left to the
+    /// translation, which coerces as before.
+    fn check_comparison_types(&self, left: &ast::Expr, right: &ast::Expr) -> Result<()> {
+        let (Some(left_type), Some(right_type)) = (self.operand_type(left), self.operand_type(right)) else {
+            return Ok(());
+        };
+        let is_literal = |expr: &ast::Expr| {
+            Self::extract_const_value(expr).is_some()
+                || Self::extract_float_const(expr).is_some()
+                || matches!(expr.kind, ast::ExprKind::BoolLit(_))
+        };
+        for ((this, this_type), (other, other_type)) in
+            [((left, &left_type), (right, &right_type)), ((right, &right_type), (left, &left_type))]
+        {
+            let mismatch = matches!(
+                (this_type, other_type),
+                (ast::BaseType::Int | ast::BaseType::Bool, ast::BaseType::Float)
+                    | (ast::BaseType::Bool, ast::BaseType::Int)
+                    | (ast::BaseType::Int | ast::BaseType::Float, ast::BaseType::Bool)
+            );
+            if mismatch && is_literal(other) && !is_literal(this) {
+                return Err(Error::type_error(
+                    &format!("{} operand to compare with {}", Self::base_type_name(this_type), self.describe_operand(this)),
+                    &self.describe_operand(other),
+                    other.span,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Base type of a literal or of a declared variable, parameter or array element
+    fn operand_type(&self, expr: &ast::Expr) -> Option<ast::BaseType> {
+        let name = match &expr.kind {
+            ast::ExprKind::IntLit(_) => return Some(ast::BaseType::Int),
+            ast::ExprKind::FloatLit(_) => return Some(ast::BaseType::Float),
+            ast::ExprKind::BoolLit(_) => return Some(ast::BaseType::Bool),
+            ast::ExprKind::UnOp { op: ast::UnOp::Neg, expr: inner } => {
+                return self.operand_type(inner).filter(|t| *t != ast::BaseType::Bool);
+            }
+            ast::ExprKind::Ident(name) => {
+                let context = &self.context;
+                return if context.get_int_var(name).is_some() || context.get_int_param(name).is_some() {
+                    Some(ast::BaseType::Int)
+                } else if context.get_bool_var(name).is_some() || context.get_bool_param(name).is_some() {
+                    Some(ast::BaseType::Bool)
+                } else if context.get_float_var(name).is_some() || context.get_float_param(name).is_some() {
+                    Some(ast::BaseType::Float)
+                } else {
+                    None
+                };
+            }
+            ast::ExprKind::ArrayAccess { array, .. } => match &array.kind {
+                ast::ExprKind::Ident(name) => name,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let context = &self.context;
+        if context.get_int_var_array(name).is_some() || context.get_int_param_array(name).is_some() {
+            Some(ast::BaseType::Int)
+        } else if context.get_bool_var_array(name).is_some() || context.get_bool_param_array(name).is_some() {
+            Some(ast::BaseType::Bool)
+        } else if context.get_float_var_array(name).is_some() || context.get_float_param_array(name).is_some() {
+            Some(ast::BaseType::Float)
+        } else {
+            None
+        }
+    }
+
+    fn base_type_name(base_type: &ast::BaseType) -> &'static str {
+        match base_type {
+            ast::BaseType::Bool => "bool",
+            ast::BaseType::Int | ast::BaseType::Enum(_) => "int",
+            ast::BaseType::Float => "float",
+        }
+    }
+
+    /// Short description of an operand for type errors, e.g. `int variable 'x'`
+    fn describe_operand(&self, expr: &ast::Expr) -> String {
+        let base_type = self.operand_type(expr).map_or("", |t| Self::base_type_name(&t));
+        match &expr.kind {
+            ast::ExprKind::Ident(name) => {
+                let kind = if self.context.get_int_var(name).is_some()
+                    || self.context.get_bool_var(name).is_some()
+                    || self.context.get_float_var(name).is_some()
+                {
+                    "variable"
+                } else {
+                    "parameter"
+                };
+                format!("{} {} '{}'", base_type, kind, name)
+            }
+            ast::ExprKind::ArrayAccess { array, .. } => match &array.kind {
+                ast::ExprKind::Ident(name) => format!("{} element of '{}'", base_type, name),
+                _ => format!("{} element", base_type),
+            },
+            ast::ExprKind::BoolLit(value) => format!("bool literal {}", value),
+            _ => match (Self::extract_const_value(expr), Self::extract_float_const(expr)) {
+                (Some(value), _) => format!("int literal {}", value),
+                (_, Some(value)) => format!("float literal {:?}", value),
+                _ => format!("{} expression", base_type),
+            },
+        }
+    }
+
+    /// `constraint if c then a else b endif` as `c -> a` and `not c -> b`
+    ///
+    /// Without an else branch only the implication is posted. A condition known
+    /// at translation time posts the selected branch directly.
+    fn translate_constraint_if(
+        &mut self,
+        cond: &ast::Expr,
+        then_expr: &ast::Expr,
+        else_expr: Option<&ast::Expr>,
+    ) -> Result<()> {
+        if let Ok(holds) = self.eval_bool_expr(cond) {
+            let branch = if holds { Some(then_expr) } else { else_expr };
+            if let Some(branch) = branch {
+                self.translate_constraint_expr(branch)?;
+            }
+            return Ok(());
+        }
+
+        let c = self.expr_to_bool_var(cond)?;
+        let then_holds = self.expr_to_bool_var(then_expr)?;
+        self.model.bool_clause(&[then_holds], &[c]);
+        if let Some(else_expr) = else_expr {
+            let else_holds = self.expr_to_bool_var(else_expr)?;
+            self.model.bool_clause(&[c, else_holds], &[]);
+        }
+        Ok(())
+    }
+
+    fn translate_constraint_unop(
+        &mut self,
+        op: ast::UnOp,
+        expr: &ast::Expr,
+        span: Span,
+    ) -> Result<()> {
+        match op {
+            ast::UnOp::Not => {
+                // Translate as negation: expr must be false (0)
+                let bool_var = self.expr_to_bool_var(expr)?;
+                let zero = self.model.int(0, 0);
+                self.model.new(bool_var.eq(zero));
+            }
+            ast::UnOp::Neg => {
+                return Err(Error::unsupported_feature(
+                    "Unary negation in constraints",
+                    "Phase 2",
+                    span,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert an expression to a boolean variable (0 or 1)
+    /// Used for boolean logical operations
+    fn expr_to_bool_var(&mut self, expr: &ast::Expr) -> Result<VarId> {
+        if let Some(inner) = self.unwrap_assert_trace(expr)? {
+            return self.expr_to_bool_var(&inner);
+        }
+        if let Some(result) = self.inline_user_call(expr, Self::expr_to_bool_var) {
+            return result;
+        }
+        if let ast::ExprKind::BinOp { op, left, right } = &expr.kind
+            && let Some(holds) = self.set_relation(*op, left, right)?
+        {
+            return Ok(holds);
+        }
+
+        match &expr.kind {
+            // Boolean literals
+            ast::ExprKind::BoolLit(b) => {
+                let val = if *b { 1 } else { 0 };
+                Ok(self.int_constant(val))
+            }
+            // The let's own constraints hold together with its body
+            ast::ExprKind::Let { decls, constraints, body } => self.with_let_scope(decls, |t| {
+                let mut conjuncts = constraints.iter()
+                    .map(|c| t.expr_to_bool_var(c))
+                    .collect::<Result<Vec<_>>>()?;
+                let holds = t.expr_to_bool_var(body)?;
+                if conjuncts.is_empty() {
+                    return Ok(holds);
+                }
+                conjuncts.push(holds);
+                Ok(t.model.bool_and(&conjuncts))
+            }),
+            // r <-> (c -> a) /\ (not c -> b), with a missing else branch being true
+            ast::ExprKind::IfThenElse { cond, then_expr, else_expr } => {
+                if let Ok(holds) = self.eval_bool_expr(cond) {
+                    return match (holds, else_expr) {
+                        (true, _) => self.expr_to_bool_var(then_expr),
+                        (false, Some(else_expr)) => self.expr_to_bool_var(else_expr),
+                        (false, None) => Ok(self.int_constant(1)),
+                    };
+                }
+                let c = self.expr_to_bool_var(cond)?;
+                let a = self.expr_to_bool_var(then_expr)?;
+                let b = match else_expr {
+                    Some(else_expr) => self.expr_to_bool_var(else_expr)?,
+                    None => self.int_constant(1),
+                };
+                let r = self.model.bool();
+                self.model.bool_clause(&[a], &[r, c]);
+                self.model.bool_clause(&[c, b], &[r]);
+                self.model.bool_clause(&[r], &[c, a]);
+                self.model.bool_clause(&[r, c], &[b]);
+                Ok(r)
+            }
+            // Boolean variables
+            ast::ExprKind::Ident(name) => {
+                if let Some(var) = self.context.get_bool_var(name) {
+                    return Ok(var);
+                }
+                if let Some(value) = self.context.get_bool_param(name) {
+                    let val = if value { 1 } else { 0 };
+                    return Ok(self.int_constant(val));
+                }
+                Err(Error::message(
+                    &format!("Undefined boolean variable: '{}'", name),
+                    expr.span,
+                ))
+            }
+            // Array element: bool_array[i] (constant or variable index)
+            ast::ExprKind::ArrayAccess { .. } => self.get_var_or_value(expr),
+            // Reified `alldifferent(x)`: true iff no pair of elements clashes
+            ast::ExprKind::Call { name, args }
+                if args.len() == 1
+                    && matches!(
+                        Self::canonical_global_name(name),
+                        "alldifferent" | "alldiff" | "alldifferent_except_0"
+                    ) =>
+            {
+                let vars = self.get_array_vars(&args[0])?;
+                let except_zero = Self::canonical_global_name(name) == "alldifferent_except_0";
+                let mut clashes = Vec::new();
+                for i in 0..vars.len() {
+                    for j in (i + 1)..vars.len() {
+                        let equal = self.model.bool();
+                        self.model.eq_reif(vars[i], vars[j], equal);
+                        clashes.push(if except_zero {
+                            // Equal values clash unless they are both zero
+                            let zero = self.is_zero(vars[i]);
+                            let nonzero = self.model.bool_not(zero);
+                            self.model.bool_and(&[equal, nonzero])
+                        } else {
+                            equal
+                        });
+                    }
+                }
+                if clashes.is_empty() {
+                    return Ok(self.int_constant(1));
+                }
+                let any_clash = self.model.bool_or(&clashes);
+                Ok(self.model.bool_not(any_clash))
+            }
+            // Comparison operators - reify into a fresh boolean
+            ast::ExprKind::BinOp { op, left, right } if matches!(op,
+                ast::BinOp::Lt | ast::BinOp::Le | ast::BinOp::Gt |
+                ast::BinOp::Ge | ast::BinOp::Eq | ast::BinOp::Ne) => {
+                let left_var = self.get_var_or_value(left)?;
+                let right_var = self.get_var_or_value(right)?;
+                let result = self.model.bool();
+                match op {
+                    ast::BinOp::Lt => self.model.lt_reif(left_var, right_var, result),
+                    ast::BinOp::Le => self.model.le_reif(left_var, right_var, result),
+                    ast::BinOp::Gt => self.model.gt_reif(left_var, right_var, result),
+                    ast::BinOp::Ge => self.model.ge_reif(left_var, right_var, result),
+                    ast::BinOp::Eq => self.model.eq_reif(left_var, right_var, result),
+                    ast::BinOp::Ne => self.model.ne_reif(left_var, right_var, result),
+                    _ => unreachable!(),
+                }
+                Ok(result)
+            }
+            ast::ExprKind::UnOp { op: ast::UnOp::Not, expr: inner } => {
+                // Not of a boolean expression: flip the value
+                let inner_var = self.expr_to_bool_var(inner)?;
+                let one = self.model.int(1, 1);
+                let negated = self.model.sub(one, inner_var);
+                Ok(negated)
+            }
+            ast::ExprKind::BinOp { op: ast::BinOp::And, left, right } => {
+                // AND: both must be true
+                // Use Selen's bool_and to create the result
+                let left_var = self.expr_to_bool_var(left)?;
+                let right_var = self.expr_to_bool_var(right)?;
+                
+                // bool_and returns a VarId representing the AND result
+                let result = self.model.bool_and(&[left_var, right_var]);
+                Ok(result)
+            }
+            ast::ExprKind::BinOp { op: ast::BinOp::Or, left, right } => {
+                // OR: at least one must be true
+                // Use Selen's bool_or to create the result
+                let left_var = self.expr_to_bool_var(left)?;
+                let right_var = self.expr_to_bool_var(right)?;
+                
+                // bool_or returns a VarId representing the OR result
+                let result = self.model.bool_or(&[left_var, right_var]);
+                Ok(result)
+            }
+            _ => {
+                Err(Error::unsupported_feature(
+                    &format!("Expression type in boolean context: {:?}", expr.kind),
+                    "Phase 2",
+                    expr.span,
+                ))
+            }
+        }
+    }
+
+    fn translate_solve(&mut self, solve: &ast::Solve) -> Result<()> {
+        let (ast::Solve::Satisfy { search, .. }
+        | ast::Solve::Minimize { search, .. }
+        | ast::Solve::Maximize { search, .. }) = solve;
+        self.search_heuristics.clear();
+        if !self.options.free_search {
+            for annotation in search {
+                let vars = match &annotation.variables.kind {
+                    ast::ExprKind::ArrayLit(elements) => elements
+                        .iter()
+                        .map(|element| self.get_var_or_value(element))
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => self.get_array_vars(&annotation.variables)?,
+                };
+                self.search_heuristics.push(SearchHeuristic {
+                    vars,
+                    var_select: annotation.var_select.clone(),
+                    val_select: annotation.val_select.clone(),
+                });
+            }
+        }
+
+        match solve {
+            ast::Solve::Satisfy { search_option, .. } => {
+                // Default behavior - no optimization
+                self.objective_type = ObjectiveType::Satisfy;
+                self.objective_var = None;
+                self.search_option = search_option.clone();
+            }
+            ast::Solve::Minimize { expr, search_option, .. } => {
+                let var = self.translate_objective(expr)?;
+                self.objective_type = ObjectiveType::Minimize;
+                self.objective_var = Some(var);
+                self.objective_open = self.mentions_unbounded_int(expr);
+                self.search_option = search_option.clone();
+            }
+            ast::Solve::Maximize { expr, search_option, .. } => {
+                let var = self.translate_objective(expr)?;
+                self.objective_type = ObjectiveType::Maximize;
+                self.objective_var = Some(var);
+                self.objective_open = self.mentions_unbounded_int(expr);
+                self.search_option = search_option.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `expr` mentions an int variable or array declared without bounds
+    fn mentions_unbounded_int(&self, expr: &ast::Expr) -> bool {
+        let mut names = HashSet::new();
+        Self::collect_idents(expr, &mut names);
+        names.iter().any(|name| self.unbounded_ints.contains(name))
+    }
+
+    /// Fixed variable holding `value`, created once per distinct value
+    fn int_constant(&mut self, value: i32) -> VarId {
+        if let Some(&var) = self.context.int_constants.get(&value) {
+            return var;
+        }
+        let var = self.model.int(value, value);
+        self.context.int_constants.insert(value, var);
+        var
+    }
+
+    /// Translate an objective expression
+    ///
+    /// Linear objectives like `2*x + 3*y - 5` are posted as a single sum over scaled
+    /// views instead of a chain of intermediate multiply/add variables.
+    fn translate_objective(&mut self, expr: &ast::Expr) -> Result<VarId> {
+        if !matches!(expr.kind, ast::ExprKind::BinOp { .. } | ast::ExprKind::UnOp { .. }) {
+            return self.get_var_or_value(expr);
+        }
+
+        let mut terms: Vec<(i32, VarId)> = Vec::new();
+        let mut constant = 0i32;
+        if !self.extract_linear_terms(expr, 1, &mut terms, &mut constant) {
+            return self.get_var_or_value(expr);
+        }
+
+        let mut views: Vec<_> = terms
+            .iter()
+            .filter(|(coeff, _)| *coeff != 0)
+            .map(|&(coeff, var)| var.times(Val::ValI(coeff)))
+            .collect();
+        if constant != 0 || views.is_empty() {
+            let const_var = self.int_constant(constant);
+            views.push(const_var.times(Val::ValI(1)));
+        }
+        Ok(self.model.sum(&views))
+    }
+
+    /// Decompose an integer expression into `sum(coeff * var) + constant`
+    ///
+    /// Returns false if the expression is not linear over integer variables.
+    /// Coefficients of repeated variables are merged.
+    fn extract_linear_terms(
+        &self,
+        expr: &ast::Expr,
+        scale: i32,
+        terms: &mut Vec<(i32, VarId)>,
+        constant: &mut i32,
+    ) -> bool {
+        if let Ok(value) = self.eval_int_expr(expr) {
+            return match value.checked_mul(scale).and_then(|v| constant.checked_add(v)) {
+                Some(sum) => {
+                    *constant = sum;
+                    true
+                }
+                None => false,
+            };
+        }
+
+        match &expr.kind {
+            ast::ExprKind::Ident(name) => {
+                match self.context.get_int_var(name).or_else(|| self.context.get_bool_var(name)) {
+                    Some(var) => Self::push_linear_term(terms, scale, var),
+                    None => false,
+                }
+            }
+            ast::ExprKind::ArrayAccess { array, indices } => {
+                let ast::ExprKind::Ident(name) = &array.kind else {
+                    return false;
+                };
+                let arr = self.context.get_int_var_array(name).or_else(|| self.context.get_bool_var_array(name));
+                match (arr, self.const_flat_index(name, indices)) {
+                    (Some(arr), Some(flat)) if flat < arr.len() => {
+                        Self::push_linear_term(terms, scale, arr[flat])
+                    }
+                    _ => false,
+                }
+            }
+            ast::ExprKind::Call { name, args } if name == "sum" && args.len() == 1 => {
+                let ast::ExprKind::Ident(array_name) = &args[0].kind else {
+                    return false;
+                };
+                match self.context.get_int_var_array(array_name) {
+                    Some(arr) => arr.iter().all(|&var| Self::push_linear_term(terms, scale, var)),
+                    None => false,
+                }
+            }
+            ast::ExprKind::GenCall { name, generators, body } if name == "sum" => {
+                let generators = Self::flatten_generators(generators);
+                self.extract_linear_gencall_terms(&generators, 0, body, scale, terms, constant)
+            }
+            ast::ExprKind::UnOp { op: ast::UnOp::Neg, expr: inner } => {
+                self.extract_linear_terms(inner, -scale, terms, constant)
+            }
+            ast::ExprKind::BinOp { op: ast::BinOp::Add, left, right } => {
+                self.extract_linear_terms(left, scale, terms, constant)
+                    && self.extract_linear_terms(right, scale, terms, constant)
+            }
+            ast::ExprKind::BinOp { op: ast::BinOp::Sub, left, right } => {
+                self.extract_linear_terms(left, scale, terms, constant)
+                    && self.extract_linear_terms(right, -scale, terms, constant)
+            }
+            ast::ExprKind::BinOp { op: ast::BinOp::Mul, left, right } => {
+                // Constant coefficient may appear on either side of `*`
+                let (coeff, operand) = if let Ok(c) = self.eval_int_expr(left) {
+                    (c, right)
+                } else if let Ok(c) = self.eval_int_expr(right) {
+                    (c, left)
+                } else {
+                    return false;
+                };
+                match coeff.checked_mul(scale) {
+                    Some(new_scale) => self.extract_linear_terms(operand, new_scale, terms, constant),
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Add `scale * var` to a linear term list, merging repeated variables
+    fn push_linear_term(terms: &mut Vec<(i32, VarId)>, scale: i32, var: VarId) -> bool {
+        match terms.iter_mut().find(|(_, v)| *v == var) {
+            Some((coeff, _)) => match coeff.checked_add(scale) {
+                Some(c) => {
+                    *coeff = c;
+                    true
+                }
+                None => false,
+            },
+            None => {
+                terms.push((scale, var));
+                true
+            }
+        }
+    }
+
+    /// Unroll `sum(i in a..b)(body)` into linear terms
+    fn extract_linear_gencall_terms(
+        &self,
+        generators: &[ast::Generator],
+        depth: usize,
+        body: &ast::Expr,
+        scale: i32,
+        terms: &mut Vec<(i32, VarId)>,
+        constant: &mut i32,
+    ) -> bool {
+        if depth >= generators.len() {
+            return self.extract_linear_terms(body, scale, terms, constant);
+        }
+        let generator = &generators[depth];
+        if generator.names.len() != 1 {
+            return false;
+        }
+        let Ok(values) = self.generator_values(&generator.expr) else {
+            return false;
+        };
+        for value in values {
+            if let Some(filter) = &generator.where_clause {
+                let keep = self
+                    .substitute_loop_var_in_expr(filter, &generator.names[0], value)
+                    .and_then(|f| self.eval_bool_expr(&f));
+                match keep {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(_) => return false,
+                }
+            }
+            let Ok(substituted) = self.substitute_loop_var_in_expr(body, &generator.names[0], value) else {
+                return false;
+            };
+            if !self.extract_linear_gencall_terms(generators, depth + 1, &substituted, scale, terms, constant) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Post a linear comparison like `sum(j in 1..n)(a[i,j] * x[j]) <= b[i]` through
+    /// Selen's `lin_*` constraints instead of building intermediate variables.
+    ///
+    /// Returns false (posting nothing) if the comparison is not linear over at
+    /// least two variables.
+    fn try_post_linear_comparison(&mut self, expr: &ast::Expr) -> bool {
+        let ast::ExprKind::BinOp { op, left, right } = &expr.kind else {
+            return false;
+        };
+        if !matches!(op, ast::BinOp::Lt | ast::BinOp::Le | ast::BinOp::Gt |
+                         ast::BinOp::Ge | ast::BinOp::Eq | ast::BinOp::Ne) {
+            return false;
+        }
+
+        // Move everything to the left: sum(coeff * var) + constant <op> 0
+        let mut terms = Vec::new();
+        let mut constant = 0i32;
+        if !self.extract_linear_terms(left, 1, &mut terms, &mut constant)
+            || !self.extract_linear_terms(right, -1, &mut terms, &mut constant)
+        {
+            return false;
+        }
+        terms.retain(|(coeff, _)| *coeff != 0);
+        if terms.len() < 2 {
+            return false;
+        }
+
+        let coeffs: Vec<i32> = terms.iter().map(|(c, _)| *c).collect();
+        let negated: Vec<i32> = coeffs.iter().map(|c| -c).collect();
+        let vars: Vec<VarId> = terms.iter().map(|(_, v)| *v).collect();
+        let rhs = -constant;
+        match op {
+            ast::BinOp::Eq => self.model.lin_eq(&coeffs, &vars, rhs),
+            ast::BinOp::Ne => self.model.lin_ne(&coeffs, &vars, rhs),
+            ast::BinOp::Le => self.model.lin_le(&coeffs, &vars, rhs),
+            ast::BinOp::Lt => self.model.lin_le(&coeffs, &vars, rhs - 1),
+            ast::BinOp::Ge => self.model.lin_le(&negated, &vars, -rhs),
+            ast::BinOp::Gt => self.model.lin_le(&negated, &vars, -rhs - 1),
+            _ => unreachable!(),
+        }
+        true
+    }
+
+    /// Get a VarId from an expression (either a variable reference or create a constant)
+    fn get_var_or_value(&mut self, expr: &ast::Expr) -> Result<VarId> {
+        if let Some(inner) = self.unwrap_assert_trace(expr)? {
+            return self.get_var_or_value(&inner);
+        }
+        if let ast::ExprKind::IfThenElse { cond, then_expr, else_expr } = &expr.kind {
+            let else_expr = else_expr.as_deref().ok_or_else(|| {
+                Error::message("if-then without else needs an else branch in an expression", expr.span)
+            })?;
+            return self.if_then_else_var(cond, then_expr, else_expr, expr.span);
+        }
+        if let ast::ExprKind::Let { decls, constraints, body } = &expr.kind {
+            return self.with_let_scope(decls, |t| {
+                for c in constraints {
+                    t.translate_constraint_expr(c)?;
+                }
+                t.get_var_or_value(body)
+            });
+        }
+        let debug = std::env::var("TRANSLATOR_DEBUG").is_ok();
+        match &expr.kind {
+            ast::ExprKind::Ident(name) => {
+                // Try integer variable
+                if let Some(var) = self.context.get_int_var(name) {
+                    if debug {
+                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> existing var {:?}", name, var);
+                    }
+                    return Ok(var);
+                }
+                // Try boolean variable
+                if let Some(var) = self.context.get_bool_var(name) {
+                    if debug {
+                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> existing bool var {:?}", name, var);
+                    }
+                    return Ok(var);
+                }
+                // Try float variable
+                if let Some(var) = self.context.get_float_var(name) {
+                    if debug {
+                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> existing float var {:?}", name, var);
+                    }
+                    return Ok(var);
+                }
+                // Try integer parameter
+                if let Some(value) = self.context.get_int_param(name) {
+                    // Create a constant variable
+                    let const_var = self.int_constant(value);
+                    if debug {
+                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> new constant {:?} (value={})", name, const_var, value);
+                    }
+                    return Ok(const_var);
+                }
+                // Try float parameter
+                if let Some(value) = self.context.get_float_param(name) {
+                    // Create a constant variable
+                    let const_var = self.model.float(value, value);
+                    if debug {
+                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> new constant {:?} (value={})", name, const_var, value);
+                    }
+                    return Ok(const_var);
+                }
+                // Try boolean parameter
+                if let Some(value) = self.context.get_bool_param(name) {
+                    // Create a constant variable (0 or 1)
+                    let val = if value { 1 } else { 0 };
+                    let const_var = self.int_constant(val);
+                    if debug {
+                        eprintln!("TRANSLATOR_DEBUG: get_var_or_value(Ident({})) -> new constant {:?} (value={})", name, const_var, val);
+                    }
+                    return Ok(const_var);
+                }
+                // Not found - give helpful error
+                Err(Error::message(
+                    &format!("Undefined variable or parameter: '{}'", name),
+                    expr.span,
+                ))
+            }
+            ast::ExprKind::IntLit(i) => {
+                // Literal comparisons are handled in translate_constraint_binop without a variable;
+                // elsewhere share one constant variable per value
+                Ok(self.int_constant(*i as i32))
+            }
+            ast::ExprKind::FloatLit(f) => {
+                // Create a constant float variable
+                Ok(self.model.float(*f, *f))
+            }
+            ast::ExprKind::BoolLit(b) => {
+                // Create a constant boolean variable (0 or 1)
+                let val = if *b { 1 } else { 0 };
+                Ok(self.int_constant(val))
+            }
+            ast::ExprKind::UnOp { op: ast::UnOp::Neg, expr: inner } => {
+                // Fold negated literals into a single constant
+                if let Some(value) = Self::extract_const_value(expr) {
+                    return Ok(self.int_constant(value as i32));
+                }
+                if let ast::ExprKind::FloatLit(f) = inner.kind {
+                    return Ok(self.model.float(-f, -f));
+                }
+                let inner_var = self.get_var_or_value(inner)?;
+                let zero = self.model.int(0, 0);
+                Ok(self.model.sub(zero, inner_var))
+            }
+            ast::ExprKind::BinOp { op, left, right } => {
+                if *op == ast::BinOp::Mod
+                    && let Some(modulus) = self.const_int_operand(right).and_then(|m| i32::try_from(m).ok())
+                    && let Some(factors) = self.product_operands(left)?
+                {
+                    return self.product_mod(&factors, modulus, expr.span);
+                }
+
+                let left_var = self.get_var_or_value(left)?;
+                let right_var = self.get_var_or_value(right)?;
+
+                match op {
+                    ast::BinOp::Add => Ok(self.model.add(left_var, right_var)),
+                    ast::BinOp::Sub => Ok(self.model.sub(left_var, right_var)),
+                    ast::BinOp::Mul => self.checked_mul(left_var, right_var, expr.span),
+                    ast::BinOp::Div => Ok(self.int_div(left_var, right_var)),
+                    ast::BinOp::FDiv => Ok(self.model.div(left_var, right_var)),
+                    ast::BinOp::Mod => {
+                        if std::env::var("ZELEN_DEBUG").is_ok() {
+                            eprintln!("DEBUG: Creating modulo: {:?} mod {:?}", left_var, right_var);
+                        }
+                        let result = self.int_mod(left_var, right_var);
+                        if std::env::var("ZELEN_DEBUG").is_ok() {
+                            eprintln!("DEBUG:   -> Modulo result VarId: {:?}", result);
+                        }
+                        Ok(result)
+                    }
+                    _ => Err(Error::unsupported_feature(
+                        &format!("Binary operator {:?} in expressions", op),
+                        "Phase 2",
+                        expr.span,
+                    )),
+                }
+            }
+            ast::ExprKind::ArrayAccess { array, indices } => {
+                // Get the array name
+                let array_name = match &array.kind {
+                    ast::ExprKind::Ident(name) => name,
+                    _ => {
+                        return Err(Error::message(
+                            "Array access must use simple array name",
+                            array.span,
+                        ));
+                    }
+                };
+                
+                // Try to handle as multi-dimensional if multiple indices
+                if indices.len() > 1 {
+                    // Multi-dimensional array access - use native 2D/3D element constraints
+                    if let Some(metadata) = self.context.array_metadata.get(array_name).cloned() {
+                        if metadata.dimensions.len() != indices.len() {
+                            return Err(Error::message(
+                                &format!(
+                                    "Array index dimension mismatch: expected {}, got {}",
+                                    metadata.dimensions.len(),
+                                    indices.len()
+                                ),
+                                expr.span,
+                            ));
+                        }
+                        
+                        // Reject constant indices outside their dimension before building constraints
+                        for (dim, idx) in indices.iter().enumerate() {
+                            if let Ok(val) = self.eval_int_expr(idx)
+                                && metadata.offset(dim, val).is_none()
+                            {
+                                return Err(Error::message(
+                                    &format!(
+                                        "Array index {} out of bounds for dimension {} of '{}' (size {})",
+                                        val, dim + 1, array_name, metadata.dimensions[dim]
+                                    ),
+                                    idx.span,
+                                ));
+                            }
+                        }
+                        
+                        // Constant indices resolve straight to the flattened element
+                        if let Some(flat) = self.const_flat_index(array_name, indices) {
+                            return self.flat_array_element(array_name, flat).ok_or_else(|| {
+                                Error::message(&format!("Undefined array: '{}'", array_name), array.span)
+                            });
+                        }
+
+                        // Variable indices - bound each one to its own dimension (so a
+                        // column past the end cannot alias into the next row), flatten
+                        // them with a single linear equality and index the flattened
+                        // array (parameter values become constants)
+                        let mut coeffs = Vec::with_capacity(indices.len() + 1);
+                        let mut vars = Vec::with_capacity(indices.len() + 1);
+                        let mut multiplier = 1i32;
+                        for (dim_idx, idx) in indices.iter().enumerate().rev() {
+                            let index_var = self.get_var_or_value(idx)?;
+                            let start = metadata.starts[dim_idx];
+                            let size = metadata.dimensions[dim_idx];
+                            coeffs.push(multiplier);
+                            vars.push(self.element_index_from(index_var, start, size));
+                            multiplier = multiplier.saturating_mul(size as i32);
+                        }
+                        // flat = sum(m_k * (i_k - start_k)), zero-based
+                        let flat_index_var = self.model.int(0, metadata.total_size() as i32 - 1);
+                        coeffs.push(-1);
+                        vars.push(flat_index_var);
+                        self.model.lin_eq(&coeffs, &vars, 0);
+
+                        if let Some(arr) = self.flat_array_as_vars(array_name) {
+                            let result = if self.context.get_bool_var_array(array_name).is_some()
+                                || self.context.get_bool_param_array(array_name).is_some()
+                            {
+                                self.model.bool()
+                            } else if self.context.get_float_var_array(array_name).is_some()
+                                || self.context.get_float_param_array(array_name).is_some()
+                            {
+                                self.model.float(f64::MIN, f64::MAX)
+                            } else {
+                                self.element_int_result(&arr, flat_index_var)
+                            };
+                            self.model.element(&arr, flat_index_var, result);
+                            return Ok(result);
+                        }
+
+                        return Err(Error::message(
+                            &format!("Undefined array: '{}'", array_name),
+                            array.span,
+                        ));
+                    } else {
+                        return Err(Error::message(
+                            &format!("Array metadata not found for: '{}'", array_name),
+                            array.span,
+                        ));
+                    }
+                }
+                
+                // 1D array access - original logic
+                let index = &indices[0];
+                
+                // Try to evaluate the index expression to a constant first
+                let start = self.context.array_start(array_name);
+                if let Ok(index_val) = self.eval_int_expr(index) {
+                    // Constant index - direct array access
+                    if let Some(len) = self.context.array_len(array_name)
+                        && (index_val < start || (index_val - start) as usize >= len)
+                    {
+                        return Err(Error::message(
+                            &format!("Array index {} out of bounds for '{}' (size {})", index_val, array_name, len),
+                            index.span,
+                        ));
+                    }
+                    let array_index = (index_val - start) as usize;
+                    
+                    if let Some(arr) = self.context.get_int_var_array(array_name) {
+                        if array_index < arr.len() {
+                            return Ok(arr[array_index]);
+                        }
+                    }
+                    if let Some(arr) = self.context.get_int_param_array(array_name) {
+                        if array_index < arr.len() {
+                            let val = arr[array_index];
+                            return Ok(self.int_constant(val));
+                        }
+                    }
+                    if let Some(arr) = self.context.get_bool_var_array(array_name) {
+                        if array_index < arr.len() {
+                            return Ok(arr[array_index]);
+                        }
+                    }
+                    if let Some(arr) = self.context.get_bool_param_array(array_name) {
+                        if array_index < arr.len() {
+                            let val = if arr[array_index] { 1 } else { 0 };
+                            return Ok(self.int_constant(val));
+                        }
+                    }
+                    if let Some(arr) = self.context.get_float_var_array(array_name) {
+                        if array_index < arr.len() {
+                            return Ok(arr[array_index]);
+                        }
+                    }
+                    if let Some(arr) = self.context.get_float_param_array(array_name) {
+                        if array_index < arr.len() {
+                            let val = arr[array_index];
+                            return Ok(self.model.float(val, val));
+                        }
+                    }
+                    
+                    return Err(Error::message(
+                        &format!("Undefined array: '{}'", array_name),
+                        array.span,
+                    ));
+                }
+                
+                // Variable index - use element constraint
+                let index_var = self.get_var_or_value(index)?;
+                
+                if let Some(arr) = self.context.get_int_var_array(array_name).cloned() {
+                    let zero_based_index = self.element_index_from(index_var, start, arr.len());
+                    let result = self.element_int_result(&arr, zero_based_index);
+                    self.model.element(&arr, zero_based_index, result);
+                    return Ok(result);
+                }
+                if let Some(arr) = self.context.get_bool_var_array(array_name).cloned() {
+                    let zero_based_index = self.element_index_from(index_var, start, arr.len());
+                    let result = self.model.bool();
+                    self.model.element(&arr, zero_based_index, result);
+                    return Ok(result);
+                }
+                if let Some(arr) = self.context.get_float_var_array(array_name).cloned() {
+                    let zero_based_index = self.element_index_from(index_var, start, arr.len());
+                    let result = self.model.float(f64::MIN, f64::MAX);
+                    self.model.element(&arr, zero_based_index, result);
+                    return Ok(result);
+                }
+                if let Some(values) = self.context.get_int_param_array(array_name).cloned() {
+                    // Lift the constants into fixed variables so element can index them
+                    let arr: Vec<VarId> = values.iter().map(|&v| self.int_constant(v)).collect();
+                    let zero_based_index = self.element_index_from(index_var, start, arr.len());
+                    let result = self.element_int_result(&arr, zero_based_index);
+                    self.model.element(&arr, zero_based_index, result);
+                    return Ok(result);
+                }
+                if let Some(values) = self.context.get_bool_param_array(array_name).cloned() {
+                    let arr: Vec<VarId> = values.iter()
+                        .map(|&v| if v { self.model.int(1, 1) } else { self.model.int(0, 0) })
+                        .collect();
+                    let zero_based_index = self.element_index_from(index_var, start, arr.len());
+                    let result = self.model.bool();
+                    self.model.element(&arr, zero_based_index, result);
+                    return Ok(result);
+                }
+                
+                Err(Error::message(
+                    &format!("Undefined array: '{}'", array_name),
+                    array.span,
+                ))
+            }
+            ast::ExprKind::Call { .. } if let Some(result) = self.inline_user_call(expr, Self::get_var_or_value) => {
+                result
+            }
+            ast::ExprKind::Call { name, args } => {
+                // Handle aggregate functions
+                self.translate_aggregate_call(name, args, expr.span)
+            }
+            ast::ExprKind::GenCall { name, generators, body } => {
+                // Handle aggregates over generators: sum(i in s)(w[i])
+                self.translate_aggregate_gencall(name, generators, body, expr.span)
+            }
+            _ => Err(Error::unsupported_feature(
+                &format!("Expression type: {:?}", expr.kind),
+                "Phase 2",
+                expr.span,
+            )),
+        }
+    }
+
+    /// Translate aggregate generator calls like `sum(i in 1..n)(x[i])`
+    fn translate_aggregate_gencall(
+        &mut self,
+        name: &str,
+        generators: &[ast::Generator],
+        body: &ast::Expr,
+        span: ast::Span,
+    ) -> Result<VarId> {
+        match name {
+            "sum" => {
+                let generators = Self::flatten_generators(generators);
+                let terms = self.collect_generator_terms(&generators, body)?;
+                if terms.is_empty() {
+                    return Ok(self.model.int(0, 0));
+                }
+                Ok(self.model.sum(&terms))
+            }
+            "product" | "min" | "max" => {
+                let terms = self.generator_operands(name, generators, body, span)?;
+                match name {
+                    "product" if terms.is_empty() => Ok(self.int_constant(1)),
+                    "product" => self.product_of(&terms, span),
+                    _ => self.min_max_of(&terms, name == "max", span),
+                }
+            }
+            _ => Err(Error::unsupported_feature(
+                &format!("Generator call '{}' in expressions", name),
+                "Phase 2",
+                span,
+            )),
+        }
+    }
+
+    /// One operand per generator binding for `product`/`min`/`max`, which have no
+    /// neutral element to substitute for non-members of a set variable
+    fn generator_operands(
+        &mut self,
+        name: &str,
+        generators: &[ast::Generator],
+        body: &ast::Expr,
+        span: ast::Span,
+    ) -> Result<Vec<VarId>> {
+        let generators = Self::flatten_generators(generators);
+        let mut terms = Vec::new();
+        self.visit_generator_bindings(&generators, 0, body, &mut Vec::new(), &mut |t, body, guards| {
+            if !guards.is_empty() {
+                return Err(Error::unsupported_feature(
+                    &format!("{}() over a set variable", name),
+                    "Phase 2",
+                    span,
+                ));
+            }
+            terms.push(t.get_var_or_value(body)?);
+            Ok(())
+        })?;
+        Ok(terms)
+    }
+
+    /// Recursively expand generators, calling `visit` once per binding
+    ///
+    /// Ranges are unrolled directly. A generator over a set variable iterates its
+    /// whole universe; the membership indicators of the elements bound so far are
+    /// passed to `visit` as guards so callers can discount non-members.
+    fn visit_generator_bindings(
+        &mut self,
+        generators: &[ast::Generator],
+        depth: usize,
+        body: &ast::Expr,
+        guards: &mut Vec<VarId>,
+        visit: &mut GeneratorVisitor<'_>,
+    ) -> Result<()> {
+        if depth >= generators.len() {
+            return visit(self, body, guards);
+        }
+
+        let generator = &generators[depth];
+        if generator.names.len() != 1 {
+            return Err(Error::message(
+                "Generator must have exactly one variable",
+                generator.expr.span,
+            ));
+        }
+        let loop_var = &generator.names[0];
+
+        // Either a set variable (value, Some(indicator)) or a plain range (value, None)
+        let set_var = self
+            .context
+            .resolve_set_var(&generator.expr, |e| self.eval_int_expr(e).ok())
+            .cloned();
+        let iterations: Vec<(i32, Option<VarId>)> = match set_var {
+            Some(set_var) => set_var.members().map(|(value, ind)| (value, Some(ind))).collect(),
+            None => self.generator_values(&generator.expr)?.into_iter().map(|value| (value, None)).collect(),
+        };
+
+        for (value, indicator) in iterations {
+            let old_val = self.context.int_params.get(loop_var).copied();
+            self.context.int_params.insert(loop_var.clone(), value);
+
+            if self.generator_filter_passes(generator)? {
+                let substituted = self.substitute_loop_var_in_expr(body, loop_var, value)?;
+                guards.extend(indicator);
+                let result = self.visit_generator_bindings(generators, depth + 1, &substituted, guards, visit);
+                if indicator.is_some() {
+                    guards.pop();
+                }
+                result?;
+            }
+
+            Self::restore_loop_var(&mut self.context.int_params, loop_var, old_val);
+        }
+
+        Ok(())
+    }
+
+    /// Collect one integer term per generator binding; set-variable non-members
+    /// contribute zero
+    fn collect_generator_terms(&mut self, generators: &[ast::Generator], body: &ast::Expr) -> Result<Vec<VarId>> {
+        let mut terms = Vec::new();
+        self.visit_generator_bindings(generators, 0, body, &mut Vec::new(), &mut |t, body, guards| {
+            let mut term = t.get_var_or_value(body)?;
+            for &guard in guards {
+                term = t.model.mul(guard, term);
+            }
+            terms.push(term);
+            Ok(())
+        })?;
+        Ok(terms)
+    }
+
+    /// Collect one reified body per generator binding for `exists`; a binding
+    /// over a set variable only counts when its element is a member
+    fn collect_generator_witnesses(&mut self, generators: &[ast::Generator], body: &ast::Expr) -> Result<Vec<VarId>> {
+        let mut witnesses = Vec::new();
+        self.visit_generator_bindings(generators, 0, body, &mut Vec::new(), &mut |t, body, guards| {
+            let holds = t.expr_to_bool_var(body)?;
+            if guards.is_empty() {
+                witnesses.push(holds);
+            } else {
+                let mut operands = guards.to_vec();
+                operands.push(holds);
+                witnesses.push(t.model.bool_and(&operands));
+            }
+            Ok(())
+        })?;
+        Ok(witnesses)
+    }
+
+    /// Post `left <op> right` where at least one side is a float variable
+    ///
+    /// Selen's expression comparisons between two float variables do not prune,
+    /// so they are posted as float linear constraints over `left - right`.
+    fn post_float_comparison(&mut self, op: ast::BinOp, left: VarId, right: VarId) {
+        let vars = [left, right];
+        match op {
+            ast::BinOp::Eq => self.model.lin_eq(&[1.0, -1.0], &vars, 0.0),
+            ast::BinOp::Ne => self.model.lin_ne(&[1.0, -1.0], &vars, 0.0),
+            ast::BinOp::Le => self.model.lin_le(&[1.0, -1.0], &vars, 0.0),
+            ast::BinOp::Ge => self.model.lin_le(&[-1.0, 1.0], &vars, 0.0),
+            ast::BinOp::Lt => {
+                self.model.lin_le(&[1.0, -1.0], &vars, 0.0);
+                self.model.lin_ne(&[1.0, -1.0], &vars, 0.0);
+            }
+            ast::BinOp::Gt => {
+                self.model.lin_le(&[-1.0, 1.0], &vars, 0.0);
+                self.model.lin_ne(&[1.0, -1.0], &vars, 0.0);
+            }
+            _ => unreachable!("post_float_comparison called with non-comparison {:?}", op),
+        }
+    }
+
+    /// Post `var <op> value` for a float constant
+    ///
+    /// Integer variables compare through a fixed float variable, as in
+    /// [`Self::post_float_comparison`].
+    fn post_float_bound(&mut self, op: ast::BinOp, var: VarId, value: f64) {
+        if self.int_var_bounds(var).is_some() {
+            let constant = self.model.float(value, value);
+            self.post_float_comparison(op, var, constant);
+            return;
+        }
+        match op {
+            ast::BinOp::Eq => self.model.lin_eq(&[1.0], &[var], value),
+            ast::BinOp::Ne => self.model.lin_ne(&[1.0], &[var], value),
+            ast::BinOp::Le => self.model.lin_le(&[1.0], &[var], value),
+            ast::BinOp::Ge => self.model.lin_le(&[-1.0], &[var], -value),
+            ast::BinOp::Lt => {
+                self.model.lin_le(&[1.0], &[var], value);
+                self.model.lin_ne(&[1.0], &[var], value);
+            }
+            ast::BinOp::Gt => {
+                self.model.lin_le(&[-1.0], &[var], -value);
+                self.model.lin_ne(&[1.0], &[var], value);
+            }
+            _ => unreachable!("post_float_bound called with non-comparison {:?}", op),
+        }
+    }
+
+    /// Integer result of `floor`, `ceil` or `round` over the float variable `f`
+    /// with bounds `lo..hi`, channeled by `r <= f < r + 1`, `r - 1 < f <= r`
+    /// and `|f - r| <= 0.5` respectively; like `f64::round`, halves round away
+    /// from zero
+    fn post_float_rounding(&mut self, name: &str, f: VarId, lo: f64, hi: f64) -> VarId {
+        let round = |x: f64| match name {
+            "floor" => x.floor(),
+            "ceil" => x.ceil(),
+            _ => x.round(),
+        };
+        let r = self.model.int(round(lo) as i32, round(hi) as i32);
+        let vars = [r, f];
+        let (below, above) = match name {
+            "floor" => (0.0, 1.0),
+            "ceil" => (1.0, 0.0),
+            _ => (0.5, 0.5),
+        };
+        // r - f <= below, and f - r <= above with equality excluded unless zero
+        self.model.lin_le(&[1.0, -1.0], &vars, below);
+        self.model.lin_le(&[-1.0, 1.0], &vars, above);
+        match name {
+            "floor" => self.model.lin_ne(&[-1.0, 1.0], &vars, above),
+            "ceil" => self.model.lin_ne(&[1.0, -1.0], &vars, below),
+            // A half rounds up when f >= 0 (excluding f - r = 0.5) and down
+            // otherwise (excluding r - f = 0.5)
+            _ if lo >= 0.0 => self.model.lin_ne(&[-1.0, 1.0], &vars, 0.5),
+            _ if hi < 0.0 => self.model.lin_ne(&[1.0, -1.0], &vars, 0.5),
+            _ => {
+                let (nonneg, half_down, half_up) = (self.model.bool(), self.model.bool(), self.model.bool());
+                self.model.lin_le_reif(&[-1.0], &[f], 0.0, nonneg);
+                self.model.lin_eq_reif(&[-1.0, 1.0], &vars, 0.5, half_down);
+                self.model.lin_eq_reif(&[1.0, -1.0], &vars, 0.5, half_up);
+                self.model.lin_le(&[1, 1], &[half_down, nonneg], 1);
+                self.model.lin_le(&[1, -1], &[half_up, nonneg], 0);
+            }
+        }
+        r
+    }
+
+    /// Current integer bounds of a variable, or None for float variables
+    fn int_var_bounds(&self, var: VarId) -> Option<(i32, i32)> {
+        match &self.model.get_vars()[var] {
+            Var::VarI(domain) => Some((domain.min(), domain.max())),
+            Var::VarF(_) => None,
+        }
+    }
+
+    /// Zero-based position for an `element` constraint indexed by the one-based
+    /// `index_var`, restricted to the positions the index can actually reach
+    fn element_index(&mut self, index_var: VarId, len: usize) -> VarId {
+        self.element_index_from(index_var, 1, len)
+    }
+
+    /// Like [`Self::element_index`] for an array whose first index is `start`
+    fn element_index_from(&mut self, index_var: VarId, start: i32, len: usize) -> VarId {
+        let last = len as i32 - 1;
+        let (lo, hi) = match self.int_var_bounds(index_var) {
+            Some((lo, hi)) => (lo.saturating_sub(start).max(0), hi.saturating_sub(start).min(last)),
+            None => (0, last),
+        };
+        // An index that misses the array entirely keeps the full range so the
+        // equality below fails instead of creating an empty domain
+        let zero_based = if lo <= hi { self.model.int(lo, hi) } else { self.model.int(0, last) };
+        self.model.lin_eq(&[1, -1], &[zero_based, index_var], -start);
+        zero_based
+    }
+
+    /// Result variable for `arr[index]`, bounded by the elements `index` can select
+    fn element_int_result(&mut self, arr: &[VarId], zero_based: VarId) -> VarId {
+        let (lo, hi) = self.int_var_bounds(zero_based).unwrap_or((0, arr.len() as i32 - 1));
+        let reachable = arr.iter()
+            .skip(lo.max(0) as usize)
+            .take((hi - lo + 1).max(0) as usize)
+            .map(|&v| self.int_var_bounds(v));
+        let mut bounds: Option<(i32, i32)> = None;
+        for b in reachable {
+            let Some((min, max)) = b else { return self.model.int(i32::MIN, i32::MAX) };
+            bounds = Some(match bounds {
+                Some((lo, hi)) => (lo.min(min), hi.max(max)),
+                None => (min, max),
+            });
+        }
+        match bounds {
+            Some((min, max)) => self.model.int(min, max),
+            None => self.model.int(i32::MIN, i32::MAX),
+        }
+    }
+
+    /// Post the implied sum of an all-different array whose variables all share
+    /// the domain `lo..lo+n-1`, i.e. the array must be a permutation of it
+    fn post_permutation_sum(&mut self, vars: &[VarId]) {
+        let Some(first) = vars.first().and_then(|&v| self.int_var_bounds(v)) else {
+            return;
+        };
+        let n = vars.len() as i64;
+        let (lo, hi) = first;
+        if hi as i64 - lo as i64 + 1 != n || vars.iter().any(|&v| self.int_var_bounds(v) != Some(first)) {
+            return;
+        }
+        let total = n * lo as i64 + n * (n - 1) / 2;
+        if let Ok(total) = i32::try_from(total) {
+            let ones = vec![1; vars.len()];
+            self.model.lin_eq(&ones, vars, total);
+        }
+    }
+
+    /// Translate `max`/`min` over integer variables
+    ///
+    /// Posted as `r >= x_i` for every operand plus a clause that `r` equals one
+    /// of them (or the `<=` mirror for min). Selen's global max/min propagators
+    /// derive wrong bounds when operands are constant or become fixed during
+    /// search, which breaks clamps like `max(0, x - k)` and `max(a) < max(b)`.
+    fn min_max_of(&mut self, vars: &[VarId], is_max: bool, span: ast::Span) -> Result<VarId> {
+        let name = if is_max { "max" } else { "min" };
+        if vars.is_empty() {
+            return Err(Error::message(
+                &format!("{}() of an empty array is undefined", name),
+                span,
+            ));
+        }
+        let bounds: Option<Vec<(i32, i32)>> = vars.iter().map(|&v| self.int_var_bounds(v)).collect();
+        let Some(bounds) = bounds else {
+            let result = if is_max { self.model.max(vars) } else { self.model.min(vars) };
+            return result.map_err(|e| Error::message(&format!("{}(): {}", name, e), span));
+        };
+
+        let (lo, hi) = if is_max {
+            (bounds.iter().map(|b| b.0).max().unwrap(), bounds.iter().map(|b| b.1).max().unwrap())
+        } else {
+            (bounds.iter().map(|b| b.0).min().unwrap(), bounds.iter().map(|b| b.1).min().unwrap())
+        };
+        let result = self.model.int(lo, hi);
+        let mut witnesses = Vec::with_capacity(vars.len());
+        for &var in vars {
+            if is_max {
+                self.model.new(result.ge(var));
+            } else {
+                self.model.new(result.le(var));
+            }
+            let is_var = self.model.bool();
+            self.model.eq_reif(result, var, is_var);
+            witnesses.push(is_var);
+        }
+        self.model.bool_clause(&witnesses, &[]);
+        Ok(result)
+    }
+
+    /// Truncated quotient and remainder of integer `a div b` and `a mod b`,
+    /// with no solution when `b` is 0, as in MiniZinc
+    ///
+    /// Posted as `a = b * q + r` with `|r| < |b|` and `r` zero or carrying the
+    /// sign of `a`. Selen's `div` is real division with a float result and its
+    /// `modulo` misses solutions once the dividend is a variable, so neither is
+    /// used. Returns `None` when either operand's bounds are unknown.
+    fn int_div_mod(&mut self, a: VarId, b: VarId) -> Option<(VarId, VarId)> {
+        let (a_min, a_max) = self.int_var_bounds(a)?;
+        let (b_min, b_max) = self.int_var_bounds(b)?;
+
+        // Extreme quotients come from the dividend bounds over the divisor bounds
+        // nearest to and furthest from zero
+        let mut divisors = vec![b_min, b_max];
+        if b_min <= -1 && -1 <= b_max {
+            divisors.push(-1);
+        }
+        if b_min <= 1 && 1 <= b_max {
+            divisors.push(1);
+        }
+        let mut q_min = 0i64;
+        let mut q_max = 0i64;
+        for &x in &[a_min, a_max] {
+            for &y in divisors.iter().filter(|&&y| y != 0) {
+                let q = i64::from(x) / i64::from(y);
+                q_min = q_min.min(q);
+                q_max = q_max.max(q);
+            }
+        }
+        let clamp = |v: i64| v.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32;
+        let quotient = self.model.int(clamp(q_min), clamp(q_max));
+
+        // The remainder is below the divisor and the dividend in magnitude
+        let a_abs = i64::from(a_min).abs().max(i64::from(a_max).abs());
+        let b_abs = i64::from(b_min).abs().max(i64::from(b_max).abs());
+        let r_abs = clamp(a_abs.min(b_abs - 1).max(0));
+        let r_min = if a_min < 0 { -r_abs } else { 0 };
+        let r_max = if a_max > 0 { r_abs } else { 0 };
+        let remainder = self.model.int(r_min, r_max);
+
+        if b_min == b_max {
+            if b_min == 0 {
+                self.model.new(b.ne(0));
+            }
+            self.model.lin_eq(&[1, -b_min, -1], &[a, quotient, remainder], 0);
+        } else {
+            let product = self.model.mul(b, quotient);
+            self.model.lin_eq(&[1, -1, -1], &[a, product, remainder], 0);
+            let (r_magnitude, b_magnitude) = (self.model.abs(remainder), self.model.abs(b));
+            self.model.lin_le(&[1, -1], &[r_magnitude, b_magnitude], -1);
+        }
+
+        // When the dividend may take either sign, the remainder follows it
+        if r_min < 0 && r_max > 0 {
+            let nonneg = self.model.bool();
+            self.model.lin_le_reif(&[-1], &[a], 0, nonneg);
+            self.model.lin_le(&[-1, -r_min], &[remainder, nonneg], -r_min);
+            self.model.lin_le(&[1, -r_max], &[remainder, nonneg], 0);
+        }
+        Some((quotient, remainder))
+    }
+
+    /// Translate integer `a div b`, truncating toward zero as MiniZinc does
+    fn int_div(&mut self, a: VarId, b: VarId) -> VarId {
+        match self.int_div_mod(a, b) {
+            Some((quotient, _)) => quotient,
+            None => self.model.div(a, b),
+        }
+    }
+
+    /// Translate integer `a mod b`, taking the sign of the dividend as MiniZinc does
+    fn int_mod(&mut self, a: VarId, b: VarId) -> VarId {
+        match self.int_div_mod(a, b) {
+            Some((_, remainder)) => remainder,
+            None => self.model.modulo(a, b),
+        }
+    }
+
+    /// `a * b`, rejected when the product's bounds leave the 32-bit range instead
+    /// of letting the solver wrap them
+    fn checked_mul(&mut self, a: VarId, b: VarId, span: ast::Span) -> Result<VarId> {
+        if let (Some((a_min, a_max)), Some((b_min, b_max))) = (self.int_var_bounds(a), self.int_var_bounds(b)) {
+            let corners = [a_min, a_max].into_iter()
+                .flat_map(|x| [b_min, b_max].map(|y| i64::from(x) * i64::from(y)));
+            for corner in corners {
+                if i32::try_from(corner).is_err() {
+                    return Err(Error::message(
+                        &format!(
+                            "Integer overflow: product of {}..{} and {}..{} exceeds the 32-bit range",
+                            a_min, a_max, b_min, b_max
+                        ),
+                        span,
+                    )
+                    .with_workaround("Tighten the factor domains or reduce the product with `mod`"));
+                }
+            }
+        }
+        Ok(self.model.mul(a, b))
+    }
+
+    /// Product of a non-empty list of factors
+    fn product_of(&mut self, factors: &[VarId], span: ast::Span) -> Result<VarId> {
+        let mut result = factors[0];
+        for &factor in &factors[1..] {
+            result = self.checked_mul(result, factor, span)?;
+        }
+        Ok(result)
+    }
+
+    /// Factors of a `product(...)` call or generator call, if `expr` is one
+    fn product_operands(&mut self, expr: &ast::Expr) -> Result<Option<Vec<VarId>>> {
+        match &expr.kind {
+            ast::ExprKind::Call { name, args } if name == "product" && args.len() == 1 => {
+                Ok(Some(self.get_array_vars(&args[0])?))
+            }
+            ast::ExprKind::GenCall { name, generators, body } if name == "product" => {
+                Ok(Some(self.generator_operands(name, generators, body, expr.span)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// `product(factors) mod modulus`, reducing after every multiplication so the
+    /// intermediates stay within `modulus * max|factor|` even when the full
+    /// product would overflow
+    ///
+    /// Truncated remainders distribute over multiplication
+    /// (`(a * b) mod m == ((a mod m) * b) mod m`), so this matches MiniZinc.
+    /// Each step is posted as `acc * f = m * k + r` through [`Self::int_mod`].
+    fn product_mod(&mut self, factors: &[VarId], modulus: i32, span: ast::Span) -> Result<VarId> {
+        if modulus == 0 {
+            return Err(Error::message("Modulo by zero", span));
+        }
+        let Some((&first, rest)) = factors.split_first() else {
+            return Ok(self.int_constant(1 % modulus));
+        };
+        let modulus = self.int_constant(modulus);
+        let mut acc = self.int_mod(first, modulus);
+        for &factor in rest {
+            let product = self.checked_mul(acc, factor, span)?;
+            acc = self.int_mod(product, modulus);
+        }
+        Ok(acc)
+    }
+
+    /// Translate aggregate function calls (sum, min, max, etc.)
+    fn translate_aggregate_call(&mut self, name: &str, args: &[ast::Expr], span: ast::Span) -> Result<VarId> {
+        match name {
+            "enum_next" | "enum_prev" => {
+                let (values, x) = self.enum_step_operands(args, span)?;
+                if self.eval_int_expr(x).is_ok() {
+                    let value = self.eval_int_call(name, args, span)?;
+                    return Ok(self.int_constant(value));
+                }
+                let cardinality = values.len() as i32;
+                let x = self.get_var_or_value(x)?;
+                // The last value has no successor (and the first no predecessor),
+                // so the operand is restricted to values where the step exists
+                let (result, offset) = if name == "enum_next" {
+                    self.model.new(x.le(cardinality - 1));
+                    (self.model.int(2, cardinality.max(2)), 1)
+                } else {
+                    self.model.new(x.ge(2));
+                    (self.model.int(1, (cardinality - 1).max(1)), -1)
+                };
+                self.model.lin_eq(&[1, -1], &[result, x], offset);
+                Ok(result)
+            }
+            "abs" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                if let Ok(value) = self.eval_int_call(name, args, span) {
+                    return Ok(self.int_constant(value));
+                }
+                let x = self.get_var_or_value(&args[0])?;
+                Ok(self.model.abs(x))
+            }
+            "int2float" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                if let Ok(value) = self.eval_int_expr(&args[0]) {
+                    return Ok(self.model.float(value as f64, value as f64));
+                }
+                let x = self.get_var_or_value(&args[0])?;
+                let Some((lo, hi)) = self.int_var_bounds(x) else {
+                    return Err(Error::type_error("integer expression", "float expression", args[0].span));
+                };
+                // A float copy of x, so float arithmetic sees a float operand
+                let f = self.model.float(lo as f64, hi as f64);
+                self.model.lin_eq(&[1.0, -1.0], &[f, x], 0.0);
+                Ok(f)
+            }
+            "floor" | "ceil" | "round" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                if let Ok(value) = self.eval_int_call(name, args, span) {
+                    return Ok(self.int_constant(value));
+                }
+                let f = self.get_var_or_value(&args[0])?;
+                match &self.model.get_vars()[f] {
+                    Var::VarF(domain) => {
+                        let (lo, hi) = (domain.min, domain.max);
+                        Ok(self.post_float_rounding(name, f, lo, hi))
+                    }
+                    // Integers are already whole
+                    Var::VarI(_) => Ok(f),
+                }
+            }
+            "bool2int" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                if let Ok(value) = self.eval_int_call(name, args, span) {
+                    return Ok(self.int_constant(value));
+                }
+                // Boolean variables already take the values 0 and 1
+                self.expr_to_bool_var(&args[0])
+            }
+            "card" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                let set = self
+                    .set_operand(&args[0])?
+                    .ok_or_else(|| Error::type_error("set expression", "other expression", args[0].span))?;
+                if set.indicators.is_empty() {
+                    return Ok(self.int_constant(0));
+                }
+                Ok(self.model.sum(&set.indicators))
+            }
+            "sum" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                
+                // Get the array; the sum of no elements is 0
+                let vars = self.get_array_vars(&args[0])?;
+                if vars.is_empty() {
+                    return Ok(self.int_constant(0));
+                }
+                Ok(self.model.sum(&vars))
+            }
+            "min" => {
+                if args.len() == 2 {
+                    // Binary form: min(a, b)
+                    let a = self.get_var_or_value(&args[0])?;
+                    let b = self.get_var_or_value(&args[1])?;
+                    return self.min_max_of(&[a, b], false, span);
+                }
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 or 2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                
+                let vars = self.get_array_vars(&args[0])?;
+                self.min_max_of(&vars, false, span)
+            }
+            "max" => {
+                if args.len() == 2 {
+                    // Binary form: max(a, b)
+                    let a = self.get_var_or_value(&args[0])?;
+                    let b = self.get_var_or_value(&args[1])?;
+                    return self.min_max_of(&[a, b], true, span);
+                }
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 or 2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                
+                let vars = self.get_array_vars(&args[0])?;
+                self.min_max_of(&vars, true, span)
+            }
+            "product" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                
+                // Product doesn't have a built-in Selen function for arrays
+                // We need to multiply all elements together
+                let vars = self.get_array_vars(&args[0])?;
+                if vars.is_empty() {
+                    return Ok(self.int_constant(1));
+                }
+                
+                self.product_of(&vars, span)
+            }
+            "count" => {
+                if args.len() != 2 {
+                    return Err(Error::type_error(
+                        "2 arguments",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                
+                // Get the array
+                let vars = self.get_array_vars(&args[0])?;
+                
+                // Get the value to count
+                let value = self.get_var_or_value(&args[1])?;
+                if vars.is_empty() {
+                    return Ok(self.int_constant(0));
+                }
+                
+                // Create a result variable for the count (0 to array length)
+                let count_result = self.model.int(0, vars.len() as i32);
+                
+                // Call Selen's count_var constraint (supports both constant and variable values)
+                self.model.count(&vars, value, count_result);
+                
+                Ok(count_result)
+            }
+            "exists" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                
+                // Get the array (should be boolean variables)
+                let vars = self.get_array_vars(&args[0])?;
+                
+                if vars.is_empty() {
+                    return Ok(self.int_constant(0));
+                }
+                
+                // exists = OR of all elements
+                // Returns a boolean variable (0 or 1)
+                Ok(self.model.bool_or(&vars))
+            }
+            "forall" => {
+                if args.len() != 1 {
+                    return Err(Error::type_error(
+                        "1 argument",
+                        &format!("{} arguments", args.len()),
+                        span,
+                    ));
+                }
+                
+                // Get the array (should be boolean variables)
+                let vars = self.get_array_vars(&args[0])?;
+                
+                if vars.is_empty() {
+                    return Ok(self.int_constant(1));
+                }
+                
+                // forall = AND of all elements
+                // Returns a boolean variable (0 or 1)
+                Ok(self.model.bool_and(&vars))
+            }
+            _ => Err(Error::unsupported_feature(
+                &format!("Function '{}'", name),
+                "Phase 2",
+                span,
+            )),
+        }
+    }
+
+    /// Get an int array that may be decision variables or a parameter array,
+    /// turning parameter values into constants
+    fn get_array_vars_or_constants(&mut self, expr: &ast::Expr) -> Result<Vec<VarId>> {
+        match self.get_array_vars(expr) {
+            Ok(vars) => Ok(vars),
+            Err(_) => Ok(self
+                .eval_int_array(expr)?
+                .into_iter()
+                .map(|v| self.int_constant(v))
+                .collect()),
+        }
+    }
+
+    /// Get array variables from an expression (handles identifiers, literals,
+    /// comprehensions and `++` concatenations)
+    fn get_array_vars(&mut self, expr: &ast::Expr) -> Result<Vec<VarId>> {
+        match &expr.kind {
+            ast::ExprKind::Ident(array_name) => {
+                // Try each array type
+                if let Some(vars) = self.context.get_int_var_array(array_name) {
+                    return Ok(vars.clone());
+                }
+                if let Some(vars) = self.context.get_bool_var_array(array_name) {
+                    return Ok(vars.clone());
+                }
+                if let Some(vars) = self.context.get_float_var_array(array_name) {
+                    return Ok(vars.clone());
+                }
+                Err(Error::message(
+                    &format!("Undefined array variable: '{}'", array_name),
+                    expr.span,
+                ))
+            }
+            ast::ExprKind::ArrayComp { expr: body, generators } => {
+                // Materialize the comprehension into one variable per binding
+                let generators = Self::flatten_generators(generators);
+                let mut vars = Vec::new();
+                self.visit_generator_bindings(&generators, 0, body, &mut Vec::new(), &mut |t, body, guards| {
+                    if !guards.is_empty() {
+                        return Err(Error::unsupported_feature(
+                            "Array comprehensions over set variables",
+                            "Phase 4",
+                            body.span,
+                        ));
+                    }
+                    vars.push(if Self::is_relation(body) {
+                        t.expr_to_bool_var(body)?
+                    } else {
+                        t.get_var_or_value(body)?
+                    });
+                    Ok(())
+                })?;
+                Ok(vars)
+            }
+            ast::ExprKind::Call { name, args } if name == "row" || name == "col" => {
+                self.matrix_slice(name, args, expr.span)
+            }
+            ast::ExprKind::BinOp { op: ast::BinOp::Concat, left, right } => {
+                let mut vars = self.get_array_vars_or_constants(left)?;
+                vars.extend(self.get_array_vars_or_constants(right)?);
+                Ok(vars)
+            }
+            ast::ExprKind::ArrayLit(elements) => {
+                // Inline list of variables, constants or (reified) constraints
+                elements
+                    .iter()
+                    .map(|element| {
+                        if Self::is_relation(element) {
+                            self.expr_to_bool_var(element)
+                        } else {
+                            self.get_var_or_value(element)
+                        }
+                    })
+                    .collect()
+            }
+            _ => Err(Error::type_error(
+                "array identifier",
+                "other expression",
+                expr.span,
+            )),
+        }
+    }
+
+    /// Elements of one row (`row(m, i)`) or column (`col(m, j)`) of a 2D array
+    fn matrix_slice(&mut self, name: &str, args: &[ast::Expr], span: Span) -> Result<Vec<VarId>> {
+        if args.len() != 2 {
+            return Err(Error::type_error(
+                "2 arguments",
+                &format!("{} arguments", args.len()),
+                span,
+            ));
+        }
+        let ast::ExprKind::Ident(array_name) = &args[0].kind else {
+            return Err(Error::type_error("2D array identifier", "other expression", args[0].span));
+        };
+        let (rows, cols) = match self.context.array_metadata.get(array_name).map(|m| m.dimensions.as_slice()) {
+            Some(&[rows, cols]) => (rows, cols),
+            _ => {
+                return Err(Error::message(
+                    &format!("{}() needs a 2D array, but '{}' is not one", name, array_name),
+                    args[0].span,
+                ));
+            }
+        };
+        let index = self.eval_int_expr(&args[1])?;
+        let count = if name == "row" { rows } else { cols };
+        if index < 1 || index as usize > count {
+            return Err(Error::message(
+                &format!("{}({}, {}) is out of bounds 1..{}", name, array_name, index, count),
+                args[1].span,
+            ));
+        }
+        let flat = self.flat_array_as_vars(array_name).ok_or_else(|| {
+            Error::message(&format!("Undefined array variable: '{}'", array_name), args[0].span)
+        })?;
+        let k = index as usize - 1;
+        Ok(if name == "row" {
+            flat[k * cols..(k + 1) * cols].to_vec()
+        } else {
+            (0..rows).map(|r| flat[r * cols + k]).collect()
+        })
+    }
+
+    /// Whether `expr` is a comparison or logical connective, whose value only
+    /// exists as a reified boolean
+    fn is_relation(expr: &ast::Expr) -> bool {
+        match &expr.kind {
+            ast::ExprKind::BinOp { op, .. } => matches!(
+                op,
+                ast::BinOp::Lt
+                    | ast::BinOp::Le
+                    | ast::BinOp::Gt
+                    | ast::BinOp::Ge
+                    | ast::BinOp::Eq
+                    | ast::BinOp::Ne
+                    | ast::BinOp::And
+                    | ast::BinOp::Or
+                    | ast::BinOp::Impl
+                    | ast::BinOp::Iff
+                    | ast::BinOp::Xor
+                    | ast::BinOp::In
+            ),
+            ast::ExprKind::UnOp { op: ast::UnOp::Not, .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Element at a zero-based flattened position of any variable or parameter array
+    fn flat_array_element(&mut self, array_name: &str, flat: usize) -> Option<VarId> {
+        if let Some(&var) = self.context.get_int_var_array(array_name).and_then(|arr| arr.get(flat)) {
+            return Some(var);
+        }
+        if let Some(&var) = self.context.get_bool_var_array(array_name).and_then(|arr| arr.get(flat)) {
+            return Some(var);
+        }
+        if let Some(&var) = self.context.get_float_var_array(array_name).and_then(|arr| arr.get(flat)) {
+            return Some(var);
+        }
+        if let Some(&value) = self.context.get_int_param_array(array_name).and_then(|arr| arr.get(flat)) {
+            return Some(self.int_constant(value));
+        }
+        if let Some(&value) = self.context.get_bool_param_array(array_name).and_then(|arr| arr.get(flat)) {
+            return Some(self.int_constant(value as i32));
+        }
+        if let Some(&value) = self.context.get_float_param_array(array_name).and_then(|arr| arr.get(flat)) {
+            return Some(self.model.float(value, value));
+        }
+        None
+    }
+
+    /// All elements of a flattened variable or parameter array as variables,
+    /// with parameter values lifted into fixed variables
+    fn flat_array_as_vars(&mut self, array_name: &str) -> Option<Vec<VarId>> {
+        if let Some(arr) = self.context.get_int_var_array(array_name)
+            .or_else(|| self.context.get_bool_var_array(array_name))
+            .or_else(|| self.context.get_float_var_array(array_name))
+        {
+            return Some(arr.clone());
+        }
+        if let Some(values) = self.context.get_int_param_array(array_name).cloned() {
+            return Some(values.iter().map(|&v| self.int_constant(v)).collect());
+        }
+        if let Some(values) = self.context.get_bool_param_array(array_name).cloned() {
+            return Some(values.iter().map(|&v| self.int_constant(v as i32)).collect());
+        }
+        if let Some(values) = self.context.get_float_param_array(array_name).cloned() {
+            return Some(values.iter().map(|&v| self.model.float(v, v)).collect());
+        }
+        None
+    }
+
+    /// Zero-based flattened position of an array access with constant indices
+    fn const_flat_index(&self, array_name: &str, indices: &[ast::Expr]) -> Option<usize> {
+        let metadata = self.context.array_metadata.get(array_name);
+        let mut zero_based = Vec::with_capacity(indices.len());
+        for (dim, index) in indices.iter().enumerate() {
+            let value = self.eval_int_expr(index).ok()?;
+            let start = metadata.and_then(|m| m.starts.get(dim)).copied().unwrap_or(1);
+            if value < start {
+                return None;
+            }
+            zero_based.push((value - start) as usize);
+        }
+        match metadata {
+            Some(metadata) => metadata.flatten_indices(&zero_based, Span::dummy()).ok(),
+            None if zero_based.len() == 1 => Some(zero_based[0]),
+            None => None,
+        }
+    }
+
+    /// Integer variable equal to `then_expr` when `cond` holds and `else_expr` otherwise
+    fn if_then_else_var(
+        &mut self,
+        cond: &ast::Expr,
+        then_expr: &ast::Expr,
+        else_expr: &ast::Expr,
+        span: Span,
+    ) -> Result<VarId> {
+        if let Ok(holds) = self.eval_bool_expr(cond) {
+            return self.get_var_or_value(if holds { then_expr } else { else_expr });
+        }
+        let c = self.expr_to_bool_var(cond)?;
+        let a = self.get_var_or_value(then_expr)?;
+        let b = self.get_var_or_value(else_expr)?;
+        let (Some((a_lo, a_hi)), Some((b_lo, b_hi))) = (self.int_var_bounds(a), self.int_var_bounds(b)) else {
+            return Err(Error::unsupported_feature("Float if-then-else with a variable condition", "Phase 4", span));
+        };
+        let result = self.model.int(a_lo.min(b_lo), a_hi.max(b_hi));
+        let is_a = self.model.bool();
+        self.model.lin_eq_reif(&[1, -1], &[result, a], 0, is_a);
+        self.model.bool_clause(&[is_a], &[c]);
+        let is_b = self.model.bool();
+        self.model.lin_eq_reif(&[1, -1], &[result, b], 0, is_b);
+        self.model.bool_clause(&[c, is_b], &[]);
+        Ok(result)
+    }
+
+    /// Check `assert(cond, msg[, e])` or log `trace(msg[, e])` and yield `e`
+    ///
+    /// The forms without a wrapped expression stand for `true`. Returns `None`
+    /// for any other expression. A false assertion fails translation with its
+    /// message; trace messages are printed only with verbose output.
+    fn unwrap_assert_trace<'a>(&self, expr: &'a ast::Expr) -> Result<Option<Cow<'a, ast::Expr>>> {
+        let ast::ExprKind::Call { name, args } = &expr.kind else {
+            return Ok(None);
+        };
+        let (message, wrapped) = match (name.as_str(), args.as_slice()) {
+            ("assert", [cond, message, wrapped @ ..]) if wrapped.len() <= 1 => {
+                if !self.eval_bool_expr(cond)? {
+                    return Err(Error::message(
+                        &format!("Assertion failed: {}", Self::message_text(message)),
+                        expr.span,
+                    ));
+                }
+                (None, wrapped.first())
+            }
+            ("trace", [message, wrapped @ ..]) if wrapped.len() <= 1 => (Some(message), wrapped.first()),
+            ("assert" | "trace", _) => {
+                return Err(Error::type_error(
+                    if name == "assert" { "2 or 3 arguments" } else { "1 or 2 arguments" },
+                    &format!("{} arguments", args.len()),
+                    expr.span,
+                ));
+            }
+            _ => return Ok(None),
+        };
+        if let Some(message) = message
+            && self.options.verbose
+        {
+            eprintln!("{}", Self::message_text(message));
+        }
+        Ok(Some(match wrapped {
+            Some(wrapped) => Cow::Borrowed(wrapped),
+            None => Cow::Owned(ast::Expr { kind: ast::ExprKind::BoolLit(true), span: expr.span }),
+        }))
+    }
+
+    /// Text of an `assert`/`trace` message argument
+    fn message_text(message: &ast::Expr) -> String {
+        match &message.kind {
+            ast::ExprKind::StringLit(text) => text.clone(),
+            _ => format!("{:?}", message.kind),
+        }
+    }
+
+    /// Evaluate an integer expression to a compile-time constant
+    fn eval_int_expr(&self, expr: &ast::Expr) -> Result<i32> {
+        if let Some(inner) = self.unwrap_assert_trace(expr)? {
+            return self.eval_int_expr(&inner);
+        }
+        match &expr.kind {
+            ast::ExprKind::IntLit(i) => Ok(*i as i32),
+            // Booleans coerce to 0/1 in integer context
+            ast::ExprKind::BoolLit(b) => Ok(i32::from(*b)),
+            ast::ExprKind::IfThenElse { cond, then_expr, else_expr: Some(else_expr) } => {
+                self.eval_int_expr(if self.eval_bool_expr(cond)? { then_expr } else { else_expr })
+            }
+            ast::ExprKind::Ident(name) => {
+                if let Some(value) = self.context.get_int_param(name) {
+                    Ok(value)
+                } else if let Some(value) = self.context.get_bool_param(name) {
+                    Ok(i32::from(value))
+                } else {
+                    Err(Error::message(
+                        &format!("Undefined parameter: {}", name),
+                        expr.span,
+                    ))
+                }
+            }
+            ast::ExprKind::BinOp { op, left, right } => {
+                let left_val = self.eval_int_expr(left)?;
+                let right_val = self.eval_int_expr(right)?;
+                let overflow = || Error::message(
+                    &format!("Integer overflow evaluating {} {} {}", left_val, op, right_val),
+                    expr.span,
+                );
+                match op {
+                    ast::BinOp::Add => left_val.checked_add(right_val).ok_or_else(overflow),
+                    ast::BinOp::Sub => left_val.checked_sub(right_val).ok_or_else(overflow),
+                    ast::BinOp::Mul => left_val.checked_mul(right_val).ok_or_else(overflow),
+                    // MiniZinc truncates toward zero and `mod` takes the dividend's
+                    // sign, which is exactly Rust's `/` and `%`
+                    ast::BinOp::Div | ast::BinOp::Mod if right_val == 0 => Err(Error::message(
+                        &format!("Division by zero evaluating {} {} {}", left_val, op, right_val),
+                        expr.span,
+                    )),
+                    ast::BinOp::Div => left_val.checked_div(right_val).ok_or_else(overflow),
+                    ast::BinOp::Mod => left_val.checked_rem(right_val).ok_or_else(overflow),
+                    _ => Err(Error::message(
+                        &format!("Cannot evaluate operator {:?} at compile time", op),
+                        expr.span,
+                    )),
+                }
+            }
+            ast::ExprKind::UnOp { op, expr: inner } => {
+                let value = self.eval_int_expr(inner)?;
+                match op {
+                    ast::UnOp::Neg => Ok(-value),
+                    ast::UnOp::Not => Err(Error::message(
+                        "Cannot apply boolean NOT to integer",
+                        expr.span,
+                    )),
+                }
+            }
+            ast::ExprKind::ArrayAccess { array, indices } => {
+                // Constant access into an integer parameter array
+                if let ast::ExprKind::Ident(name) = &array.kind
+                    && let Some(values) = self.context.get_int_param_array(name)
+                    && let Some(flat) = self.const_flat_index(name, indices)
+                    && let Some(&value) = values.get(flat)
+                {
+                    return Ok(value);
+                }
+                Err(Error::message(
+                    "Cannot evaluate expression at compile time",
+                    expr.span,
+                ))
+            }
+            ast::ExprKind::Call { name, args } => self.eval_int_call(name, args, expr.span),
+            _ => Err(Error::message(
+                "Cannot evaluate expression at compile time",
+                expr.span,
+            )),
+        }
+    }
+
+    /// Evaluate `pow`, `abs`, `min`, `max`, `bool2int` and the float roundings
+    /// over constant arguments
+    fn eval_int_call(&self, name: &str, args: &[ast::Expr], span: ast::Span) -> Result<i32> {
+        match (name, args) {
+            ("bool2int", [b]) => Ok(i32::from(self.eval_bool_expr(b)?)),
+            ("floor" | "ceil" | "round", [x]) => {
+                let x = self.eval_float_expr(x)?;
+                let value = match name {
+                    "floor" => x.floor(),
+                    "ceil" => x.ceil(),
+                    _ => x.round(),
+                };
+                if value < i32::MIN as f64 || value > i32::MAX as f64 || value.is_nan() {
+                    return Err(Error::message(&format!("{}({}) is out of the integer range", name, x), span));
+                }
+                Ok(value as i32)
+            }
+            ("abs", [x]) => {
+                let x = self.eval_int_expr(x)?;
+                x.checked_abs().ok_or_else(|| Error::message("abs() overflows", span))
+            }
+            ("pow", [base, exp]) => {
+                let base = self.eval_int_expr(base)?;
+                let exp = self.eval_int_expr(exp)?;
+                let exp = u32::try_from(exp)
+                    .map_err(|_| Error::message(&format!("pow() with negative exponent {}", exp), span))?;
+                base.checked_pow(exp).ok_or_else(|| Error::message("pow() overflows", span))
+            }
+            ("min" | "max", [a, b]) => {
+                let a = self.eval_int_expr(a)?;
+                let b = self.eval_int_expr(b)?;
+                Ok(if name == "min" { a.min(b) } else { a.max(b) })
+            }
+            ("min" | "max", [array]) => {
+                let values = self.eval_int_array(array)?;
+                let result = if name == "min" { values.iter().min() } else { values.iter().max() };
+                result.copied().ok_or_else(|| Error::message(&format!("{}() of an empty array", name), span))
+            }
+            ("enum_next" | "enum_prev", _) => {
+                let (values, x) = self.enum_step_operands(args, span)?;
+                let pos = self.eval_int_expr(x)?;
+                let Some(value) = usize::try_from(pos).ok().and_then(|p| p.checked_sub(1)).and_then(|i| values.get(i))
+                else {
+                    return Err(Error::message(
+                        &format!("{}({}) is out of range: the enum has {} values", name, pos, values.len()),
+                        span,
+                    ));
+                };
+                let step = if name == "enum_next" { pos + 1 } else { pos - 1 };
+                if step < 1 || step as usize > values.len() {
+                    return Err(Error::message(
+                        &format!(
+                            "{}({}) is out of range: it is the {} value of its enum",
+                            name,
+                            value,
+                            if name == "enum_next" { "last" } else { "first" }
+                        ),
+                        span,
+                    ));
+                }
+                Ok(step)
+            }
+            _ => Err(Error::message(
+                &format!("Cannot evaluate call to '{}' at compile time", name),
+                span,
+            )),
+        }
+    }
+
+    /// Split `enum_next`/`enum_prev` arguments into the enum's values and the
+    /// operand. The enum is named by the optional first argument or inferred from
+    /// an enum-typed variable, array element or value.
+    fn enum_step_operands<'a>(
+        &self,
+        args: &'a [ast::Expr],
+        span: ast::Span,
+    ) -> Result<(&Vec<String>, &'a ast::Expr)> {
+        let (enum_name, x) = match args {
+            [ast::Expr { kind: ast::ExprKind::Ident(enum_name), .. }, x] => (Some(enum_name.as_str()), x),
+            [x] => (self.enum_of(x), x),
+            _ => {
+                return Err(Error::type_error(
+                    "1 or 2 arguments",
+                    &format!("{} arguments", args.len()),
+                    span,
+                ));
+            }
+        };
+        enum_name
+            .and_then(|enum_name| self.context.enums.get(enum_name))
+            .ok_or_else(|| Error::type_error("enum-typed expression", "other expression", x.span))
+            .map(|values| (values, x))
+    }
+
+    /// The enum type of an enum variable, enum array element or enum value
+    fn enum_of(&self, expr: &ast::Expr) -> Option<&str> {
+        let name = match &expr.kind {
+            ast::ExprKind::Ident(name) => name,
+            ast::ExprKind::ArrayAccess { array, .. } => match &array.kind {
+                ast::ExprKind::Ident(name) => name,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        if let Some((enum_name, _)) = self.enum_var_mapping.get(name) {
+            return Some(enum_name);
+        }
+        self.context
+            .enums
+            .iter()
+            .find(|(_, values)| values.contains(name))
+            .map(|(enum_name, _)| enum_name.as_str())
+    }
+
+    fn eval_float_expr(&self, expr: &ast::Expr) -> Result<f64> {
+        if let Some(inner) = self.unwrap_assert_trace(expr)? {
+            return self.eval_float_expr(&inner);
+        }
+        match &expr.kind {
+            ast::ExprKind::FloatLit(f) => Ok(*f),
+            ast::ExprKind::IntLit(i) => Ok(*i as f64),
+            ast::ExprKind::IfThenElse { cond, then_expr, else_expr: Some(else_expr) } => {
+                self.eval_float_expr(if self.eval_bool_expr(cond)? { then_expr } else { else_expr })
+            }
+            ast::ExprKind::Ident(name) => {
+                if let Some(value) = self.context.get_float_param(name) {
+                    Ok(value)
+                } else if let Some(value) = self.context.get_int_param(name) {
+                    Ok(value as f64)
+                } else {
+                    Err(Error::message(
+                        &format!("Undefined parameter: {}", name),
+                        expr.span,
+                    ))
+                }
+            }
+            ast::ExprKind::UnOp { op: ast::UnOp::Neg, expr: inner } => {
+                Ok(-self.eval_float_expr(inner)?)
+            }
+            ast::ExprKind::Call { name, args } if name == "int2float" && args.len() == 1 => {
+                Ok(self.eval_int_expr(&args[0])? as f64)
+            }
+            _ => Err(Error::message(
+                "Cannot evaluate float expression at compile time",
+                expr.span,
+            )),
+        }
+    }
+
+    fn eval_bool_expr(&self, expr: &ast::Expr) -> Result<bool> {
+        if let Some(inner) = self.unwrap_assert_trace(expr)? {
+            return self.eval_bool_expr(&inner);
+        }
+        match &expr.kind {
+            ast::ExprKind::BoolLit(b) => Ok(*b),
+            ast::ExprKind::IfThenElse { cond, then_expr, else_expr } => {
+                match (self.eval_bool_expr(cond)?, else_expr) {
+                    (true, _) => self.eval_bool_expr(then_expr),
+                    (false, Some(else_expr)) => self.eval_bool_expr(else_expr),
+                    (false, None) => Ok(true),
+                }
+            }
+            ast::ExprKind::Ident(name) => {
+                if let Some(value) = self.context.get_bool_param(name) {
+                    Ok(value)
+                } else {
+                    Err(Error::message(
+                        &format!("Undefined parameter: {}", name),
+                        expr.span,
+                    ))
+                }
+            }
+            ast::ExprKind::UnOp { op: ast::UnOp::Not, expr: inner } => Ok(!self.eval_bool_expr(inner)?),
+            ast::ExprKind::BinOp { op, left, right } => match op {
+                ast::BinOp::And => Ok(self.eval_bool_expr(left)? && self.eval_bool_expr(right)?),
+                ast::BinOp::Or => Ok(self.eval_bool_expr(left)? || self.eval_bool_expr(right)?),
+                ast::BinOp::Impl => Ok(!self.eval_bool_expr(left)? || self.eval_bool_expr(right)?),
+                ast::BinOp::Iff => Ok(self.eval_bool_expr(left)? == self.eval_bool_expr(right)?),
+                ast::BinOp::Xor => Ok(self.eval_bool_expr(left)? != self.eval_bool_expr(right)?),
+                ast::BinOp::Lt | ast::BinOp::Le | ast::BinOp::Gt |
+                ast::BinOp::Ge | ast::BinOp::Eq | ast::BinOp::Ne => {
+                    let ordering = match (self.eval_int_expr(left), self.eval_int_expr(right)) {
+                        (Ok(l), Ok(r)) => l.cmp(&r),
+                        _ => {
+                            let l = self.eval_float_expr(left)?;
+                            let r = self.eval_float_expr(right)?;
+                            l.partial_cmp(&r).ok_or_else(|| {
+                                Error::message("Cannot compare NaN at compile time", expr.span)
+                            })?
+                        }
+                    };
+                    Ok(match op {
+                        ast::BinOp::Lt => ordering.is_lt(),
+                        ast::BinOp::Le => ordering.is_le(),
+                        ast::BinOp::Gt => ordering.is_gt(),
+                        ast::BinOp::Ge => ordering.is_ge(),
+                        ast::BinOp::Eq => ordering.is_eq(),
+                        _ => ordering.is_ne(),
+                    })
+                }
+                ast::BinOp::In => {
+                    let value = self.eval_int_expr(left)?;
+                    Ok(self.eval_int_set(right)?.contains(&value))
+                }
+                _ => Err(Error::message(
+                    &format!("Cannot evaluate operator {:?} as a boolean at compile time", op),
+                    expr.span,
+                )),
+            },
+            _ => Err(Error::message(
+                "Cannot evaluate boolean expression at compile time",
+                expr.span,
+            )),
+        }
+    }
+
+    /// Evaluate a constant integer array given as a parameter array or literal
+    fn eval_int_array(&self, expr: &ast::Expr) -> Result<Vec<i32>> {
+        match &expr.kind {
+            ast::ExprKind::Ident(name) => self.context.get_int_param_array(name).map(|values| values.to_vec()).ok_or_else(|| {
+                Error::message(&format!("Undefined integer parameter array: {}", name), expr.span)
+            }),
+            ast::ExprKind::ArrayLit(elements) => elements.iter().map(|e| self.eval_int_expr(e)).collect(),
+            ast::ExprKind::BinOp { op: ast::BinOp::Concat, left, right } => {
+                let mut values = self.eval_int_array(left)?;
+                values.extend(self.eval_int_array(right)?);
+                Ok(values)
+            }
+            _ => Err(Error::type_error("integer parameter array", "other expression", expr.span)),
+        }
+    }
+
+    /// Evaluate a constant integer set given as a range or set literal
+    fn eval_int_set(&self, expr: &ast::Expr) -> Result<Vec<i32>> {
+        match &expr.kind {
+            ast::ExprKind::SetLit(elements) => {
+                let mut values = elements
+                    .iter()
+                    .map(|e| self.eval_int_expr(e))
+                    .collect::<Result<Vec<_>>>()?;
+                values.sort_unstable();
+                values.dedup();
+                Ok(values)
+            }
+            _ => {
+                let (start, end) = self.eval_int_domain(expr)?;
+                Ok((start..=end).collect())
+            }
+        }
+    }
+
+    fn eval_int_domain(&self, domain: &ast::Expr) -> Result<(i32, i32)> {
+        match &domain.kind {
+            ast::ExprKind::BinOp {
+                op: ast::BinOp::Range,
+                left,
+                right,
+            } => {
+                let min = self.eval_int_expr(left)?;
+                let max = self.eval_int_expr(right)?;
+                Ok((min, max))
+            }
+            _ => Err(Error::type_error(
+                "range expression",
+                "other expression",
+                domain.span,
+            )),
+        }
+    }
+
+    fn eval_float_domain(&self, domain: &ast::Expr) -> Result<(f64, f64)> {
+        match &domain.kind {
+            ast::ExprKind::BinOp {
+                op: ast::BinOp::Range,
+                left,
+                right,
+            } => {
+                let min = self.eval_float_expr(left)?;
+                let max = self.eval_float_expr(right)?;
+                Ok((min, max))
+            }
+            ast::ExprKind::Range(left, right) => {
+                // Handle Range variant as well
+                let min = self.eval_float_expr(left)?;
+                let max = self.eval_float_expr(right)?;
+                Ok((min, max))
+            }
+            _ => Err(Error::type_error(
+                "range expression",
+                "other expression",
+                domain.span,
+            )),
+        }
+    }
+
+    fn eval_index_set_size(&self, index_set: &ast::Expr) -> Result<usize> {
+        self.eval_index_set(index_set).map(|(_, size)| size)
+    }
+
+    /// First index and size of an index set such as `0..n-1`
+    fn eval_index_set(&self, index_set: &ast::Expr) -> Result<(i32, usize)> {
+        match &index_set.kind {
+            ast::ExprKind::BinOp {
+                op: ast::BinOp::Range,
+                left,
+                right,
+            } => {
+                let start = self.eval_int_expr(left)?;
+                let end = self.eval_int_expr(right)?;
+                // A reversed range such as 1..0 is empty
+                Ok((start, (i64::from(end) - i64::from(start) + 1).max(0) as usize))
+            }
+            _ => Err(Error::type_error(
+                "range expression",
+                "other expression",
+                index_set.span,
+            )),
+        }
+    }
+}
+
+impl Default for Translator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_translate_simple_param() {
+        let source = "int: n = 5;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_var_with_domain() {
+        let source = "var 1..10: x;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_var_array() {
+        let source = r#"
+            array[1..4] of var 1..4: queens;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_bool_var() {
+        let source = "var bool: flag;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        let translated = result.unwrap();
+        assert_eq!(translated.bool_vars.len(), 1);
+        assert!(translated.bool_vars.contains_key("flag"));
+    }
+
+    #[test]
+    fn test_translate_float_var() {
+        let source = "var 0.0..1.0: probability;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        let translated = result.unwrap();
+        assert_eq!(translated.float_vars.len(), 1);
+        assert!(translated.float_vars.contains_key("probability"));
+    }
+
+    #[test]
+    fn test_translate_bool_array() {
+        let source = "array[1..5] of var bool: flags;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        let translated = result.unwrap();
+        assert_eq!(translated.bool_var_arrays.len(), 1);
+        assert!(translated.bool_var_arrays.contains_key("flags"));
+        assert_eq!(translated.bool_var_arrays.get("flags").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_translate_float_array() {
+        let source = "array[1..3] of var 0.0..10.0: prices;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        let translated = result.unwrap();
+        assert_eq!(translated.float_var_arrays.len(), 1);
+        assert!(translated.float_var_arrays.contains_key("prices"));
+        assert_eq!(translated.float_var_arrays.get("prices").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_translate_bool_param() {
+        let source = "bool: enabled = true;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_float_param() {
+        let source = "float: pi = 3.14159;";
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_boolean_and_constraint() {
+        let source = r#"
+            var bool: a;
+            var bool: b;
+            constraint a /\ b;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_boolean_or_constraint() {
+        let source = r#"
+            var bool: x;
+            var bool: y;
+            constraint x \/ y;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_boolean_not_constraint() {
+        let source = r#"
+            var bool: flag;
+            constraint not flag;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_boolean_implication() {
+        let source = r#"
+            var bool: a;
+            var bool: b;
+            constraint a -> b;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_float_arithmetic() {
+        let source = r#"
+            var 0.0..10.0: x;
+            var 0.0..10.0: y;
+            constraint x + y <= 15.0;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_array_access() {
+        let source = r#"
+            array[1..5] of var 1..10: arr;
+            constraint arr[3] > 5;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate(&ast);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_translate_sum_aggregate() {
+        let source = r#"
+            array[1..3] of var 1..10: values;
+            constraint sum(values) == 15;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let solution = model_data.model.solve();
+        assert!(solution.is_ok());
+    }
+
+    #[test]
+    fn test_translate_min_aggregate() {
+        let source = r#"
+            array[1..3] of var 1..10: values;
+            constraint min(values) >= 5;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify the constraint: all values should be >= 5
+        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
+            let solution = sol.unwrap();
+            for var_id in values_arr {
+                let val = solution.get_int(*var_id);
+                assert!(val >= 5, "Expected all values >= 5, but got {}", val);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_max_aggregate() {
+        let source = r#"
+            array[1..3] of var 1..10: values;
+            constraint max(values) <= 7;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify the constraint: all values should be <= 7
+        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
+            let solution = sol.unwrap();
+            for var_id in values_arr {
+                let val = solution.get_int(*var_id);
+                assert!(val <= 7, "Expected all values <= 7, but got {}", val);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_product_aggregate() {
+        let source = r#"
+            array[1..3] of var 2..4: factors;
+            constraint product(factors) == 24;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify the constraint
+        if let Some(factors_arr) = model_data.int_var_arrays.get("factors") {
+            let solution = sol.unwrap();
+            let mut product = 1;
+            for var_id in factors_arr {
+                let val = solution.get_int(*var_id);
+                product *= val;
+            }
+            assert_eq!(product, 24, "Expected product == 24, but got {}", product);
+        }
+    }
+
+    #[test]
+    fn test_translate_minimize() {
+        let source = r#"
+            var 1..10: x;
+            constraint x >= 3;
+            solve minimize x;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        let model_data = result.unwrap();
+        assert_eq!(model_data.objective_type, ObjectiveType::Minimize);
+        assert!(model_data.objective_var.is_some());
+    }
+
+    #[test]
+    fn test_translate_maximize() {
+        let source = r#"
+            var 1..10: x;
+            constraint x <= 7;
+            solve maximize x;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        let model_data = result.unwrap();
+        assert_eq!(model_data.objective_type, ObjectiveType::Maximize);
+        assert!(model_data.objective_var.is_some());
+    }
+
+    #[test]
+    fn test_element_constraint_variable_index() {
+        let source = r#"
+            array[1..5] of var 1..10: values;
+            var 1..5: index;
+            var 1..10: result;
+            
+            % Element constraint: result == values[index]
+            constraint result == values[index];
+            constraint index == 3;
+            constraint result == 7;
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify: index should be 3, result should be 7, values[3] should be 7
+        let solution = sol.unwrap();
+        if let Some(&index_var) = model_data.int_vars.get("index") {
+            assert_eq!(solution.get_int(index_var), 3);
+        }
+        if let Some(&result_var) = model_data.int_vars.get("result") {
+            assert_eq!(solution.get_int(result_var), 7);
+        }
+        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
+            // values[3] (0-indexed: values[2]) should be 7
+            assert_eq!(solution.get_int(values_arr[2]), 7);
+        }
+    }
+
+    #[test]
+    fn test_element_constraint_in_expression() {
+        let source = r#"
+            array[1..4] of var 1..10: arr;
+            var 1..4: i;
+            
+            % Use element in a constraint expression
+            constraint arr[i] > 5;
+            constraint i == 2;
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify: arr[2] should be > 5
+        let solution = sol.unwrap();
+        if let Some(arr) = model_data.int_var_arrays.get("arr") {
+            let val_at_index_2 = solution.get_int(arr[1]); // 0-indexed
+            assert!(val_at_index_2 > 5, "Expected arr[2] > 5, got {}", val_at_index_2);
+        }
+    }
+
+    #[test]
+    fn test_count_aggregate() {
+        let source = r#"
+            array[1..5] of var 1..5: values;
+            var 0..5: count_result;
+            
+            % Count how many values equal 3
+            constraint count_result == count(values, 3);
+            
+            % Set some values to 3
+            constraint values[1] == 3;
+            constraint values[2] == 3;
+            constraint values[3] == 3;
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify: count_result should be 3
+        let solution = sol.unwrap();
+        if let Some(&count_var) = model_data.int_vars.get("count_result") {
+            assert_eq!(solution.get_int(count_var), 3);
+        }
+    }
+
+    #[test]
+    fn test_count_with_constraint() {
+        let source = r#"
+            array[1..4] of var 1..3: values;
+            
+            % At least 2 values must be equal to 2
+            constraint count(values, 2) >= 2;
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify: at least 2 values should be 2
+        let solution = sol.unwrap();
+        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
+            let count_2s: i32 = values_arr.iter()
+                .map(|&v| solution.get_int(v))
+                .filter(|&val| val == 2)
+                .count() as i32;
+            assert!(count_2s >= 2, "Expected at least 2 values equal to 2, got {}", count_2s);
+        }
+    }
+
+    #[test]
+    fn test_exists_aggregate() {
+        let source = r#"
+            array[1..4] of var bool: flags;
+            var bool: any_true;
+            
+            % at least one flag must be true
+            constraint any_true == exists(flags);
+            constraint any_true;  % Force it to be true
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify: at least one flag should be true
+        let solution = sol.unwrap();
+        if let Some(flags_arr) = model_data.bool_var_arrays.get("flags") {
+            let any_true = flags_arr.iter()
+                .map(|&v| solution.get_int(v))
+                .any(|val| val != 0);
+            assert!(any_true, "Expected at least one flag to be true");
+        }
+    }
+
+    #[test]
+    fn test_forall_aggregate() {
+        let source = r#"
+            array[1..4] of var bool: flags;
+            var bool: all_true;
+            
+            % all flags must be true
+            constraint all_true == forall(flags);
+            constraint all_true;  % Force it to be true
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok());
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        assert!(sol.is_ok());
+        
+        // Verify: all flags should be true
+        let solution = sol.unwrap();
+        if let Some(flags_arr) = model_data.bool_var_arrays.get("flags") {
+            let all_true = flags_arr.iter()
+                .map(|&v| solution.get_int(v))
+                .all(|val| val != 0);
+            assert!(all_true, "Expected all flags to be true");
+        }
+    }
+
+    #[test]
+    fn test_modulo_operator() {
+        // Test that modulo operator can be evaluated with constants
+        let source = r#"
+            var 1..20: x;
+            var 0..4: remainder;
+            
+            % Direct constraint with constants: check if 13 mod 5 = 3
+            constraint 13 mod 5 == 3;
+            constraint x == 13;
+            constraint remainder == 3;
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok(), "Failed to translate modulo expression");
+    }
+
+    #[test]
+    fn test_modulo_with_constraint() {
+        // Test modulo with variable divisor (the problematic case)
+        let source = r#"
+            var 1..100: dividend;
+            var 1..10: divisor;
+            var 0..9: remainder;
+            
+            constraint remainder == dividend mod divisor;
+            constraint dividend == 47;
+            constraint divisor == 10;
+            
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok(), "Failed to translate array with values");
+        
+        // Test that it solves correctly
+        let model_data = result.unwrap();
+        let sol = model_data.model.solve();
+        
+        if let Err(e) = sol {
+            eprintln!("FAILED TO SOLVE: {:?}", e);
+            eprintln!("This is the modulo with variable divisor issue!");
+            panic!("Model should solve but got: {:?}", e);
+        }
+        
+        let solution = sol.unwrap();
+        if let Some(dividend_var) = model_data.int_vars.get("dividend") {
+            let div_val = solution.get_int(*dividend_var);
+            assert_eq!(div_val, 47, "dividend should be 47");
+        }
+        
+        if let Some(divisor_var) = model_data.int_vars.get("divisor") {
+            let divisor_val = solution.get_int(*divisor_var);
+            assert_eq!(divisor_val, 10, "divisor should be 10");
+        }
+        
+        if let Some(remainder_var) = model_data.int_vars.get("remainder") {
+            let rem_val = solution.get_int(*remainder_var);
+            assert_eq!(rem_val, 7, "remainder should be 7 (47 mod 10 = 7)");
+        }
+    }
+
+    #[test]
+    fn test_array_initialization_int() {
+        // Test integer parameter array initialization
+        let source = r#"
+            array[1..3] of int: limits = [5, 10, 15];
+            array[1..3] of var 1..10: x;
+            
+            constraint x[1] <= limits[1];
+            constraint x[2] <= limits[2];
+            constraint x[3] <= limits[3];
+            
+            solve satisfy;
+        "#;
+
+        let ast = parse(source).unwrap();
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok(), "Failed to translate array initialization");
+        
+        let model_data = result.unwrap();
+        let solution = model_data.model.solve();
+        assert!(solution.is_ok(), "Failed to solve with parameter array");
+        
+        let sol = solution.unwrap();
+        if let Some(arr) = model_data.int_var_arrays.get("x") {
+            assert_eq!(arr.len(), 3, "Array should have 3 elements");
+            let x1 = sol.get_int(arr[0]);
+            let x2 = sol.get_int(arr[1]);
+            let x3 = sol.get_int(arr[2]);
+            
+            // Verify constraints were applied
+            assert!(x1 <= 5, "x[1] should be <= 5");
+            assert!(x2 <= 10, "x[2] should be <= 10");
+            assert!(x3 <= 15, "x[3] should be <= 15");
+        }
+    }
+
+    #[test]
+    fn test_array_initialization_float() {
+        // Test float parameter array initialization
+        let source = r#"
+            array[1..2] of float: thresholds = [1.5, 2.5];
+            array[1..2] of var 0.0..5.0: values;
+            
+            constraint values[1] <= thresholds[1];
+            constraint values[2] <= thresholds[2];
+            
+            solve satisfy;
+        "#;
+
+        let ast = parse(source).unwrap();
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok(), "Failed to translate float array initialization");
+        
+        let model_data = result.unwrap();
+        let solution = model_data.model.solve();
+        assert!(solution.is_ok(), "Failed to solve with float parameter array");
+        
+        let sol = solution.unwrap();
+        if let Some(arr) = model_data.float_var_arrays.get("values") {
+            assert_eq!(arr.len(), 2, "Array should have 2 elements");
+            let v1 = sol.get_float(arr[0]);
+            let v2 = sol.get_float(arr[1]);
+            
+            // Verify constraints were applied
+            assert!(v1 <= 1.6, "values[1] should be <= 1.5 (with small tolerance)");
+            assert!(v2 <= 2.6, "values[2] should be <= 2.5 (with small tolerance)");
+        }
+    }
+
+    #[test]
+    fn test_array_initialization_bool() {
+        // Test bool parameter array initialization
+        let source = r#"
+            array[1..2] of bool: flags = [true, false];
+            array[1..2] of var bool: enabled;
+            
+            solve satisfy;
+        "#;
+
+        let ast = parse(source).unwrap();
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok(), "Failed to translate bool array initialization");
+        
+        let model_data = result.unwrap();
+        let solution = model_data.model.solve();
+        assert!(solution.is_ok(), "Failed to solve with bool parameter array");
+    }
+
+    #[test]
+    fn test_array_initialization_in_arithmetic() {
+        // Test using parameter array elements in arithmetic expressions
+        let source = r#"
+            array[1..2] of int: costs = [10, 20];
+            array[1..2] of var 0..1: select;
+            
+            constraint costs[1] * select[1] + costs[2] * select[2] <= 25;
+            
+            solve maximize select[1] + select[2];
+        "#;
+
+        let ast = parse(source).unwrap();
+        let result = Translator::translate_with_vars(&ast);
+        assert!(result.is_ok(), "Failed to translate array in arithmetic");
+        
+        let model_data = result.unwrap();
+        let solution = model_data.model.solve();
+        assert!(solution.is_ok(), "Failed to solve with array in arithmetic");
+        
+        let sol = solution.unwrap();
+        if let Some(arr) = model_data.int_var_arrays.get("select") {
+            assert_eq!(arr.len(), 2, "Array should have 2 elements");
+            let s1 = sol.get_int(arr[0]);
+            let s2 = sol.get_int(arr[1]);
+            
+            // Verify constraint: 10*s1 + 20*s2 <= 25
+            let total_cost = 10 * s1 + 20 * s2;
+            assert!(total_cost <= 25, "Cost constraint should be satisfied");
+        }
+    }
+
+    #[test]
+    fn test_elementwise_reified_equality() {
+        // eq[i] must reflect whether a[i] and b[i] match
+        let source = r#"
+            int: n = 4;
+            array[1..n] of var 1..5: a;
+            array[1..n] of var 1..5: b;
+            array[1..n] of var bool: eq;
+
+            constraint forall(i in 1..n)(eq[i] <-> (a[i] == b[i]));
+            constraint a[1] == 1;
+            constraint a[2] == 2;
+            constraint a[3] == 3;
+            constraint a[4] == 4;
+            constraint b[1] == 1;
+            constraint b[2] == 5;
+            constraint b[3] == 3;
+            constraint b[4] == 2;
+
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let eq = model_data.bool_var_arrays.get("eq").unwrap().clone();
+        let solution = model_data.model.solve().unwrap();
+
+        let values: Vec<i32> = eq.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(values, vec![1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_fzn_prefixed_all_different() {
+        let source = r#"
+            array[1..3] of var 1..3: x;
+            constraint fzn_all_different_int(x);
+            constraint x[1] == 2;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays.get("x").unwrap().clone();
+        let solution = model_data.model.solve().unwrap();
+
+        let mut values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(values[0], 2);
+        values.sort();
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_constant_index_out_of_bounds() {
+        for (index, source) in [
+            (4, "array[1..3] of var 1..9: arr; constraint arr[4] > 1; solve satisfy;"),
+            (0, "array[1..3] of var 1..9: arr; constraint arr[0] > 1; solve satisfy;"),
+        ] {
+            let ast = parse(source).unwrap();
+            let err = Translator::translate_with_vars(&ast).err().expect("index should be rejected");
+            let msg = format!("{}", err);
+            assert!(
+                msg.contains(&format!("Array index {} out of bounds for 'arr' (size 3)", index)),
+                "unexpected message: {}", msg
+            );
+            // The span points at the index expression, not the start of the file
+            assert!(source[err.span.start..].starts_with(&index.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_constant_index_out_of_bounds_2d() {
+        let source = r#"
+            array[1..2, 1..3] of var 1..9: grid;
+            constraint grid[1, 4] > 1;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let err = Translator::translate_with_vars(&ast).err().expect("index should be rejected");
+        assert!(format!("{}", err).contains("Array index 4 out of bounds for dimension 2 of 'grid' (size 3)"));
+    }
+
+    #[test]
+    fn test_sum_over_set_var_members() {
+        let source = r#"
+            array[1..4] of int: w = [3, 5, 2, 7];
+            var set of 1..4: s;
+            var 0..100: total;
+            constraint total = sum(i in s)(w[i]);
+            constraint total >= 12;
+            constraint total <= 12;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let total = model_data.int_vars["total"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(total), 12);
+
+        // No subset of the weights sums beyond 17
+        let source = r#"
+            array[1..4] of int: w = [3, 5, 2, 7];
+            var set of 1..4: s;
+            var 0..100: total;
+            constraint total = sum(i in s)(w[i]);
+            constraint total >= 18;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert!(model_data.model.solve().is_err());
+    }
+
+    #[test]
+    fn test_negative_domain_bounds() {
+        let source = r#"
+            var -10..-3: x;
+            var -2.5..-0.5: f;
+            solve minimize x;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_vars["x"];
+        let f = model_data.float_vars["f"];
+        let solution = crate::optimize(&ast, crate::SolverConfig::default()).unwrap().unwrap();
+        // The optimum sits on the most negative bound
+        assert_eq!(solution.get_int(x), -10);
+        let f_val = solution.get_float(f);
+        assert!((-2.5..=-0.5).contains(&f_val));
+    }
+
+    #[test]
+    fn test_negative_comparison_constants() {
+        let source = r#"
+            var -10..10: x;
+            var -10..10: y;
+            constraint x > -5;
+            constraint x < -3;
+            constraint -2 = y;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_vars["x"];
+        let y = model_data.int_vars["y"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(x), -4);
+        assert_eq!(solution.get_int(y), -2);
+    }
+
+    #[test]
+    fn test_negated_variable_expression() {
+        let source = r#"
+            var 1..5: x;
+            var -5..-1: y;
+            constraint y = -x;
+            constraint x = 3;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let y = model_data.int_vars["y"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(y), -3);
+    }
+
+    #[test]
+    fn test_reified_element_equality() {
+        for (index, expected) in [(2, true), (3, false)] {
+            let source = format!(r#"
+                array[1..4] of int: arr = [4, 7, 1, 7];
+                var 1..4: i;
+                var bool: b;
+                constraint b <-> (arr[i] == 7);
+                constraint i = {};
+                solve satisfy;
+            "#, index);
+            let ast = parse(&source).unwrap();
+            let model_data = Translator::translate_with_vars(&ast).unwrap();
+            let b = model_data.bool_vars["b"];
+            let solution = model_data.model.solve().unwrap();
+            assert_eq!(solution.get_int(b) == 1, expected, "index {}", index);
+        }
+
+        // Forcing b also restricts the index to matching positions
+        let source = r#"
+            array[1..4] of var 0..9: arr;
+            var 1..4: i;
+            var bool: b;
+            constraint forall(k in 1..4)(arr[k] = k);
+            constraint b <-> (arr[i] == 3);
+            constraint b;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let i = model_data.int_vars["i"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(i), 3);
+    }
+
+    #[test]
+    fn test_linear_objective_uses_single_variable() {
+        let source = r#"
+            var 0..10: x;
+            var 0..10: y;
+            constraint x + y >= 4;
+            solve minimize 2*x + y*3 - 1;
+        "#;
+        let ast = parse(source).unwrap();
+        let objective = match ast.items.last() {
+            Some(ast::Item::Solve(ast::Solve::Minimize { expr, .. })) => expr.clone(),
+            _ => panic!("expected minimize"),
+        };
+
+        let mut translator = Translator::new();
+        for item in &ast.items[..2] {
+            translator.translate_item(item).unwrap();
+        }
+
+        let before = translator.model.variable_count();
+        translator.get_var_or_value(&objective).unwrap();
+        let naive = translator.model.variable_count() - before;
+
+        let before = translator.model.variable_count();
+        translator.translate_objective(&objective).unwrap();
+        let optimized = translator.model.variable_count() - before;
+
+        // One sum variable plus one for the constant offset
+        assert_eq!(optimized, 2);
+        assert!(optimized < naive, "optimized {} vs naive {}", optimized, naive);
+
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_vars["x"];
+        let y = model_data.int_vars["y"];
+        let objective_var = model_data.objective_var.unwrap();
+        let solution = model_data.model.minimize(objective_var).unwrap();
+        assert_eq!(solution.get_int(x), 4);
+        assert_eq!(solution.get_int(y), 0);
+        assert_eq!(solution.get_int(objective_var), 7);
+    }
+
+    #[test]
+    fn test_repeated_parameter_shares_constant_var() {
+        let source = r#"
+            int: limit = 7;
+            array[1..20] of var 0..20: x;
+            constraint forall(i in 1..20)(x[i] + limit >= limit);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let mut translator = Translator::new();
+        for item in &ast.items {
+            translator.translate_item(item).unwrap();
+        }
+        // `limit` is referenced 40 times but backed by a single fixed variable
+        assert_eq!(translator.context.int_constants.len(), 1);
+        assert!(translator.context.int_constants.contains_key(&7));
+    }
+
+    #[test]
+    fn test_forall_linear_rows_batched() {
+        let batched = r#"
+            array[1..3, 1..3] of int: a = array2d(1..3, 1..3, [1, 2, 0, 0, 1, 3, 2, 0, 1]);
+            array[1..3] of int: b = [8, 12, 7];
+            array[1..3] of var 0..10: x;
+            constraint forall(i in 1..3)(sum(j in 1..3)(a[i, j] * x[j]) = b[i]);
+            solve satisfy;
+        "#;
+        let unrolled = r#"
+            array[1..3, 1..3] of int: a = array2d(1..3, 1..3, [1, 2, 0, 0, 1, 3, 2, 0, 1]);
+            array[1..3] of int: b = [8, 12, 7];
+            array[1..3] of var 0..10: x;
+            constraint sum(j in 1..3)(a[1, j] * x[j]) = b[1];
+            constraint sum(j in 1..3)(a[2, j] * x[j]) = b[2];
+            constraint sum(j in 1..3)(a[3, j] * x[j]) = b[3];
+            solve satisfy;
+        "#;
+        // Each row summed into its own variable first, as the rows were posted
+        // before they were batched
+        let staged = r#"
+            array[1..3, 1..3] of int: a = array2d(1..3, 1..3, [1, 2, 0, 0, 1, 3, 2, 0, 1]);
+            array[1..3] of int: b = [8, 12, 7];
+            array[1..3] of var 0..10: x;
+            var int: s1 = sum(j in 1..3)(a[1, j] * x[j]);
+            var int: s2 = sum(j in 1..3)(a[2, j] * x[j]);
+            var int: s3 = sum(j in 1..3)(a[3, j] * x[j]);
+            constraint s1 = b[1] /\ s2 = b[2] /\ s3 = b[3];
+            solve satisfy;
+        "#;
+
+        let batched_model = Translator::translate_with_vars(&parse(batched).unwrap()).unwrap();
+        let unrolled_model = Translator::translate_with_vars(&parse(unrolled).unwrap()).unwrap();
+        let staged_model = Translator::translate_with_vars(&parse(staged).unwrap()).unwrap();
+        assert!(
+            batched_model.model.constraint_count() < staged_model.model.constraint_count(),
+            "batched {} vs staged {}",
+            batched_model.model.constraint_count(),
+            staged_model.model.constraint_count()
+        );
+        assert!(batched_model.model.variable_count() < staged_model.model.variable_count());
+
+        // Top-level comparisons take the same linear path, so both post one row per
+        // constraint without intermediate product or sum variables
+        assert_eq!(
+            batched_model.model.pending_constraint_asts.len(),
+            unrolled_model.model.pending_constraint_asts.len()
+        );
+        assert_eq!(batched_model.model.variable_count(), unrolled_model.model.variable_count());
+        assert_eq!(batched_model.model.variable_count(), 3);
+
+        // x = [2, 3, 3] is the unique solution of a * x = b
+        let x = batched_model.int_var_arrays["x"].clone();
+        let solution = batched_model.model.solve().unwrap();
+        let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(values, vec![2, 3, 3]);
+    }
+
+    #[test]
+    fn test_partition_set_into_two_sets() {
+        let source = r#"
+            array[1..2] of var set of 1..6: s;
+            var 0..100: total2;
+            constraint partition_set(s, 1..6);
+            constraint sum(i in s[1])(1) = 3;
+            constraint sum(i in s[2])(1) = 3;
+            constraint sum(i in s[1])(i) = 6;
+            constraint total2 = sum(i in s[2])(i);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let total2 = model_data.int_vars["total2"];
+        let solution = model_data.model.solve().unwrap();
+        // s[1] = {1, 2, 3} forces s[2] = {4, 5, 6}
+        assert_eq!(solution.get_int(total2), 15);
+
+        // Six elements cannot be split into sets of 3 and 4
+        let source = r#"
+            array[1..2] of var set of 1..6: s;
+            constraint partition_set(s, 1..6);
+            constraint sum(i in s[1])(1) = 3;
+            constraint sum(i in s[2])(1) = 4;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert!(model_data.model.solve().is_err());
+    }
+
+    #[test]
+    fn test_forall_multi_name_generator() {
+        let source = r#"
+            array[1..3] of var 1..3: x;
+            array[1..3] of var 1..3: y;
+            constraint forall(i, j in 1..3)(x[i] + y[j] <= 4);
+            constraint x[1] = 3;
+            solve maximize sum(j in 1..3)(y[j]);
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let y = model_data.int_var_arrays["y"].clone();
+        let objective = model_data.objective_var.unwrap();
+        let solution = model_data.model.maximize(objective).unwrap();
+        // x[1] = 3 is paired with every y[j], so all y[j] are limited to 1
+        for &v in &y {
+            assert_eq!(solution.get_int(v), 1);
+        }
+    }
+
+    #[test]
+    fn test_forall_where_clause_triangular() {
+        let source = r#"
+            array[1..4] of var 1..4: x;
+            constraint forall(i in 1..4, j in 1..4 where i < j)(x[i] < x[j]);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        // Posting the i >= j pairs too would make the model infeasible
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solution = model_data.model.solve().unwrap();
+        let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(values, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_multi_name_generator_with_where() {
+        let source = r#"
+            array[1..3] of var 1..3: x;
+            constraint forall(i, j in 1..3 where i < j)(x[i] != x[j]);
+            constraint x[1] = 2;
+            constraint x[2] = 3;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(x[2]), 1);
+    }
+
+    #[test]
+    fn test_sum_generator_where_clause() {
+        let source = r#"
+            array[1..5] of var 0..1: x;
+            var 0..10: evens;
+            constraint forall(i in 1..5)(x[i] = 1);
+            constraint evens = sum(i in 1..5 where i mod 2 = 0)(x[i]);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let evens = model_data.int_vars["evens"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(evens), 2);
+    }
+
+    #[test]
+    fn test_binary_max_clamping() {
+        let source = r#"
+            int: k = 4;
+            var 0..10: x;
+            constraint x >= 6;
+            solve minimize max(0, x - k);
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_vars["x"];
+        let objective = model_data.objective_var.unwrap();
+        // The objective tracks the clamped difference for every feasible x
+        let mut best = None;
+        for solution in model_data.model.enumerate() {
+            let (x_val, obj_val) = (solution.get_int(x), solution.get_int(objective));
+            assert_eq!(obj_val, (x_val - 4).max(0));
+            if best.is_none_or(|(_, b)| obj_val < b) {
+                best = Some((x_val, obj_val));
+            }
+        }
+        assert_eq!(best, Some((6, 2)));
+
+        let source = r#"
+            var 0..10: x;
+            var 0..10: excess;
+            var 0..10: floor;
+            constraint excess = max(0, x - 4);
+            constraint floor = min(x, 3);
+            constraint x = 7;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let excess = model_data.int_vars["excess"];
+        let floor = model_data.int_vars["floor"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(excess), 3);
+        assert_eq!(solution.get_int(floor), 3);
+
+        // The clamp never goes below zero
+        let source = r#"
+            var 0..10: x;
+            var -10..10: excess;
+            constraint excess = max(0, x - 4);
+            constraint x <= 2;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let excess = model_data.int_vars["excess"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(excess), 0);
+    }
+
+    #[test]
+    fn test_redundant_permutation_sum() {
+        let source = r#"
+            array[1..4] of var 1..4: perm;
+            constraint alldifferent(perm);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let plain = Translator::translate_with_vars(&ast).unwrap();
+        let options = TranslatorOptions::default().with_redundant_permutation_sums(true);
+        let redundant = Translator::translate_with_vars_and_options(&ast, options.clone()).unwrap();
+        // Linear constraints stay pending until search starts
+        let posted = |m: &Model| m.constraint_count() + m.pending_constraint_asts.len();
+        assert_eq!(posted(&redundant.model), posted(&plain.model) + 1);
+
+        // The implied sum removes no permutations
+        assert_eq!(plain.model.enumerate().count(), 24);
+        assert_eq!(redundant.model.enumerate().count(), 24);
+
+        // Arrays that are not a full permutation of their domain are left alone
+        let source = r#"
+            array[1..3] of var 1..5: x;
+            constraint alldifferent(x);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let plain = Translator::translate_with_vars(&ast).unwrap();
+        let redundant = Translator::translate_with_vars_and_options(&ast, options).unwrap();
+        assert_eq!(posted(&redundant.model), posted(&plain.model));
+    }
+
+    #[test]
+    fn test_exists_generator_constraint() {
+        let source = r#"
+            int: n = 4;
+            array[1..n] of var 1..9: a;
+            constraint exists(i in 1..n)(a[i] = 5);
+            constraint forall(i in 1..3)(a[i] != 5);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let a = model_data.int_var_arrays["a"].clone();
+        let solution = model_data.model.solve().unwrap();
+        // The only remaining position must supply the witness
+        assert_eq!(solution.get_int(a[3]), 5);
+
+        // Every solution has at least one 5
+        let source = r#"
+            array[1..2] of var 4..5: a;
+            constraint exists(i in 1..2)(a[i] = 5);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let a = model_data.int_var_arrays["a"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert_eq!(solutions.len(), 3);
+        for solution in &solutions {
+            assert!(a.iter().any(|&v| solution.get_int(v) == 5));
         }
     }
 
-    /// Translate aggregate function calls (sum, min, max, etc.)
-    fn translate_aggregate_call(&mut self, name: &str, args: &[ast::Expr], span: ast::Span) -> Result<VarId> {
-        match name {
-            "sum" => {
-                if args.len() != 1 {
-                    return Err(Error::type_error(
-                        "1 argument",
-                        &format!("{} arguments", args.len()),
-                        span,
-                    ));
-                }
-                
-                // Get the array
-                let vars = self.get_array_vars(&args[0])?;
-                Ok(self.model.sum(&vars))
-            }
-            "min" => {
-                if args.len() != 1 {
-                    return Err(Error::type_error(
-                        "1 argument",
-                        &format!("{} arguments", args.len()),
-                        span,
-                    ));
-                }
-                
-                let vars = self.get_array_vars(&args[0])?;
-                self.model.min(&vars).map_err(|e| Error::message(
-                    &format!("min() requires at least one variable: {:?}", e),
-                    span,
-                ))
-            }
-            "max" => {
-                if args.len() != 1 {
-                    return Err(Error::type_error(
-                        "1 argument",
-                        &format!("{} arguments", args.len()),
-                        span,
-                    ));
-                }
-                
-                let vars = self.get_array_vars(&args[0])?;
-                self.model.max(&vars).map_err(|e| Error::message(
-                    &format!("max() requires at least one variable: {:?}", e),
-                    span,
-                ))
-            }
-            "product" => {
-                if args.len() != 1 {
-                    return Err(Error::type_error(
-                        "1 argument",
-                        &format!("{} arguments", args.len()),
-                        span,
-                    ));
-                }
-                
-                // Product doesn't have a built-in Selen function for arrays
-                // We need to multiply all elements together
-                let vars = self.get_array_vars(&args[0])?;
-                if vars.is_empty() {
-                    return Err(Error::message("product() requires at least one variable", span));
-                }
-                
-                // Start with the first variable and multiply the rest
-                let mut result = vars[0];
-                for &var in &vars[1..] {
-                    result = self.model.mul(result, var);
-                }
-                Ok(result)
-            }
-            "count" => {
-                if args.len() != 2 {
-                    return Err(Error::type_error(
-                        "2 arguments",
-                        &format!("{} arguments", args.len()),
-                        span,
-                    ));
+    #[test]
+    fn test_aggregates_on_both_sides() {
+        // sum on both sides of an inequality
+        let source = r#"
+            array[1..3] of var 0..3: a;
+            array[1..2] of var 0..2: b;
+            constraint sum(a) <= sum(b);
+            constraint a[1] = 3;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let a = model_data.int_var_arrays["a"].clone();
+        let b = model_data.int_var_arrays["b"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            let sum_a: i32 = a.iter().map(|&v| solution.get_int(v)).sum();
+            let sum_b: i32 = b.iter().map(|&v| solution.get_int(v)).sum();
+            assert!(sum_a <= sum_b);
+        }
+
+        // max of one array equal to min of another
+        let source = r#"
+            array[1..2] of var 1..4: a;
+            array[1..2] of var 1..4: b;
+            constraint max(a) = min(b);
+            constraint a[1] = 3;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let a = model_data.int_var_arrays["a"].clone();
+        let b = model_data.int_var_arrays["b"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            let max_a = a.iter().map(|&v| solution.get_int(v)).max().unwrap();
+            let min_b = b.iter().map(|&v| solution.get_int(v)).min().unwrap();
+            assert_eq!(max_a, min_b);
+        }
+
+        // product on both sides
+        let source = r#"
+            array[1..2] of var 1..6: a;
+            array[1..2] of var 1..6: b;
+            constraint product(a) = product(b);
+            constraint a[1] = 2 /\ a[2] = 6 /\ b[1] = 3;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let b = model_data.int_var_arrays["b"].clone();
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(b[1]), 4);
+
+        // Generator forms behave the same as the array forms
+        let source = r#"
+            array[1..2] of var 1..6: a;
+            array[1..2] of var 1..6: b;
+            constraint product(i in 1..2)(a[i]) = product(i in 1..2)(b[i]);
+            constraint min(i in 1..2)(a[i]) < min(i in 1..2)(b[i]);
+            constraint max(i in 1..2)(b[i]) < max(i in 1..2)(a[i]);
+            constraint a[1] = 1 /\ a[2] = 4 /\ b[1] = 2;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let b = model_data.int_var_arrays["b"].clone();
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(b[1]), 2);
+
+        // Operands fixed during search (tied maxima) must not lose solutions
+        let source = r#"
+            array[1..2] of var 1..6: a;
+            array[1..2] of var 1..6: b;
+            constraint max(b) < max(a);
+            constraint a[1] = 1 /\ a[2] = 4 /\ b[1] = 2;
+            constraint b[2] = 2;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert!(model_data.model.solve().is_ok());
+    }
+
+    #[test]
+    fn test_forall_fixes_array_from_parameters() {
+        let source = r#"
+            int: n = 4;
+            array[1..n] of int: p = [3, 1, 4, 2];
+            array[1..n] of var 1..n: x;
+            constraint forall(i in 1..n)(x[i] = p[i]);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let mut translator = Translator::new();
+        for item in &ast.items {
+            translator.translate_item(item).unwrap();
+        }
+        // Each equality is posted against the literal value, not a lifted constant
+        assert!(translator.context.int_constants.is_empty());
+
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solution = model_data.model.solve().unwrap();
+        let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(values, vec![3, 1, 4, 2]);
+    }
+
+    #[test]
+    fn test_all_equal() {
+        let source = r#"
+            array[1..4] of var 1..9: x;
+            constraint all_equal(x);
+            constraint x[2] >= 7;
+            constraint x[4] != 9;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert_eq!(solutions.len(), 2);
+        for solution in &solutions {
+            let first = solution.get_int(x[0]);
+            assert!(x.iter().all(|&v| solution.get_int(v) == first));
+        }
+
+        // Singleton arrays are trivially all equal
+        let source = r#"
+            array[1..1] of var 1..3: y;
+            constraint all_equal(y);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert_eq!(model_data.model.enumerate().count(), 3);
+    }
+
+    #[test]
+    fn test_no_overlap_intervals() {
+        let source = r#"
+            array[1..3] of int: dur = [3, 2, 4];
+            array[1..3] of var 0..9: s;
+            constraint no_overlap(s, dur);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let s = model_data.int_var_arrays["s"].clone();
+        let dur = [3, 2, 4];
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            for i in 0..3 {
+                for j in (i + 1)..3 {
+                    let (si, sj) = (solution.get_int(s[i]), solution.get_int(s[j]));
+                    assert!(si + dur[i] <= sj || sj + dur[j] <= si);
                 }
-                
-                // Get the array
-                let vars = self.get_array_vars(&args[0])?;
-                
-                // Get the value to count
-                let value = self.get_var_or_value(&args[1])?;
-                
-                // Create a result variable for the count (0 to array length)
-                let count_result = self.model.int(0, vars.len() as i32);
-                
-                // Call Selen's count_var constraint (supports both constant and variable values)
-                self.model.count(&vars, value, count_result);
-                
-                Ok(count_result)
             }
-            "exists" => {
-                if args.len() != 1 {
-                    return Err(Error::type_error(
-                        "1 argument",
-                        &format!("{} arguments", args.len()),
-                        span,
-                    ));
-                }
-                
-                // Get the array (should be boolean variables)
-                let vars = self.get_array_vars(&args[0])?;
-                
-                if vars.is_empty() {
-                    return Err(Error::message("exists() requires at least one variable", span));
+        }
+    }
+
+    #[test]
+    fn test_assignment_cost_weighted_sum() {
+        let source = r#"
+            int: n = 3;
+            array[1..n, 1..n] of int: cost = array2d(1..n, 1..n, [4, 2, 8, 4, 3, 7, 3, 1, 6]);
+            array[1..n, 1..n] of var 0..1: assign;
+            var 0..100: total = sum(i in 1..n, j in 1..n)(assign[i, j] * cost[i, j]);
+            constraint forall(i in 1..n)(sum(j in 1..n)(assign[i, j]) = 1);
+            constraint forall(j in 1..n)(sum(i in 1..n)(assign[i, j]) = 1);
+            solve minimize total;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let total = model_data.int_vars["total"];
+        let assign = model_data.int_var_arrays["assign"].clone();
+        let objective = model_data.objective_var.unwrap();
+        let solution = model_data.model.minimize(objective).unwrap();
+
+        // Cheapest assignment costs 12; the total must match the chosen cells
+        let cost = [4, 2, 8, 4, 3, 7, 3, 1, 6];
+        let chosen: i32 = assign.iter().zip(cost).map(|(&v, c)| solution.get_int(v) * c).sum();
+        assert_eq!(solution.get_int(total), 12);
+        assert_eq!(chosen, 12);
+    }
+
+    #[test]
+    fn test_disjunctive_single_machine() {
+        let source = r#"
+            array[1..3] of var 0..6: start;
+            array[1..3] of var 1..3: dur;
+            constraint dur[1] = 3 /\ dur[2] = 2 /\ dur[3] = 2;
+            constraint disjunctive(start, dur);
+            constraint start[2] = 0;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let start = model_data.int_var_arrays["start"].clone();
+        let dur = model_data.int_var_arrays["dur"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            for i in 0..3 {
+                for j in (i + 1)..3 {
+                    let (si, sj) = (solution.get_int(start[i]), solution.get_int(start[j]));
+                    let (di, dj) = (solution.get_int(dur[i]), solution.get_int(dur[j]));
+                    assert!(si + di <= sj || sj + dj <= si, "tasks {} and {} overlap", i + 1, j + 1);
                 }
-                
-                // exists = OR of all elements
-                // Returns a boolean variable (0 or 1)
-                Ok(self.model.bool_or(&vars))
             }
-            "forall" => {
-                if args.len() != 1 {
-                    return Err(Error::type_error(
-                        "1 argument",
-                        &format!("{} arguments", args.len()),
-                        span,
-                    ));
-                }
-                
-                // Get the array (should be boolean variables)
-                let vars = self.get_array_vars(&args[0])?;
-                
-                if vars.is_empty() {
-                    return Err(Error::message("forall() requires at least one variable", span));
-                }
-                
-                // forall = AND of all elements
-                // Returns a boolean variable (0 or 1)
-                Ok(self.model.bool_and(&vars))
+        }
+    }
+
+    #[test]
+    fn test_disjoint_sets() {
+        let source = r#"
+            var set of 1..3: a;
+            var set of 2..4: b;
+            constraint disjoint(a, b);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        // 1 and 4 are free; 2 and 3 each have three options (a, b, or neither)
+        assert_eq!(model_data.model.enumerate().count(), 4 * 9);
+    }
+
+    #[test]
+    fn test_strictly_increasing_with_fixed_duplicate() {
+        let source = r#"
+            array[1..4] of var 1..9: x;
+            constraint x[2] = 5;
+            constraint x[3] = 5;
+            constraint strictly_increasing(x);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert!(matches!(
+            model_data.model.solve(),
+            Err(selen::core::SolverError::NoSolution { .. })
+        ));
+
+        // The non-strict ordering accepts the duplicate
+        let source = source.replace("strictly_increasing", "increasing");
+        let ast = parse(&source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solution = model_data.model.solve().unwrap();
+        let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+
+        // Decreasing orders mirror the increasing ones
+        let source = r#"
+            array[1..3] of var 1..3: y;
+            constraint strictly_decreasing(y);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let y = model_data.int_var_arrays["y"].clone();
+        let solution = model_data.model.solve().unwrap();
+        let values: Vec<i32> = y.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(values, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_bin_packing() {
+        let source = r#"
+            array[1..4] of int: weight = [4, 3, 3, 2];
+            array[1..4] of var 1..2: bin;
+            constraint bin_packing(6, bin, weight);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let bin = model_data.int_var_arrays["bin"].clone();
+        let weight = [4, 3, 3, 2];
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Only {4, 2} / {3, 3} fits, in either bin order
+        assert_eq!(solutions.len(), 2);
+        for solution in &solutions {
+            for b in 1..=2 {
+                let load: i32 = (0..4).filter(|&i| solution.get_int(bin[i]) == b).map(|i| weight[i]).sum();
+                assert!(load <= 6);
+            }
+        }
+
+        // The load variant exposes each bin's total weight
+        let source = r#"
+            array[1..4] of int: weight = [4, 3, 3, 2];
+            array[1..4] of var int: bin;
+            array[1..2] of var 0..6: load;
+            constraint bin_packing_load(load, bin, weight);
+            constraint bin[1] = 1;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let bin = model_data.int_var_arrays["bin"].clone();
+        let load = model_data.int_var_arrays["load"].clone();
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(load[0]), 6);
+        assert_eq!(solution.get_int(load[1]), 6);
+        assert_eq!(solution.get_int(bin[3]), 1);
+    }
+
+    #[test]
+    fn test_counts_in_linear_expression() {
+        let source = r#"
+            int: k = 5;
+            array[1..4] of var 1..3: x;
+            constraint 2 * count(x, 1) + count(x, 2) == k;
+            constraint x[1] = 3;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Two ones and a two fill the three free positions
+        assert_eq!(solutions.len(), 3);
+        for solution in &solutions {
+            let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+            let ones = values.iter().filter(|&&v| v == 1).count() as i32;
+            let twos = values.iter().filter(|&&v| v == 2).count() as i32;
+            assert_eq!(2 * ones + twos, 5);
+        }
+    }
+
+    #[test]
+    fn test_forall_off_diagonal_pairs() {
+        let source = r#"
+            int: n = 3;
+            array[1..n, 1..n] of var 0..1: m;
+            constraint forall(i, j in 1..n where i != j)(m[i, j] = 0);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        // One constraint per ordered pair with i != j
+        assert_eq!(model_data.model.constraint_count(), 6);
+
+        let m = model_data.int_var_arrays["m"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Only the diagonal stays free
+        assert_eq!(solutions.len(), 8);
+        for solution in &solutions {
+            for i in 0..3 {
+                for j in 0..3 {
+                    if i != j {
+                        assert_eq!(solution.get_int(m[i * 3 + j]), 0);
+                    }
+                }
             }
-            _ => Err(Error::unsupported_feature(
-                &format!("Function '{}'", name),
-                "Phase 2",
-                span,
-            )),
         }
     }
 
-    /// Get array variables from an expression (handles identifiers and literals)
-    fn get_array_vars(&mut self, expr: &ast::Expr) -> Result<Vec<VarId>> {
-        match &expr.kind {
-            ast::ExprKind::Ident(array_name) => {
-                // Try each array type
-                if let Some(vars) = self.context.get_int_var_array(array_name) {
-                    return Ok(vars.clone());
-                }
-                if let Some(vars) = self.context.get_bool_var_array(array_name) {
-                    return Ok(vars.clone());
-                }
-                if let Some(vars) = self.context.get_float_var_array(array_name) {
-                    return Ok(vars.clone());
-                }
-                Err(Error::message(
-                    &format!("Undefined array variable: '{}'", array_name),
-                    expr.span,
-                ))
-            }
-            _ => Err(Error::type_error(
-                "array identifier",
-                "other expression",
-                expr.span,
-            )),
+    #[test]
+    fn test_div_mod_negative_operands() {
+        // Compile-time evaluation
+        let source = r#"
+            int: q1 = (-7) div 2;
+            int: r1 = (-7) mod 2;
+            int: q2 = 7 div (-2);
+            int: r2 = 7 mod (-2);
+            int: q3 = (-7) div (-2);
+            int: r3 = (-7) mod (-2);
+        "#;
+        let ast = parse(source).unwrap();
+        let mut translator = Translator::new();
+        for item in &ast.items {
+            translator.translate_item(item).unwrap();
+        }
+        let param = |name: &str| translator.context.get_int_param(name).unwrap();
+        assert_eq!((param("q1"), param("r1")), (-3, -1));
+        assert_eq!((param("q2"), param("r2")), (-3, 1));
+        assert_eq!((param("q3"), param("r3")), (3, -1));
+
+        // Runtime constraints agree with the constant evaluator
+        for (a, b, q, r) in [(-7, 2, -3, -1), (7, -2, -3, 1), (-7, -2, 3, -1), (7, 2, 3, 1)] {
+            let source = format!(
+                r#"
+                var -10..10: a;
+                var -10..10: b;
+                var -10..10: q;
+                var -10..10: r;
+                constraint a = {a};
+                constraint b = {b};
+                constraint q = a div b;
+                constraint r = a mod b;
+                solve satisfy;
+                "#
+            );
+            let ast = parse(&source).unwrap();
+            let model_data = Translator::translate_with_vars(&ast).unwrap();
+            let (q_var, r_var) = (model_data.int_vars["q"], model_data.int_vars["r"]);
+            let solution = model_data.model.solve().unwrap();
+            assert_eq!((solution.get_int(q_var), solution.get_int(r_var)), (q, r), "{} div/mod {}", a, b);
         }
     }
 
-    /// Evaluate an integer expression to a compile-time constant
-    fn eval_int_expr(&self, expr: &ast::Expr) -> Result<i32> {
-        match &expr.kind {
-            ast::ExprKind::IntLit(i) => Ok(*i as i32),
-            ast::ExprKind::Ident(name) => {
-                if let Some(value) = self.context.get_int_param(name) {
-                    Ok(value)
-                } else {
-                    Err(Error::message(
-                        &format!("Undefined parameter: {}", name),
-                        expr.span,
-                    ))
-                }
+    #[test]
+    fn test_div_mod_variable_operands_enumerate() {
+        // Every (a, b) pair with b != 0 appears once, with truncated q and r
+        let source = r#"
+            var -7..7: a;
+            var -3..3: b;
+            var -10..10: q;
+            var -10..10: r;
+            constraint q = a div b;
+            constraint r = a mod b;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let vars = ["a", "b", "q", "r"].map(|name| model_data.int_vars[name]);
+        let mut solutions: Vec<[i32; 4]> = model_data
+            .model
+            .enumerate()
+            .map(|s| vars.map(|v| s.get_int(v)))
+            .collect();
+        solutions.sort();
+        let mut expected: Vec<[i32; 4]> = (-7..=7)
+            .flat_map(|a| (-3..=3).filter(|&b| b != 0).map(move |b| [a, b, a / b, a % b]))
+            .collect();
+        expected.sort();
+        assert_eq!(solutions, expected);
+
+        // A variable dividend over a constant divisor keeps every value
+        let source = r#"
+            var -10..10: a;
+            constraint a mod 3 = 0;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let a = model_data.int_vars["a"];
+        let mut values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(a)).collect();
+        values.sort();
+        assert_eq!(values, vec![-9, -6, -3, 0, 3, 6, 9]);
+
+        let source = r#"
+            var 1..10: a;
+            constraint a div 3 = 2;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let a = model_data.int_vars["a"];
+        let mut values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(a)).collect();
+        values.sort();
+        assert_eq!(values, vec![6, 7, 8]);
+    }
+
+    #[test]
+    fn test_bool_equality_chain() {
+        let source = r#"
+            var bool: a;
+            var bool: b;
+            var bool: c;
+            constraint a = b /\ b = c;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let vars = [model_data.bool_vars["a"], model_data.bool_vars["b"], model_data.bool_vars["c"]];
+        let solutions: Vec<Vec<i32>> = model_data
+            .model
+            .enumerate()
+            .map(|s| vars.iter().map(|&v| s.get_int(v)).collect())
+            .collect();
+        assert_eq!(solutions.len(), 2);
+        for values in &solutions {
+            assert!(values.iter().all(|&v| v == values[0]));
+        }
+
+        // Fixing the end of the chain propagates to the start
+        let source = r#"
+            var bool: a;
+            var bool: b;
+            var bool: c;
+            constraint a = b /\ b = c;
+            constraint c = true;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let a = model_data.bool_vars["a"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(a), 1);
+    }
+
+    #[test]
+    fn test_constant_functions_in_index_sets() {
+        let source = r#"
+            int: k = 3;
+            int: lo = -2;
+            array[1..pow(2, k)] of var 0..1: bits;
+            array[1..abs(lo)] of var 0..1: pair;
+            array[1..max(k, 5)] of var 0..1: wide;
+            int: smallest = min([4, abs(lo), 7]);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert_eq!(model_data.int_var_arrays["bits"].len(), 8);
+        assert_eq!(model_data.int_var_arrays["pair"].len(), 2);
+        assert_eq!(model_data.int_var_arrays["wide"].len(), 5);
+
+        let mut translator = Translator::new();
+        for item in &ast.items {
+            translator.translate_item(item).unwrap();
+        }
+        assert_eq!(translator.context.get_int_param("smallest"), Some(2));
+
+        // Negative exponents are rejected rather than truncated
+        let ast = parse("int: bad = pow(2, -1);").unwrap();
+        assert!(Translator::translate_with_vars(&ast).is_err());
+    }
+
+    #[test]
+    fn test_float_sum_links_to_target() {
+        let source = r#"
+            array[1..3] of var 0.0..1.0: x;
+            var 0.0..3.0: total;
+            constraint total = sum(x);
+            constraint total = 2.5;
+            constraint x[1] = 0.5;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.float_var_arrays["x"].clone();
+        let total = model_data.float_vars["total"];
+        let solution = model_data.model.solve().unwrap();
+        let sum: f64 = x.iter().map(|&v| solution.get_float(v)).sum();
+        assert!((solution.get_float(total) - 2.5).abs() < 1e-4);
+        assert!((sum - 2.5).abs() < 1e-4, "sum of x is {}", sum);
+        assert!((solution.get_float(x[0]) - 0.5).abs() < 1e-4);
+
+        // The aggregate itself is a float variable
+        let mut translator = Translator::new();
+        for item in &ast.items {
+            if matches!(item, ast::Item::VarDecl(_)) {
+                translator.translate_item(item).unwrap();
             }
-            ast::ExprKind::BinOp { op, left, right } => {
-                let left_val = self.eval_int_expr(left)?;
-                let right_val = self.eval_int_expr(right)?;
-                match op {
-                    ast::BinOp::Add => Ok(left_val + right_val),
-                    ast::BinOp::Sub => Ok(left_val - right_val),
-                    ast::BinOp::Mul => Ok(left_val * right_val),
-                    ast::BinOp::Div => Ok(left_val / right_val),
-                    ast::BinOp::Mod => Ok(left_val % right_val),
-                    _ => Err(Error::message(
-                        &format!("Cannot evaluate operator {:?} at compile time", op),
-                        expr.span,
-                    )),
-                }
+        }
+        let sum_expr = parse("constraint sum(x) = 0.0;").unwrap();
+        let ast::Item::Constraint(constraint) = &sum_expr.items[0] else { unreachable!() };
+        let ast::ExprKind::BinOp { left, .. } = &constraint.expr.kind else { unreachable!() };
+        let sum_var = translator.get_var_or_value(left).unwrap();
+        assert!(translator.int_var_bounds(sum_var).is_none());
+    }
+
+    #[test]
+    fn test_translate_from_file_with_include() {
+        let dir = std::env::temp_dir().join(format!("zelen_include_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("data")).unwrap();
+        std::fs::write(dir.join("data/params.mzn"), "int: n = 4;\n").unwrap();
+        std::fs::write(
+            dir.join("model.mzn"),
+            r#"
+            include "globals.mzn";
+            include "data/params.mzn";
+            array[1..n] of var 1..n: x;
+            constraint alldifferent(x);
+            constraint x[1] = n;
+            solve satisfy;
+            "#,
+        )
+        .unwrap();
+
+        let model_data = Translator::translate_from_file(dir.join("model.mzn")).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        assert_eq!(x.len(), 4);
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(x[0]), 4);
+
+        // Mutually including files are reported instead of recursing forever
+        std::fs::write(dir.join("a.mzn"), "include \"b.mzn\";\n").unwrap();
+        std::fs::write(dir.join("b.mzn"), "include \"a.mzn\";\n").unwrap();
+        match Translator::translate_from_file(dir.join("a.mzn")) {
+            Err(err) => {
+                let message = format!("{}", err);
+                assert!(message.contains("Include cycle"), "{}", message);
+                // Reported at the directive that closes the cycle, in b.mzn
+                assert!(message.contains("b.mzn at line 1, column 1"), "{}", message);
             }
-            ast::ExprKind::UnOp { op, expr: inner } => {
-                let value = self.eval_int_expr(inner)?;
-                match op {
-                    ast::UnOp::Neg => Ok(-value),
-                    ast::UnOp::Not => Err(Error::message(
-                        "Cannot apply boolean NOT to integer",
-                        expr.span,
-                    )),
-                }
+            Ok(_) => panic!("expected an include cycle error"),
+        }
+
+        // Parse errors in an included file name it and point into it
+        std::fs::write(dir.join("broken.mzn"), "int: k = 3;\nvar 1..: y;\n").unwrap();
+        std::fs::write(dir.join("uses_broken.mzn"), "include \"broken.mzn\";\nsolve satisfy;\n").unwrap();
+        match Translator::translate_from_file(dir.join("uses_broken.mzn")) {
+            Err(err) => {
+                let message = format!("{}", err);
+                assert!(message.contains("broken.mzn at line 2"), "{}", message);
+                assert!(message.contains("var 1..: y;"), "{}", message);
             }
-            _ => Err(Error::message(
-                "Cannot evaluate expression at compile time",
-                expr.span,
-            )),
+            Ok(_) => panic!("expected a parse error in the included file"),
+        }
+
+        // A model parsed from combined text gets its includes expanded in place
+        let combined = format!("{}\nconstraint x[2] = 1;", std::fs::read_to_string(dir.join("model.mzn")).unwrap());
+        let expanded = Translator::expand_includes(parse(&combined).unwrap(), dir.join("model.mzn")).unwrap();
+        assert!(!expanded.items.iter().any(|item| matches!(item, ast::Item::Include(_))));
+        let model_data = Translator::translate_with_vars(&expanded).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!((solution.get_int(x[0]), solution.get_int(x[1])), (4, 1));
+
+        // A missing include that is not a library file is not silently dropped
+        std::fs::write(dir.join("typo.mzn"), "include \"paramz.mzn\";\nvar 1..2: x;\n").unwrap();
+        match Translator::translate_from_file(dir.join("typo.mzn")) {
+            Err(err) => assert!(format!("{}", err).contains("paramz.mzn' included from"), "{}", err),
+            Ok(_) => panic!("expected a missing include error"),
         }
+        let Err(err) = Translator::translate_with_vars(&parse("include \"paramz.mzn\";").unwrap()) else {
+            panic!("expected an include error without a file location");
+        };
+        assert!(err.to_string().contains("Cannot include 'paramz.mzn'"), "{}", err);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    fn eval_float_expr(&self, expr: &ast::Expr) -> Result<f64> {
-        match &expr.kind {
-            ast::ExprKind::FloatLit(f) => Ok(*f),
-            ast::ExprKind::IntLit(i) => Ok(*i as f64),
-            ast::ExprKind::Ident(name) => {
-                if let Some(value) = self.context.get_float_param(name) {
-                    Ok(value)
-                } else if let Some(value) = self.context.get_int_param(name) {
-                    Ok(value as f64)
-                } else {
-                    Err(Error::message(
-                        &format!("Undefined parameter: {}", name),
-                        expr.span,
-                    ))
-                }
+    #[test]
+    fn test_translation_errors_point_at_offending_line() {
+        fn error_line(source: &str) -> usize {
+            let ast = parse(source).unwrap();
+            match Translator::translate_with_vars(&ast) {
+                Err(err) => err.with_source(source.to_string()).location().0,
+                Ok(_) => panic!("expected a translation error"),
             }
-            _ => Err(Error::message(
-                "Cannot evaluate float expression at compile time",
-                expr.span,
-            )),
+        }
+
+        // Wrong arity for a global constraint
+        let source = "array[1..3] of var 1..3: x;\n\
+                      var 1..3: y;\n\
+                      constraint alldifferent(x, y);\n\
+                      solve satisfy;\n";
+        assert_eq!(error_line(source), 3);
+
+        // Undefined variable inside a constraint
+        let source = "var 1..3: x;\n\
+                      constraint x < 3;\n\
+                      constraint x != z;\n\
+                      solve satisfy;\n";
+        assert_eq!(error_line(source), 3);
+
+        // Unknown constraint predicate
+        let source = "var 1..3: x;\n\
+                      var 1..3: y;\n\
+                      constraint x < y;\n\
+                      constraint frobnicate(x, y);\n\
+                      solve satisfy;\n";
+        assert_eq!(error_line(source), 4);
+
+        // Array declarations report their own line
+        let source = "int: n = 3;\n\
+                      array[1..n] of int: costs;\n\
+                      solve satisfy;\n";
+        assert_eq!(error_line(source), 2);
+
+        // The convenience builder attaches the source itself
+        let source = "var 1..3: x;\nconstraint alldifferent(x, x);\n";
+        match crate::build_model(source) {
+            Err(err) => assert_eq!(err.location().0, 2),
+            Ok(_) => panic!("expected a translation error"),
         }
     }
 
-    fn eval_bool_expr(&self, expr: &ast::Expr) -> Result<bool> {
-        match &expr.kind {
-            ast::ExprKind::BoolLit(b) => Ok(*b),
-            ast::ExprKind::Ident(name) => {
-                if let Some(value) = self.context.get_bool_param(name) {
-                    Ok(value)
-                } else {
-                    Err(Error::message(
-                        &format!("Undefined parameter: {}", name),
-                        expr.span,
-                    ))
-                }
-            }
-            _ => Err(Error::message(
-                "Cannot evaluate boolean expression at compile time",
-                expr.span,
-            )),
+    #[test]
+    fn test_element_respects_index_domain() {
+        let source = r#"
+            array[1..10] of int: w = [10, 20, 30, 40, 50, 60, 70, 80, 90, 100];
+            array[1..10] of var 1..10: a;
+            var 2..4: i;
+            var int: v;
+            var int: u;
+            constraint forall(k in 1..10)(a[k] = k);
+            constraint v = w[i];
+            constraint u = a[i];
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+
+        let mut translator = Translator::new();
+        for item in &ast.items[..4] {
+            translator.translate_item(item).unwrap();
         }
+        // Only positions 2..4 are reachable, so the element result is bounded by them
+        let w_i = match &ast.items[6] {
+            ast::Item::Constraint(c) => match &c.expr.kind {
+                ast::ExprKind::BinOp { right, .. } => (**right).clone(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        let result = translator.get_var_or_value(&w_i).unwrap();
+        assert_eq!(translator.int_var_bounds(result), Some((20, 40)));
+
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let v = model_data.int_vars["v"];
+        let u = model_data.int_vars["u"];
+        let mut values: Vec<(i32, i32)> = model_data.model.enumerate()
+            .map(|s| (s.get_int(v), s.get_int(u)))
+            .collect();
+        values.sort();
+        assert_eq!(values, vec![(20, 2), (30, 3), (40, 4)]);
     }
 
-    fn eval_int_domain(&self, domain: &ast::Expr) -> Result<(i32, i32)> {
-        match &domain.kind {
-            ast::ExprKind::BinOp {
-                op: ast::BinOp::Range,
-                left,
-                right,
-            } => {
-                let min = self.eval_int_expr(left)?;
-                let max = self.eval_int_expr(right)?;
-                Ok((min, max))
-            }
-            _ => Err(Error::type_error(
-                "range expression",
-                "other expression",
-                domain.span,
-            )),
+    #[test]
+    fn test_all_equal_over_column_comprehension() {
+        let source = r#"
+            array[1..3, 1..2] of var 1..2: x;
+            constraint all_equal([x[i, 1] | i in 1..3]);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // The first column collapses to one of 2 values; the second stays free (2^3)
+        assert_eq!(solutions.len(), 16);
+        for solution in &solutions {
+            let column: Vec<i32> = (0..3).map(|i| solution.get_int(x[i * 2])).collect();
+            assert!(column.iter().all(|&v| v == column[0]));
         }
     }
 
-    fn eval_float_domain(&self, domain: &ast::Expr) -> Result<(f64, f64)> {
-        match &domain.kind {
-            ast::ExprKind::BinOp {
-                op: ast::BinOp::Range,
-                left,
-                right,
-            } => {
-                let min = self.eval_float_expr(left)?;
-                let max = self.eval_float_expr(right)?;
-                Ok((min, max))
-            }
-            ast::ExprKind::Range(left, right) => {
-                // Handle Range variant as well
-                let min = self.eval_float_expr(left)?;
-                let max = self.eval_float_expr(right)?;
-                Ok((min, max))
+    #[test]
+    fn test_set_var_fixed_cardinality_subset() {
+        let source = r#"
+            var set of 1..5: s;
+            constraint card(s) = 3;
+            constraint s subset 1..4;
+            constraint 2 in s;
+            constraint not (4 in s);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let members = model_data.set_vars["s"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(TranslatedModel::format_set(&members, &solutions[0]), "{1, 2, 3}");
+    }
+
+    #[test]
+    fn test_set_union_intersect_diff() {
+        let source = r#"
+            var set of 1..4: a;
+            var set of 1..4: b;
+            var set of 1..4: u;
+            var set of 1..4: both;
+            var 0..4: n;
+            var 1..4: x;
+            constraint a = {1, 2};
+            constraint b = {2, 3};
+            constraint u = a union b;
+            constraint both = a intersect b;
+            constraint n = card(a union b);
+            constraint x in a diff b;
+            solve satisfy;
+            output ["u = ", show(u), ", both = ", show(both)];
+        "#;
+        let ast = parse(source).unwrap();
+        let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+        let n = model_data.int_vars["n"];
+        let x = model_data.int_vars["x"];
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(solution.get_int(n), 3);
+        assert_eq!(solution.get_int(x), 1);
+        assert_eq!(
+            model_data.format_output(&solution).unwrap().trim(),
+            "u = {1, 2, 3}, both = {2}"
+        );
+    }
+
+    #[test]
+    fn test_product_mod_avoids_overflow() {
+        // 1001 * 1002 * 1003 * 1004 * 1005 is about 1e15, far beyond i32
+        let source = r#"
+            array[1..5] of var 1000..2000: a;
+            constraint forall(i in 1..5)(a[i] = 1000 + i);
+            var 0..22: r;
+            var 0..22: g;
+            constraint r = product(a) mod 23;
+            constraint g = product(i in 1..5)(a[i]) mod 23;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let r = model_data.int_vars["r"];
+        let g = model_data.int_vars["g"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(r), 13);
+        assert_eq!(solution.get_int(g), 13);
+
+        // Wide factor domains, where every reduction has a large quotient
+        let source = r#"
+            array[1..3] of var 1..100000: x;
+            var 0..6: r;
+            constraint forall(i in 1..3)(x[i] = 99999);
+            constraint r = product(x) mod 7;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let r = model_data.int_vars["r"];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(r), 1);
+
+        // Without the modulus the overflow is reported instead of wrapped
+        let source = r#"
+            array[1..3] of var 1000..2000: a;
+            var int: p;
+            constraint p = product(a);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        match Translator::translate_with_vars(&ast) {
+            Err(err) => assert!(format!("{}", err).contains("Integer overflow")),
+            Ok(_) => panic!("expected an overflow error"),
+        }
+    }
+
+    #[test]
+    fn test_search_heuristics_recorded() {
+        let source = r#"
+            array[1..4] of var 1..4: q;
+            constraint alldifferent(q);
+            solve :: int_search(q, first_fail, indomain_min) satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert_eq!(model_data.search_heuristics.len(), 1);
+        let heuristic = &model_data.search_heuristics[0];
+        assert_eq!(heuristic.var_select, ast::VarSelection::FirstFail);
+        assert_eq!(heuristic.val_select, ast::ValueSelection::IndomainMin);
+        assert_eq!(heuristic.vars, model_data.int_var_arrays["q"]);
+        assert!(model_data.model.solve().is_ok());
+
+        // Free search drops the annotations
+        let options = TranslatorOptions::default().with_free_search(true);
+        let model_data = Translator::translate_with_vars_and_options(&ast, options).unwrap();
+        assert!(model_data.search_heuristics.is_empty());
+    }
+
+    #[test]
+    fn test_value_precede_chain_canonical_order() {
+        let source = r#"
+            array[1..4] of var 1..3: x;
+            constraint value_precede_chain([1, 2, 3], x);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Restricted growth strings of length 4 over 3 values: S(4,1) + S(4,2) + S(4,3)
+        assert_eq!(solutions.len(), 1 + 7 + 6);
+        for solution in &solutions {
+            // Each value first appears only after all smaller values have appeared
+            let mut max_seen = 0;
+            for &var in &x {
+                let value = solution.get_int(var);
+                assert!(value <= max_seen + 1, "value {} appears before {}", value, max_seen + 1);
+                max_seen = max_seen.max(value);
             }
-            _ => Err(Error::type_error(
-                "range expression",
-                "other expression",
-                domain.span,
-            )),
         }
+
+        let source = r#"
+            array[1..3] of var 1..2: y;
+            constraint value_precede(2, 1, y);
+            constraint y[2] = 1;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let y = model_data.int_var_arrays["y"].clone();
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(y[0]), 2);
     }
 
-    fn eval_index_set_size(&self, index_set: &ast::Expr) -> Result<usize> {
-        match &index_set.kind {
-            ast::ExprKind::BinOp {
-                op: ast::BinOp::Range,
-                left,
-                right,
-            } => {
-                let start = self.eval_int_expr(left)?;
-                let end = self.eval_int_expr(right)?;
-                Ok((end - start + 1) as usize)
-            }
-            _ => Err(Error::type_error(
-                "range expression",
-                "other expression",
-                index_set.span,
-            )),
+    #[test]
+    fn test_assert_and_trace() {
+        let source = r#"
+            int: n = assert(4 > 0, "n must be positive", 4);
+            array[1..n] of var 1..n: x;
+            constraint assert(n >= 2, "need two queens");
+            constraint trace("posting order", x[1] < x[2]);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        assert_eq!(x.len(), 4);
+        let solution = model_data.model.solve().unwrap();
+        assert!(solution.get_int(x[0]) < solution.get_int(x[1]));
+
+        let source = r#"
+            int: n = 0;
+            constraint assert(n > 0, "n must be positive");
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        match Translator::translate_with_vars(&ast) {
+            Err(err) => assert!(format!("{}", err).contains("n must be positive"), "{}", err),
+            Ok(_) => panic!("expected the assertion to fail"),
         }
     }
-}
 
-impl Default for Translator {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn test_sort_and_arg_sort() {
+        let source = r#"
+            array[1..3] of var 1..3: x;
+            array[1..3] of var 1..3: y;
+            array[1..3] of var 1..3: p;
+            constraint x[1] = 3 /\ x[2] = 1 /\ x[3] = 2;
+            constraint sort(x, y);
+            constraint arg_sort(x, p);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let y = model_data.int_var_arrays["y"].clone();
+        let p = model_data.int_var_arrays["p"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert_eq!(solutions.len(), 1);
+        let values = |vars: &[VarId]| vars.iter().map(|&v| solutions[0].get_int(v)).collect::<Vec<_>>();
+        assert_eq!(values(&y), vec![1, 2, 3]);
+        assert_eq!(values(&p), vec![2, 3, 1]);
+
+        // Ties are ordered by position, so the permutation stays unique
+        let source = r#"
+            array[1..3] of var 1..3: x;
+            array[1..3] of var 1..3: p;
+            constraint x[1] = 2 /\ x[2] = 1 /\ x[3] = 2;
+            constraint arg_sort(x, p);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let p = model_data.int_var_arrays["p"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(p.iter().map(|&v| solutions[0].get_int(v)).collect::<Vec<_>>(), vec![2, 1, 3]);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::parse;
 
     #[test]
-    fn test_translate_simple_param() {
-        let source = "int: n = 5;";
+    fn test_constraint_if_then_else() {
+        let count = |source: &str| {
+            let ast = parse(source).unwrap();
+            Translator::translate_with_vars(&ast).unwrap().model.enumerate().count()
+        };
+
+        // Without else only `x = 1 -> y = 2` is posted: 1 + 2 * 3 solutions
+        assert_eq!(
+            count("var 1..3: x; var 1..3: y; constraint if x = 1 then y = 2 endif; solve satisfy;"),
+            7
+        );
+        // With else, y is determined by x
+        assert_eq!(
+            count("var 1..3: x; var 1..3: y; constraint if x = 1 then y = 2 else y = 3 endif; solve satisfy;"),
+            3
+        );
+
+        // A variable condition also selects between integer branches
+        let source = r#"
+            var 1..3: x;
+            var int: z = if x > 1 then 10 else 20 endif;
+            constraint x = 1;
+            solve satisfy;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let z = model_data.int_vars["z"];
+        assert_eq!(model_data.model.solve().unwrap().get_int(z), 20);
     }
 
     #[test]
-    fn test_translate_var_with_domain() {
-        let source = "var 1..10: x;";
+    fn test_among_counts_members() {
+        let source = r#"
+            array[1..4] of var 1..6: x;
+            constraint among(2, x, {3, 4, 5});
+            solve satisfy;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Choose the two members (6 ways), 3 values each inside and 3 outside: 6 * 3^4
+        assert_eq!(solutions.len(), 6 * 81);
+        for solution in &solutions {
+            let inside = x.iter().filter(|&&v| (3..=5).contains(&solution.get_int(v))).count();
+            assert_eq!(inside, 2);
+        }
+
+        // The count may be a decision variable
+        let source = r#"
+            array[1..3] of var 1..3: x;
+            var 0..3: n;
+            constraint x[1] = 1 /\ x[2] = 2 /\ x[3] = 2;
+            constraint among(n, x, [2, 3]);
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let n = model_data.int_vars["n"];
+        assert_eq!(model_data.model.solve().unwrap().get_int(n), 2);
     }
 
     #[test]
-    fn test_translate_var_array() {
+    fn test_bound_constraints_fold_into_domains() {
         let source = r#"
-            array[1..4] of var 1..4: queens;
+            int: n = 10;
+            var int: x;
+            var 1..100: y;
+            var int: z;
+            constraint 0 <= x /\ x <= n;
+            constraint y < 20;
+            constraint z >= -5 /\ z <= x;
+            solve satisfy;
         "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let bounds = |name: &str| match &model_data.model.get_vars()[model_data.int_vars[name]] {
+            Var::VarI(domain) => (domain.min(), domain.max()),
+            Var::VarF(_) => panic!("expected an int variable"),
+        };
+        assert_eq!(bounds("x"), (0, 10));
+        assert_eq!(bounds("y"), (1, 19));
+
+        // `z <= x` is not constant, leaving z half-open, so its item is posted as is
+        let x = model_data.int_vars["x"];
+        let z = model_data.int_vars["z"];
+        let solution = model_data.model.solve().unwrap();
+        assert!(solution.get_int(z) >= -5);
+        assert!(solution.get_int(z) <= solution.get_int(x));
     }
 
     #[test]
-    fn test_translate_bool_var() {
-        let source = "var bool: flag;";
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        let translated = result.unwrap();
-        assert_eq!(translated.bool_vars.len(), 1);
-        assert!(translated.bool_vars.contains_key("flag"));
+    fn test_string_interpolation_matches_show() {
+        let model = r#"
+            var 1..3: x;
+            array[1..2] of var 1..3: q;
+            constraint x = 2 /\ q[1] = 3 /\ q[2] = 1;
+            solve satisfy;
+        "#;
+        let render = |output: &str| {
+            let ast = parse(&format!("{}\n{}", model, output)).unwrap();
+            let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+            let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+            model_data.format_output(&solution).unwrap()
+        };
+
+        let interpolated = render(r#"output ["x = \(x), q[1] = \(q[1])\n", "q = \(q)\n"];"#);
+        let shown = render(r#"output ["x = ", show(x), ", q[1] = ", show(q[1]), "\n", "q = ", show(q), "\n"];"#);
+        assert_eq!(interpolated, shown);
+        assert!(interpolated.starts_with("x = 2, q[1] = 3\n"));
     }
 
     #[test]
-    fn test_translate_float_var() {
-        let source = "var 0.0..1.0: probability;";
+    fn test_bool_array_element_as_constraint() {
+        let source = r#"
+            array[1..3] of var bool: flags;
+            var 1..3: i;
+            constraint flags[2];
+            constraint flags[i];
+            constraint not flags[1] /\ not flags[3];
+            solve satisfy;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        let translated = result.unwrap();
-        assert_eq!(translated.float_vars.len(), 1);
-        assert!(translated.float_vars.contains_key("probability"));
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let flags = model_data.bool_var_arrays["flags"].clone();
+        let i = model_data.int_vars["i"];
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Only flags[2] is true, so the variable index must select it
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].get_int(flags[1]), 1);
+        assert_eq!(solutions[0].get_int(i), 2);
     }
 
     #[test]
-    fn test_translate_bool_array() {
-        let source = "array[1..5] of var bool: flags;";
+    fn test_alldifferent_except_0_and_reified() {
+        let source = r#"
+            array[1..4] of var 0..2: x;
+            constraint alldifferent_except_0(x);
+            solve satisfy;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        let translated = result.unwrap();
-        assert_eq!(translated.bool_var_arrays.len(), 1);
-        assert!(translated.bool_var_arrays.contains_key("flags"));
-        assert_eq!(translated.bool_var_arrays.get("flags").unwrap().len(), 5);
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Non-zero values 1 and 2 each appear at most once: 1 + 4 + 4 + 12 assignments
+        assert_eq!(solutions.len(), 21);
+        assert!(solutions.iter().any(|s| x.iter().all(|&v| s.get_int(v) == 0)));
+
+        let source = r#"
+            array[1..3] of var 1..3: y;
+            var bool: b;
+            constraint b <-> alldifferent(y);
+            constraint y[1] = 2 /\ y[3] = 2;
+            solve satisfy;
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let b = model_data.bool_vars["b"];
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(|s| s.get_int(b) == 0));
     }
 
     #[test]
-    fn test_translate_float_array() {
-        let source = "array[1..3] of var 0.0..10.0: prices;";
+    fn test_format_objective() {
+        let source = "var 1..5: x; constraint x != 5; solve maximize x;";
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        let translated = result.unwrap();
-        assert_eq!(translated.float_var_arrays.len(), 1);
-        assert!(translated.float_var_arrays.contains_key("prices"));
-        assert_eq!(translated.float_var_arrays.get("prices").unwrap().len(), 3);
+        let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+        let objective = model_data.objective_var.unwrap();
+        let solution = std::mem::take(&mut model_data.model).maximize(objective).unwrap();
+        assert_eq!(model_data.format_objective(&solution).as_deref(), Some("_objective = 4;"));
+
+        let source = "var 0.5..2.0: f; solve minimize f;";
+        let ast = parse(source).unwrap();
+        let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        let line = model_data.format_objective(&solution).unwrap();
+        let value: f64 = line.trim_start_matches("_objective = ").trim_end_matches(';').parse().unwrap();
+        assert_eq!(value, solution.get_float(model_data.objective_var.unwrap()));
+
+        let source = "var 1..5: x; solve satisfy;";
+        let ast = parse(source).unwrap();
+        let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert!(model_data.format_objective(&solution).is_none());
     }
 
     #[test]
-    fn test_translate_bool_param() {
-        let source = "bool: enabled = true;";
+    fn test_domains_from_compound_parameter_expressions() {
+        let source = r#"
+            int: n = 3;
+            int: m = 4;
+            array[1..n*m] of var 0..n-1: a;
+            array[1..2*n+1, 1..m-1] of var 1..n*m: g;
+            var 1..2*n+1: x;
+            var n-1..n*m: y;
+            var (n+m) div 2..n*m - m: z;
+            solve satisfy;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let bounds = |var: VarId| match &model_data.model.get_vars()[var] {
+            Var::VarI(domain) => (domain.min(), domain.max()),
+            Var::VarF(_) => panic!("expected an int variable"),
+        };
+        assert_eq!(model_data.int_var_arrays["a"].len(), 12);
+        assert_eq!(model_data.int_var_arrays["g"].len(), 21);
+        assert_eq!(bounds(model_data.int_var_arrays["a"][0]), (0, 2));
+        assert_eq!(bounds(model_data.int_var_arrays["g"][20]), (1, 12));
+        assert_eq!(bounds(model_data.int_vars["x"]), (1, 7));
+        assert_eq!(bounds(model_data.int_vars["y"]), (2, 12));
+        assert_eq!(bounds(model_data.int_vars["z"]), (3, 8));
     }
 
     #[test]
-    fn test_translate_float_param() {
-        let source = "float: pi = 3.14159;";
+    fn test_diffn_packs_rectangles() {
+        // A 2x2 square and two unit squares packed into a 3x2 area
+        let source = r#"
+            array[1..3] of int: w = [2, 1, 1];
+            array[1..3] of int: h = [2, 1, 1];
+            array[1..3] of var 0..2: x;
+            array[1..3] of var 0..1: y;
+            constraint forall(i in 1..3)(x[i] + w[i] <= 3 /\ y[i] + h[i] <= 2);
+            constraint diffn(x, y, w, h);
+            solve satisfy;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let y = model_data.int_var_arrays["y"].clone();
+        let (w, h) = ([2, 1, 1], [2, 1, 1]);
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // The square takes the left or right two columns; the unit squares fill
+        // the remaining column in either order
+        assert_eq!(solutions.len(), 4);
+        for solution in &solutions {
+            for i in 0..3 {
+                for j in (i + 1)..3 {
+                    let (xi, yi) = (solution.get_int(x[i]), solution.get_int(y[i]));
+                    let (xj, yj) = (solution.get_int(x[j]), solution.get_int(y[j]));
+                    assert!(
+                        xi + w[i] <= xj || xj + w[j] <= xi || yi + h[i] <= yj || yj + h[j] <= yi,
+                        "rectangles {} and {} overlap",
+                        i + 1,
+                        j + 1
+                    );
+                }
+            }
+        }
+
+        // A single rectangle is trivially placed
+        let single = "array[1..1] of var 0..3: x; array[1..1] of var 0..3: y; \
+                      constraint diffn(x, y, [2], [2]); solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(single).unwrap()).unwrap();
+        assert_eq!(model_data.model.enumerate().count(), 16);
     }
 
     #[test]
-    fn test_translate_boolean_and_constraint() {
+    fn test_enum_order_and_successors() {
         let source = r#"
-            var bool: a;
-            var bool: b;
-            constraint a /\ b;
+            enum Day = {Mon, Tue, Wed, Thu};
+            array[1..3] of var Day: d;
+            var Day: a;
+            var Day: last;
+            constraint d[1] = Tue;
+            constraint forall(i in 1..2)(d[i + 1] = enum_next(d[i]));
+            constraint a > Mon /\ enum_prev(Day, a) = enum_prev(Wed);
+            constraint enum_prev(last) >= Wed;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let d = model_data.int_var_arrays["d"].clone();
+        let a = model_data.int_vars["a"];
+        let last = model_data.int_vars["last"];
+        let solution = model_data.model.solve().unwrap();
+        let days: Vec<i32> = d.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(days, vec![2, 3, 4]);
+        assert_eq!(solution.get_int(a), 3);
+        assert_eq!(solution.get_int(last), 4);
+
+        // A variable's successor does not exist for the last value
+        let source = "enum C = {R, G}; var C: x; constraint enum_next(x) = enum_next(R); \
+                      constraint x != R; solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert!(model_data.model.solve().is_err());
+
+        let source = "enum C = {R, G}; var C: x; constraint x = enum_next(G); solve satisfy;";
+        let err = Translator::translate(&parse(source).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("enum_next(G) is out of range"), "{}", err);
+
+        // A constant that is no position of the enum is an error, not a panic
+        for source in [
+            "enum Day = {Mon, Tue}; var Day: x; constraint x = enum_prev(Day, 0); solve satisfy;",
+            "enum Day = {Mon, Tue}; var Day: x; constraint x = enum_next(Day, 3); solve satisfy;",
+        ] {
+            let err = Translator::translate(&parse(source).unwrap()).unwrap_err();
+            assert!(err.to_string().contains("out of range: the enum has 2 values"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_enum_values_output_as_names() {
+        let source = r#"
+            enum Color = {Red, Green, Blue};
+            var Color: c;
+            array[1..3] of var Color: row;
+            constraint c = Green;
+            constraint forall(i in 1..3)(row[i] = 4 - i);
+            solve satisfy;
+            output ["c = ", show(c), ", row = ", show(row), ", first = ", show(row[1]), "\n"];
+        "#;
+        let mut model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(
+            model_data.format_output(&solution).unwrap(),
+            "c = Green, row = [Blue, Green, Red], first = Blue\n"
+        );
+        assert_eq!(model_data.format_int_value("c", 2), "Green");
+        assert_eq!(model_data.format_int_value("row", 1), "Red");
+    }
+
+    #[test]
+    fn test_inline_array_literals() {
+        let source = r#"
+            var 0..3: a;
+            var 0..3: b;
+            var bool: p;
+            var bool: q;
+            var 0..10: total = sum([a, b, 2]);
+            constraint total = 7;
+            constraint forall([a > 1, b > 1, p]);
+            constraint exists([q, a > 5]);
+            constraint a < b;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let (a, b) = (model_data.int_vars["a"], model_data.int_vars["b"]);
+        let (p, q) = (model_data.bool_vars["p"], model_data.bool_vars["q"]);
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert_eq!(solutions.len(), 1);
+        let solution = &solutions[0];
+        assert_eq!((solution.get_int(a), solution.get_int(b)), (2, 3));
+        assert_eq!((solution.get_int(p), solution.get_int(q)), (1, 1));
+    }
+
+    #[test]
+    fn test_summary_lists_declared_variables() {
+        let source = r#"
+            enum Color = {Red, Green};
+            var 1..5: x;
+            var bool: flag;
+            var 0.0..1.0: ratio;
+            var Color: c;
+            array[1..3] of var 0..9: digits;
+            var set of 1..3: picked;
+            constraint alldifferent(digits);
+            constraint x < digits[1];
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let summary = model_data.summary();
+        for line in [
+            "x: int 1..5",
+            "flag: bool",
+            "ratio: float 0.0..1.0",
+            "c: Color",
+            "digits: array[3] of int 0..9",
+            "picked: set of {1, 2, 3}",
+        ] {
+            assert!(summary.contains(line), "missing '{}' in:\n{}", line, summary);
+        }
+        assert!(summary.contains("AllDifferent: 1"), "{}", summary);
+
+        // Comparisons posted through `model.new(..)` are counted too
+        let source = r#"
+            array[1..4] of var 0..3: x;
+            constraint forall(i in 1..3)(x[i] != x[i+1]);
+            solve maximize sum(x);
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let summary = model_data.summary();
+        assert!(summary.contains("Constraints (4 posted):"), "{}", summary);
+        assert!(summary.contains("LinearInt: 3"), "{}", summary);
+        assert!(summary.contains("Sum: 1"), "{}", summary);
+    }
+
+    #[test]
+    fn test_array_concatenation() {
+        let source = r#"
+            array[1..2] of var 1..4: rows;
+            array[1..2] of var 1..4: cols;
+            array[1..2] of int: fixed = [5, 6];
+            constraint alldifferent(rows ++ cols);
+            constraint increasing(rows ++ cols ++ fixed);
+            var 0..30: total = sum(rows ++ [10]);
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let rows = model_data.int_var_arrays["rows"].clone();
+        let cols = model_data.int_var_arrays["cols"].clone();
+        let total = model_data.int_vars["total"];
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // Strictly increasing across both arrays leaves only 1, 2, 3, 4
+        assert_eq!(solutions.len(), 1);
+        let values: Vec<i32> = rows.iter().chain(&cols).map(|&v| solutions[0].get_int(v)).collect();
+        assert_eq!(values, vec![1, 2, 3, 4]);
+        assert_eq!(solutions[0].get_int(total), 13);
+    }
+
+    #[test]
+    fn test_string_concatenation_in_output() {
+        let source = r#"
+            var 1..3: x;
+            array[1..2] of var 4..5: ys;
+            constraint x = 2;
+            constraint ys[1] < ys[2];
+            solve satisfy;
+            output ["x=" ++ show(x) ++ "\n", "ys=" ++ show(ys) ++ ", " ++ "y1=" ++ show(ys[1]) ++ "\n"];
+        "#;
+        let mut model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(
+            model_data.format_output(&solution).unwrap(),
+            "x=2\nys=[4, 5], y1=4\n"
+        );
+    }
+
+    #[test]
+    fn test_name_of_reverse_lookup() {
+        let source = r#"
+            var 1..5: x;
+            var bool: flag;
+            array[1..3] of var 0..9: digits;
+            array[1..2, 1..2] of var 0..1: grid;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert_eq!(model_data.name_of(model_data.int_vars["x"]).as_deref(), Some("x"));
+        assert_eq!(model_data.name_of(model_data.bool_vars["flag"]).as_deref(), Some("flag"));
+        let digits = &model_data.int_var_arrays["digits"];
+        assert_eq!(model_data.name_of(digits[1]).as_deref(), Some("digits[2]"));
+        let grid = &model_data.int_var_arrays["grid"];
+        assert_eq!(model_data.name_of(grid[1]).as_deref(), Some("grid[1, 2]"));
+        assert_eq!(model_data.name_of(grid[3]).as_deref(), Some("grid[2, 2]"));
+    }
+
+    #[test]
+    fn test_name_of_uses_declared_indices() {
+        let source = r#"
+            array[0..2] of var 1..3: a;
+            array[-1..0, 2..3] of var 1..3: b;
             solve satisfy;
         "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let a = &model_data.int_var_arrays["a"];
+        assert_eq!(model_data.name_of(a[0]).as_deref(), Some("a[0]"));
+        assert_eq!(model_data.name_of(a[2]).as_deref(), Some("a[2]"));
+        let b = &model_data.int_var_arrays["b"];
+        assert_eq!(model_data.name_of(b[0]).as_deref(), Some("b[-1, 2]"));
+        assert_eq!(model_data.name_of(b[3]).as_deref(), Some("b[0, 3]"));
+    }
+
+    #[test]
+    fn test_parameter_matrix_with_variable_indices() {
+        let source = r#"
+            array[1..2, 1..3] of int: cost = array2d(1..2, 1..3, [4, 2, 7, 3, 9, 1]);
+            array[1..2, 1..2, 1..2] of int: bonus = array3d(1..2, 1..2, 1..2, [0, 5, 1, 0, 2, 0, 0, 3]);
+            var 1..2: i;
+            var 1..3: j;
+            var 1..2: k;
+            constraint j < 3;
+            var 0..20: total = cost[i, j] + bonus[i, k, i];
+            solve minimize total;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let (i, j, k) = (model_data.int_vars["i"], model_data.int_vars["j"], model_data.int_vars["k"]);
+        let total = model_data.int_vars["total"];
+        let solution = crate::optimize(&ast, crate::SolverConfig::default()).unwrap().unwrap();
+        // Cheapest cell outside column 3 is cost[1, 2] = 2, plus bonus[1, k, 1] = 0 for k = 1
+        assert_eq!(
+            (solution.get_int(i), solution.get_int(j), solution.get_int(k), solution.get_int(total)),
+            (1, 2, 1, 2)
+        );
     }
 
     #[test]
-    fn test_translate_boolean_or_constraint() {
+    fn test_parameter_matrix_index_wider_than_dimension() {
+        // j = 4 must not wrap around to c[2, 1]
         let source = r#"
-            var bool: x;
-            var bool: y;
-            constraint x \/ y;
+            array[1..2, 1..3] of int: c = array2d(1..2, 1..3, [1, 2, 3, 4, 5, 6]);
+            var 1..2: i;
+            var 1..4: j;
+            constraint c[i, j] = 4;
             solve satisfy;
         "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let (i, j) = (model_data.int_vars["i"], model_data.int_vars["j"]);
+        let solutions: Vec<(i32, i32)> = model_data.model.enumerate().map(|s| (s.get_int(i), s.get_int(j))).collect();
+        assert_eq!(solutions, vec![(2, 1)]);
+    }
+
+    #[test]
+    fn test_knapsack_global() {
+        let source = r#"
+            array[1..4] of int: weight = [5, 4, 6, 3];
+            array[1..4] of int: profit = [10, 40, 30, 50];
+            array[1..4] of var 0..1: take;
+            var 0..200: total;
+            constraint knapsack(weight, profit, take, 10, total);
+            solve maximize total;
+        "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let take = model_data.int_var_arrays["take"].clone();
+        let total = model_data.int_vars["total"];
+        let solution = crate::optimize(&ast, crate::SolverConfig::default()).unwrap().unwrap();
+        let taken: Vec<i32> = take.iter().map(|&v| solution.get_int(v)).collect();
+        // Items 2 and 4 weigh 7 and earn 90; nothing else fits alongside them
+        assert_eq!(taken, vec![0, 1, 0, 1]);
+        assert_eq!(solution.get_int(total), 90);
+        let used: i32 = taken.iter().zip([5, 4, 6, 3]).map(|(t, w)| t * w).sum();
+        assert!(used <= 10);
+    }
+
+    #[test]
+    fn test_aggregate_compared_with_variable() {
+        let source = r#"
+            array[1..3] of var 1..5: x;
+            var 1..20: limit;
+            var 1..5: y;
+            constraint sum(x) < limit;
+            constraint limit < sum(x) + 2;
+            constraint min(x) > y;
+            constraint y >= max(x) - 2;
+            constraint limit > max(x) * 2;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let (limit, y) = (model_data.int_vars["limit"], model_data.int_vars["y"]);
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+            let (sum, min, max) = (values.iter().sum::<i32>(), *values.iter().min().unwrap(), *values.iter().max().unwrap());
+            let (limit, y) = (solution.get_int(limit), solution.get_int(y));
+            assert_eq!(limit, sum + 1);
+            assert!(min > y && y >= max - 2 && limit > max * 2, "{:?} y={} limit={}", values, y, limit);
+        }
+    }
+
+    #[test]
+    fn test_empty_aggregates() {
+        let source = "var 0..5: y; constraint y = sum([]) + sum(i in 1..0)(i); solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let y = model_data.int_vars["y"];
+        assert_eq!(model_data.model.solve().unwrap().get_int(y), 0);
+
+        for (source, message) in [
+            ("var 0..5: y; constraint min([]) > y; solve satisfy;", "min() of an empty array is undefined"),
+            ("var 0..5: y; constraint y < max(i in 1..0)(i); solve satisfy;", "max() of an empty array is undefined"),
+        ] {
+            let err = Translator::translate(&parse(source).unwrap()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}", err);
+            assert_ne!(err.span, Span::dummy());
+        }
+    }
+
+    #[test]
+    fn test_forall_sortedness() {
+        for constraint in [
+            "forall(i in 1..n-1)(x[i] <= x[i+1])",
+            "forall(i in 1..n-1)(x[i] <= x[i+1] /\\ x[i] >= 1)",
+            "forall([x[i] <= x[i+1] | i in 1..n-1])",
+        ] {
+            let source = format!(
+                "int: n = 4; array[1..n] of var 1..3: x; constraint {}; solve satisfy;",
+                constraint
+            );
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let x = model_data.int_var_arrays["x"].clone();
+            let solutions: Vec<_> = model_data.model.enumerate().collect();
+            // Non-decreasing sequences of length 4 over 1..3: C(6, 4)
+            assert_eq!(solutions.len(), 15, "{}", constraint);
+            for solution in &solutions {
+                let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+                assert!(values.windows(2).all(|w| w[0] <= w[1]), "{}: {:?}", constraint, values);
+            }
+        }
+
+        let source = "array[1..3] of var 1..3: x; constraint exists([x[i] > x[i+1] | i in 1..2]); solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        // All 27 assignments except the 10 non-decreasing ones
+        assert_eq!(model_data.model.enumerate().count(), 17);
+    }
+
+    #[test]
+    fn test_seeded_param_arrays_shared_across_translations() {
+        let table: Vec<i32> = (0..1000).map(|i| (i * 7) % 1000).collect();
+        let options = TranslatorOptions::default()
+            .with_int_param_array("table", table)
+            .with_float_param_array("rates", vec![0.5, 1.5]);
+
+        // Seeding shares the tables rather than copying them
+        let mut translator = Translator::new();
+        translator.options = options.clone();
+        translator.seed_param_arrays();
+        assert!(Arc::ptr_eq(&options.int_param_arrays["table"], translator.context.get_int_param_array("table").unwrap()));
+        assert!(Arc::ptr_eq(&options.float_param_arrays["rates"], translator.context.get_float_param_array("rates").unwrap()));
+
+        // Used directly, with a variable index
+        let first = parse("var 1..1000: i; constraint table[i] = 994; solve satisfy;").unwrap();
+        let model_data = Translator::translate_with_vars_and_options(&first, options.clone()).unwrap();
+        let i = model_data.int_vars["i"];
+        // 7 * 142 = 994
+        assert_eq!(model_data.model.solve().unwrap().get_int(i), 143);
+
+        // Declared without an initializer to view it as a matrix
+        let second = parse(r#"
+            array[1..10, 1..100] of int: table;
+            var 0..1000: x = table[2, 3] + table[1, 1];
+            var float: r = rates[2];
+            solve satisfy;
+        "#).unwrap();
+        let model_data = Translator::translate_with_vars_and_options(&second, options.clone()).unwrap();
+        let x = model_data.int_vars["x"];
+        let r = model_data.float_vars["r"];
+        let solution = model_data.model.solve().unwrap();
+        // table[2, 3] is flat position 102: 7 * 102 = 714
+        assert_eq!(solution.get_int(x), 714);
+        assert!((solution.get_float(r) - 1.5).abs() < 1e-9);
+
+        let mismatched = parse("array[1..10] of int: table; solve satisfy;").unwrap();
+        let Err(err) = Translator::translate_with_vars_and_options(&mismatched, options) else {
+            panic!("a 10-element declaration of the 1000-element table should fail");
+        };
+        assert!(err.to_string().contains("size"), "{}", err);
+    }
+
+    #[test]
+    fn test_div_mod_by_possibly_zero_divisor() {
+        // Divisors of 0 are excluded rather than failing the whole model
+        let source = r#"
+            var -2..2: d;
+            var -10..10: q = 7 div d;
+            var -10..10: r = 7 mod d;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let (d, q, r) = (model_data.int_vars["d"], model_data.int_vars["q"], model_data.int_vars["r"]);
+        let mut found: Vec<(i32, i32, i32)> = model_data
+            .model
+            .enumerate()
+            .map(|s| (s.get_int(d), s.get_int(q), s.get_int(r)))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![(-2, -3, 1), (-1, -7, 0), (1, 7, 0), (2, 3, 1)]);
+
+        // A divisor fixed to 0 makes the model unsatisfiable
+        for op in ["div", "mod"] {
+            let source = format!("var 0..0: d; var -10..10: q = 7 {} d; solve satisfy;", op);
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            assert!(model_data.model.solve().is_err(), "7 {} 0", op);
+        }
+
+        // Constant division by zero is reported instead of panicking
+        let err = Translator::translate(&parse("int: k = 7 div 0; solve satisfy;").unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Division by zero"), "{}", err);
     }
 
     #[test]
-    fn test_translate_boolean_not_constraint() {
+    fn test_output_var_annotations_restrict_default_output() {
         let source = r#"
-            var bool: flag;
-            constraint not flag;
+            var 1..3: x :: output_var;
+            var 1..3: hidden;
+            array[1..2] of var 4..5: ys :: output_array([1..2]);
+            constraint x = 2 /\ hidden = 3 /\ ys[1] < ys[2];
             solve satisfy;
         "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let ast::Item::VarDecl(decl) = &ast.items[0] else { panic!("expected a declaration") };
+        assert_eq!(decl.annotations[0].name, "output_var");
+
+        let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert_eq!(model_data.output_vars, vec!["x", "ys"]);
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(model_data.format_default(&solution), "x = 2;\nys = [4, 5];\n");
+
+        // Without annotations every variable is printed
+        let source = "var 1..3: x; var 1..3: hidden; constraint x = 2 /\\ hidden = 3; solve satisfy;";
+        let mut model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        let output = model_data.format_default(&solution);
+        assert!(output.contains("x = 2;\n") && output.contains("hidden = 3;\n"), "{}", output);
     }
 
     #[test]
-    fn test_translate_boolean_implication() {
+    fn test_top_level_conjunction_split() {
         let source = r#"
-            var bool: a;
-            var bool: b;
-            constraint a -> b;
+            var -2..2: x;
+            var -2..2: y;
+            var -2..2: z;
+            constraint (x > 0) /\ (y > 0) /\ (z > x + y - 2);
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let (x, y, z) = (model_data.int_vars["x"], model_data.int_vars["y"], model_data.int_vars["z"]);
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // (1,1): z in 1..2, (1,2) and (2,1): z = 2, (2,2): none
+        assert_eq!(solutions.len(), 4);
+        for solution in &solutions {
+            let (x, y, z) = (solution.get_int(x), solution.get_int(y), solution.get_int(z));
+            assert!(x > 0 && y > 0 && z > x + y - 2, "x={} y={} z={}", x, y, z);
+        }
     }
 
     #[test]
-    fn test_translate_float_arithmetic() {
+    fn test_generators_over_parameter_index_arrays() {
         let source = r#"
-            var 0.0..10.0: x;
-            var 0.0..10.0: y;
-            constraint x + y <= 15.0;
+            array[1..3] of int: picks = [2, 4, 6];
+            array[1..6] of var 0..3: x;
+            var 0..20: total = sum(i in picks)(x[i]);
+            constraint forall(i in picks)(x[i] >= i div 2);
+            constraint forall(i in 1..6 where i mod 2 = 1)(x[i] = 0);
+            constraint exists(i in {1, 3})(x[i + 1] = 3);
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let total = model_data.int_vars["total"];
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            let values: Vec<i32> = x.iter().map(|&v| solution.get_int(v)).collect();
+            assert_eq!(solution.get_int(total), values[1] + values[3] + values[5], "{:?}", values);
+            assert!(values[1] >= 1 && values[3] >= 2 && values[5] >= 3, "{:?}", values);
+            assert!(values[1] == 3 || values[3] == 3, "{:?}", values);
+        }
     }
 
     #[test]
-    fn test_translate_array_access() {
+    fn test_float_literal_comparisons() {
         let source = r#"
-            array[1..5] of var 1..10: arr;
-            constraint arr[3] > 5;
+            var 0.0..10.0: cost;
+            var 0.0..10.0: fixed;
+            constraint cost <= 9.5;
+            constraint 9.25 < cost;
+            constraint cost != 9.3;
+            constraint fixed = 2.5;
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate(&ast);
-        assert!(result.is_ok());
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        // Literals are bounds, not extra fixed variables
+        assert_eq!(model_data.model.get_vars().iter().count(), 2);
+        let (cost, fixed) = (model_data.float_vars["cost"], model_data.float_vars["fixed"]);
+        let solution = model_data.model.solve().unwrap();
+        let cost = solution.get_float(cost);
+        assert!(cost > 9.25 && cost <= 9.5, "cost = {}", cost);
+        assert!((solution.get_float(fixed) - 2.5).abs() < 1e-9);
+
+        // Float equalities are posted with the pass-2 simple equalities
+        let model = parse("constraint fixed = 0.0;").unwrap();
+        let ast::Item::Constraint(constraint) = &model.items[0] else { panic!("expected a constraint") };
+        assert!(Translator::is_simple_equality_constraint(&constraint.expr));
     }
 
     #[test]
-    fn test_translate_sum_aggregate() {
+    fn test_aggregates_over_empty_arrays() {
         let source = r#"
-            array[1..3] of var 1..10: values;
-            constraint sum(values) == 15;
+            array[1..0] of var 1..5: x;
+            array[1..0] of var bool: b;
+            var -5..5: s = sum(x);
+            var -5..5: p = product(x);
+            var -5..5: c = count(x, 3);
+            var 0..1: e = exists(b);
+            var 0..1: a = forall(b);
+            constraint forall(b);
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let solution = model_data.model.solve();
-        assert!(solution.is_ok());
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert!(model_data.int_var_arrays["x"].is_empty());
+        let values: Vec<VarId> = ["s", "p", "c"].iter().map(|n| model_data.int_vars[*n]).collect();
+        let (e, a) = (model_data.int_vars["e"], model_data.int_vars["a"]);
+        let solution = model_data.model.solve().unwrap();
+        let found: Vec<i32> = values.iter().map(|&v| solution.get_int(v)).collect();
+        assert_eq!(found, vec![0, 1, 0]);
+        assert_eq!((solution.get_int(e), solution.get_int(a)), (0, 1));
+
+        for name in ["min", "max"] {
+            let source = format!("array[1..0] of var 1..5: x; var 0..5: m = {}(x); solve satisfy;", name);
+            let err = Translator::translate(&parse(&source).unwrap()).unwrap_err();
+            assert!(err.to_string().contains(&format!("{}() of an empty array is undefined", name)), "{}", err);
+        }
     }
 
     #[test]
-    fn test_translate_min_aggregate() {
+    fn test_let_bound_term_in_two_bounds() {
         let source = r#"
-            array[1..3] of var 1..10: values;
-            constraint min(values) >= 5;
+            var 1..15: x;
+            var 1..15: y;
+            var 1..3: t;
+            constraint let { var int: t = x + y } in t >= 10 /\ t <= 20;
+            constraint forall(i in 1..2)(let { int: k = 2 * i } in x != k);
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify the constraint: all values should be >= 5
-        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
-            let solution = sol.unwrap();
-            for var_id in values_arr {
-                let val = solution.get_int(*var_id);
-                assert!(val >= 5, "Expected all values >= 5, but got {}", val);
-            }
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let (x, y, t) = (model_data.int_vars["x"], model_data.int_vars["y"], model_data.int_vars["t"]);
+        assert_eq!(model_data.int_vars.len(), 3);
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // The outer t keeps its own domain; x + y ranges over 10..20 with x not 2 or 4
+        let pairs = (1..=15).flat_map(|a| (1..=15).map(move |b| (a, b)))
+            .filter(|&(a, b)| (10..=20).contains(&(a + b)) && a != 2 && a != 4)
+            .count();
+        assert_eq!(solutions.len(), pairs * 3);
+        for solution in &solutions {
+            let sum = solution.get_int(x) + solution.get_int(y);
+            assert!((10..=20).contains(&sum), "x + y = {}", sum);
+            assert!((1..=3).contains(&solution.get_int(t)));
         }
     }
 
     #[test]
-    fn test_translate_max_aggregate() {
+    fn test_abs_in_comparison() {
+        let source = "var -5..5: x; constraint abs(x) = 3; solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let x = model_data.int_vars["x"];
+        let mut values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(x)).collect();
+        values.sort();
+        assert_eq!(values, vec![-3, 3]);
+
+        let source = "var 1..5: x; var 1..5: y; constraint abs(x - y) = 4; solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let (x, y) = (model_data.int_vars["x"], model_data.int_vars["y"]);
+        let mut pairs: Vec<(i32, i32)> = model_data.model.enumerate().map(|s| (s.get_int(x), s.get_int(y))).collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(1, 5), (5, 1)]);
+
+        let source = "var -5..5: x; constraint int_abs(x, 2); solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let x = model_data.int_vars["x"];
+        let mut values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(x)).collect();
+        values.sort();
+        assert_eq!(values, vec![-2, 2]);
+    }
+
+    #[test]
+    fn test_warnings_for_approximated_constructs() {
+        let source = "var int: x :: is_defined_var; var 1..3: y :: output_var; var float: f; solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let messages: Vec<&str> = model_data.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "Annotation 'is_defined_var' on 'x' is ignored",
+            "'x' has no bounds; the solver limits it to -100000..100000",
+            "'f' has no bounds; the solver limits it to -1e9..1e9",
+        ]);
+        assert_eq!(
+            model_data.warnings[0].display_in(source),
+            "Warning at line 1, column 15: Annotation 'is_defined_var' on 'x' is ignored"
+        );
+
+        // Bounds from the constraints or a definition leave nothing to report
+        let source = "var int: x; var int: y = x + 1; constraint x >= 0 /\\ x <= 9; solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert!(model_data.warnings.is_empty(), "{:?}", model_data.warnings);
+    }
+
+    #[test]
+    fn test_maximize_element_with_variable_index() {
         let source = r#"
-            array[1..3] of var 1..10: values;
-            constraint max(values) <= 7;
-            solve satisfy;
+            array[1..5] of int: v = [3, 9, 4, 7, 1];
+            var 1..5: i;
+            constraint i != 2;
+            solve maximize v[i];
         "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify the constraint: all values should be <= 7
-        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
-            let solution = sol.unwrap();
-            for var_id in values_arr {
-                let val = solution.get_int(*var_id);
-                assert!(val <= 7, "Expected all values <= 7, but got {}", val);
-            }
-        }
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let i = model_data.int_vars["i"];
+        let objective = model_data.objective_var.unwrap();
+        let solution = crate::optimize(&ast, crate::SolverConfig::default()).unwrap().unwrap();
+        assert_eq!((solution.get_int(i), solution.get_int(objective)), (4, 7));
+
+        let source = r#"
+            array[1..4] of var 1..10: x;
+            var 1..4: i;
+            constraint forall(j in 1..4)(x[j] <= 2 * j);
+            constraint x[4] < 5;
+            solve maximize x[i];
+        "#;
+        let ast = parse(source).unwrap();
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let (i, x) = (model_data.int_vars["i"], model_data.int_var_arrays["x"].clone());
+        let solution = crate::optimize(&ast, crate::SolverConfig::default()).unwrap().unwrap();
+        let chosen = solution.get_int(i);
+        assert_eq!(chosen, 3);
+        assert_eq!(solution.get_int(x[chosen as usize - 1]), 6);
     }
 
     #[test]
-    fn test_translate_product_aggregate() {
+    fn test_latin_square_rows_and_columns() {
         let source = r#"
-            array[1..3] of var 2..4: factors;
-            constraint product(factors) == 24;
+            int: n = 3;
+            array[1..n, 1..n] of var 1..n: sq;
+            constraint forall(i in 1..n)(alldifferent(row(sq, i)));
+            constraint forall(j in 1..n)(alldifferent(col(sq, j)));
+            constraint sq[1, 1] = 1;
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify the constraint
-        if let Some(factors_arr) = model_data.int_var_arrays.get("factors") {
-            let solution = sol.unwrap();
-            let mut product = 1;
-            for var_id in factors_arr {
-                let val = solution.get_int(*var_id);
-                product *= val;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let sq = model_data.int_var_arrays["sq"].clone();
+        let solutions: Vec<_> = model_data.model.enumerate().collect();
+        // 12 Latin squares of order 3, a third of them with 1 in the corner
+        assert_eq!(solutions.len(), 4);
+        for solution in &solutions {
+            let cell = |r: usize, c: usize| solution.get_int(sq[r * 3 + c]);
+            for k in 0..3 {
+                let mut row: Vec<i32> = (0..3).map(|c| cell(k, c)).collect();
+                let mut col: Vec<i32> = (0..3).map(|r| cell(r, k)).collect();
+                row.sort();
+                col.sort();
+                assert_eq!(row, vec![1, 2, 3]);
+                assert_eq!(col, vec![1, 2, 3]);
             }
-            assert_eq!(product, 24, "Expected product == 24, but got {}", product);
         }
+
+        let source = "array[1..3] of var 1..3: x; constraint alldifferent(row(x, 1)); solve satisfy;";
+        let Err(err) = Translator::translate_with_vars(&parse(source).unwrap()) else { panic!("expected an error") };
+        assert!(err.to_string().contains("row() needs a 2D array"), "{}", err);
     }
 
     #[test]
-    fn test_translate_minimize() {
+    fn test_consistency_annotation_on_alldifferent() {
         let source = r#"
-            var 1..10: x;
-            constraint x >= 3;
-            solve minimize x;
+            array[1..3] of var 1..3: x;
+            var 1..3: y;
+            constraint alldifferent(x) :: domain;
+            constraint y < x[1] :: domain;
+            constraint y != 2 :: priority(2);
+            solve satisfy;
         "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        let model_data = result.unwrap();
-        assert_eq!(model_data.objective_type, ObjectiveType::Minimize);
-        assert!(model_data.objective_var.is_some());
+        let ast::Item::Constraint(annotated) = &ast.items[2] else { panic!("expected a constraint") };
+        assert_eq!(annotated.annotations[0].name, "domain");
+
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let messages: Vec<&str> = model_data.warnings.iter().map(|w| w.message.as_str()).collect();
+        // alldifferent takes the flag; the comparison has no stronger variant
+        assert_eq!(messages, vec![
+            "Annotation 'domain' has no effect on this constraint",
+            "Annotation 'priority' on a constraint is ignored",
+        ]);
+        let (x, y) = (model_data.int_var_arrays["x"].clone(), model_data.int_vars["y"]);
+        let solution = model_data.model.solve().unwrap();
+        assert!(solution.get_int(y) < solution.get_int(x[0]));
     }
 
     #[test]
-    fn test_translate_maximize() {
+    fn test_regular_no_three_equal_in_a_row() {
+        // States: 1 start, 2/3 one/two 1s in a row, 4/5 one/two 2s in a row
         let source = r#"
-            var 1..10: x;
-            constraint x <= 7;
-            solve maximize x;
+            array[1..5] of var 1..2: x;
+            array[1..5, 1..2] of int: d = array2d(1..5, 1..2, [
+                2, 4,
+                3, 4,
+                0, 4,
+                2, 5,
+                2, 0,
+            ]);
+            constraint regular(x, 5, 2, d, 1, 1..5);
+            solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        let model_data = result.unwrap();
-        assert_eq!(model_data.objective_type, ObjectiveType::Maximize);
-        assert!(model_data.objective_var.is_some());
-    }
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let x = model_data.int_var_arrays["x"].clone();
+        let mut words: Vec<Vec<i32>> = model_data.model.enumerate()
+            .map(|s| x.iter().map(|&v| s.get_int(v)).collect())
+            .collect();
+        words.sort();
+        let mut expected: Vec<Vec<i32>> = (0..32)
+            .map(|bits: u32| (0..5).map(|k| ((bits >> k) & 1) as i32 + 1).collect::<Vec<i32>>())
+            .filter(|w| w.windows(3).all(|t| !(t[0] == t[1] && t[1] == t[2])))
+            .collect();
+        expected.sort();
+        assert_eq!(words.len(), 16);
+        assert_eq!(words, expected);
 
-    #[test]
-    fn test_element_constraint_variable_index() {
         let source = r#"
-            array[1..5] of var 1..10: values;
-            var 1..5: index;
-            var 1..10: result;
-            
-            % Element constraint: result == values[index]
-            constraint result == values[index];
-            constraint index == 3;
-            constraint result == 7;
-            
+            array[1..3] of var 1..2: x;
+            array[1..2, 1..3] of int: d = array2d(1..2, 1..3, [1, 2, 0, 2, 1, 0]);
+            constraint regular(x, 3, 2, d, 1, {1});
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify: index should be 3, result should be 7, values[3] should be 7
-        let solution = sol.unwrap();
-        if let Some(&index_var) = model_data.int_vars.get("index") {
-            assert_eq!(solution.get_int(index_var), 3);
-        }
-        if let Some(&result_var) = model_data.int_vars.get("result") {
-            assert_eq!(solution.get_int(result_var), 7);
-        }
-        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
-            // values[3] (0-indexed: values[2]) should be 7
-            assert_eq!(solution.get_int(values_arr[2]), 7);
-        }
+        let Err(err) = Translator::translate_with_vars(&parse(source).unwrap()) else { panic!("expected an error") };
+        assert!(err.to_string().contains("transition table must be 3 x 2"), "{}", err);
     }
 
     #[test]
-    fn test_element_constraint_in_expression() {
+    fn test_subcircuit_with_self_loops() {
         let source = r#"
-            array[1..4] of var 1..10: arr;
-            var 1..4: i;
-            
-            % Use element in a constraint expression
-            constraint arr[i] > 5;
-            constraint i == 2;
-            
+            array[1..5] of var 1..5: succ;
+            constraint subcircuit(succ);
+            constraint succ[2] = 2 /\ succ[4] = 4 /\ succ[1] = 3;
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify: arr[2] should be > 5
-        let solution = sol.unwrap();
-        if let Some(arr) = model_data.int_var_arrays.get("arr") {
-            let val_at_index_2 = solution.get_int(arr[1]); // 0-indexed
-            assert!(val_at_index_2 > 5, "Expected arr[2] > 5, got {}", val_at_index_2);
-        }
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let succ = model_data.int_var_arrays["succ"].clone();
+        let mut solutions: Vec<Vec<i32>> = model_data.model.enumerate()
+            .map(|s| succ.iter().map(|&v| s.get_int(v)).collect())
+            .collect();
+        solutions.sort();
+        // The cycle through 1 and 3 either skips node 5 or passes through it
+        assert_eq!(solutions, vec![vec![3, 2, 1, 4, 5], vec![3, 2, 5, 4, 1]]);
+
+        // Two separate cycles are not a subcircuit
+        let source = "array[1..5] of var 1..5: succ; constraint subcircuit(succ); \
+            constraint succ[1] = 2 /\\ succ[2] = 1 /\\ succ[3] = 4 /\\ succ[4] = 3; solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert!(model_data.model.solve().is_err());
+
+        // One empty circuit plus sum over k >= 2 of C(5, k) * (k - 1)! cycles
+        let source = "array[1..5] of var 1..5: succ; constraint subcircuit(succ); solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert_eq!(model_data.model.enumerate().count(), 1 + 10 + 20 + 30 + 24);
     }
 
     #[test]
-    fn test_count_aggregate() {
+    fn test_zero_based_array_output_order() {
         let source = r#"
-            array[1..5] of var 1..5: values;
-            var 0..5: count_result;
-            
-            % Count how many values equal 3
-            constraint count_result == count(values, 3);
-            
-            % Set some values to 3
-            constraint values[1] == 3;
-            constraint values[2] == 3;
-            constraint values[3] == 3;
-            
+            array[0..2] of var 1..3: x;
+            constraint x[0] = 3;
+            constraint x[2] = 1;
+            constraint alldifferent(x);
             solve satisfy;
+            output ["x = ", show(x), ", first = ", show(x[0]), "\n"];
         "#;
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify: count_result should be 3
-        let solution = sol.unwrap();
-        if let Some(&count_var) = model_data.int_vars.get("count_result") {
-            assert_eq!(solution.get_int(count_var), 3);
-        }
+        let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+        assert_eq!(model_data.array_index_sets["x"], vec![(0, 2)]);
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(model_data.format_output(&solution).unwrap(), "x = [3, 2, 1], first = 3\n");
+
+        let options = TranslatorOptions::default().with_indexed_output(true);
+        let mut model_data = Translator::translate_with_vars_and_options(&ast, options).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(model_data.format_output(&solution).unwrap(), "x = [0: 3, 1: 2, 2: 1], first = 3\n");
     }
 
     #[test]
-    fn test_count_with_constraint() {
+    fn test_variable_index_into_offset_2d_array() {
         let source = r#"
-            array[1..4] of var 1..3: values;
-            
-            % At least 2 values must be equal to 2
-            constraint count(values, 2) >= 2;
-            
+            array[0..1, 2..3] of var 1..4: grid;
+            var 0..1: r;
+            var 2..3: c;
+            constraint alldifferent([grid[i, j] | i in 0..1, j in 2..3]);
+            constraint grid[0, 2] = 1 /\ grid[0, 3] = 2 /\ grid[1, 2] = 3;
+            constraint grid[r, c] = 4;
             solve satisfy;
-        "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify: at least 2 values should be 2
-        let solution = sol.unwrap();
-        if let Some(values_arr) = model_data.int_var_arrays.get("values") {
-            let count_2s: i32 = values_arr.iter()
-                .map(|&v| solution.get_int(v))
-                .filter(|&val| val == 2)
-                .count() as i32;
-            assert!(count_2s >= 2, "Expected at least 2 values equal to 2, got {}", count_2s);
-        }
+        "#;
+        let ast = parse(source).unwrap();
+        let options = TranslatorOptions::default().with_indexed_output(true);
+        let mut model_data = Translator::translate_with_vars_and_options(&ast, options).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(solution.get_int(model_data.int_vars["r"]), 1);
+        assert_eq!(solution.get_int(model_data.int_vars["c"]), 3);
+        assert!(model_data
+            .format_default(&solution)
+            .contains("grid = [(0, 2): 1, (0, 3): 2, (1, 2): 3, (1, 3): 4];"));
     }
 
     #[test]
-    fn test_exists_aggregate() {
+    fn test_variable_index_wider_than_dimension() {
+        // An index past the end of its dimension must not alias into the next row or plane
+        let solutions = |source: &str, names: &[&str]| {
+            let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+            let vars: Vec<VarId> = names.iter().map(|&name| model_data.int_vars[name]).collect();
+            model_data
+                .model
+                .enumerate()
+                .map(|s| vars.iter().map(|&v| s.get_int(v)).collect())
+                .collect::<Vec<Vec<i32>>>()
+        };
         let source = r#"
-            array[1..4] of var bool: flags;
-            var bool: any_true;
-            
-            % at least one flag must be true
-            constraint any_true == exists(flags);
-            constraint any_true;  % Force it to be true
-            
+            array[1..2, 1..3] of var 1..6: g;
+            constraint forall(a in 1..2, b in 1..3)(g[a, b] = 3 * (a - 1) + b);
+            var 1..2: i;
+            var 1..4: j;
+            constraint g[i, j] = 4;
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify: at least one flag should be true
-        let solution = sol.unwrap();
-        if let Some(flags_arr) = model_data.bool_var_arrays.get("flags") {
-            let any_true = flags_arr.iter()
-                .map(|&v| solution.get_int(v))
-                .any(|val| val != 0);
-            assert!(any_true, "Expected at least one flag to be true");
-        }
+        assert_eq!(solutions(source, &["i", "j"]), vec![vec![2, 1]]);
+        let source = r#"
+            array[1..2, 1..2, 1..2] of var 1..8: h;
+            constraint forall(a in 1..2, b in 1..2, c in 1..2)(h[a, b, c] = 4 * (a - 1) + 2 * (b - 1) + c);
+            var 1..2: p;
+            var 1..2: q;
+            var 1..3: k;
+            constraint h[p, q, k] = 3;
+            solve satisfy;
+        "#;
+        assert_eq!(solutions(source, &["p", "q", "k"]), vec![vec![1, 2, 1]]);
     }
 
     #[test]
-    fn test_forall_aggregate() {
+    fn test_sudoku_boxes_alldifferent_in_forall() {
+        // 4x4 Sudoku: rows, columns and 2x2 boxes, each box a comprehension
         let source = r#"
-            array[1..4] of var bool: flags;
-            var bool: all_true;
-            
-            % all flags must be true
-            constraint all_true == forall(flags);
-            constraint all_true;  % Force it to be true
-            
+            int: n = 4;
+            array[1..n, 1..n] of var 1..n: grid;
+            constraint forall(r in 1..n)(alldifferent([grid[r, c] | c in 1..n]));
+            constraint forall(c in 1..n)(alldifferent([grid[r, c] | r in 1..n]));
+            constraint forall(br in 0..1, bc in 0..1)(
+                alldifferent([grid[2 * br + i, 2 * bc + j] | i in 1..2, j in 1..2])
+            );
+            constraint grid[1, 1] = 1 /\ grid[1, 2] = 2 /\ grid[2, 1] = 3;
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok());
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        assert!(sol.is_ok());
-        
-        // Verify: all flags should be true
-        let solution = sol.unwrap();
-        if let Some(flags_arr) = model_data.bool_var_arrays.get("flags") {
-            let all_true = flags_arr.iter()
-                .map(|&v| solution.get_int(v))
-                .all(|val| val != 0);
-            assert!(all_true, "Expected all flags to be true");
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let grid = model_data.int_var_arrays["grid"].clone();
+        let solutions: Vec<Vec<i32>> = model_data
+            .model
+            .enumerate()
+            .map(|solution| grid.iter().map(|&v| solution.get_int(v)).collect())
+            .collect();
+        // 16 Latin squares extend these clues, 12 of them with valid boxes
+        assert_eq!(solutions.len(), 12);
+        for cells in &solutions {
+            for b in 0..4 {
+                let (r0, c0) = (2 * (b / 2), 2 * (b % 2));
+                let mut block: Vec<i32> = (0..4).map(|k| cells[(r0 + k / 2) * 4 + c0 + k % 2]).collect();
+                block.sort();
+                assert_eq!(block, vec![1, 2, 3, 4], "box {} of {:?}", b, cells);
+            }
         }
     }
 
     #[test]
-    fn test_modulo_operator() {
-        // Test that modulo operator can be evaluated with constants
+    fn test_pretty_print_mixed_model() {
         let source = r#"
-            var 1..20: x;
-            var 0..4: remainder;
-            
-            % Direct constraint with constants: check if 13 mod 5 = 3
-            constraint 13 mod 5 == 3;
-            constraint x == 13;
-            constraint remainder == 3;
-            
+            var 1..5: n;
+            var bool: flag;
+            var 0.0..1.0: ratio;
+            array[1..3] of var 0..9: digits;
+            array[1..2] of var bool: bits;
+            constraint n = 4 /\ flag /\ ratio = 0.5;
+            constraint digits[1] = 7 /\ digits[2] = 0 /\ digits[3] = 2;
+            constraint bits[1] /\ not bits[2];
             solve satisfy;
         "#;
-        let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok(), "Failed to translate modulo expression");
+        let mut model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(
+            model_data.pretty_print(&solution),
+            "bits = [1, 0];\ndigits = [7, 0, 2];\nflag = 1;\nn = 4;\nratio = 0.5;\n"
+        );
+        assert!(!model_data.pretty_print_with_source(&solution).1);
+
+        // Output items take precedence over the dzn listing
+        let ast = parse(&format!("{}\noutput [\"n=\", show(n), \"\\n\"];", source)).unwrap();
+        let mut model_data = Translator::translate_with_vars(&ast).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(model_data.pretty_print(&solution), "n=4\n");
+        assert_eq!(model_data.pretty_print_with_source(&solution), ("n=4\n".to_string(), true));
     }
 
     #[test]
-    fn test_modulo_with_constraint() {
-        // Test modulo with variable divisor (the problematic case)
+    fn test_bool_parameter_in_integer_constant_expressions() {
         let source = r#"
-            var 1..100: dividend;
-            var 1..10: divisor;
-            var 0..9: remainder;
-            
-            constraint remainder == dividend mod divisor;
-            constraint dividend == 47;
-            constraint divisor == 10;
-            
+            bool: wide = true;
+            bool: tall = false;
+            int: n = 2 + bool2int(wide) + tall;
+            array[1..(if wide then 2 else 1 endif)] of var 1..n: x;
+            constraint forall(i in 1..n + wide)(true);
+            constraint x[1] = n /\ x[2] = 1 + bool2int(tall);
             solve satisfy;
         "#;
+        let mut model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert_eq!(model_data.int_var_arrays["x"].len(), 2);
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(model_data.format_default(&solution), "x = [3, 1];\n");
+
+        // bool2int over a variable is the variable itself
+        let source = "var bool: b; var 0..5: y; constraint y = 2 + bool2int(b); constraint y > 2; solve satisfy;";
+        let mut model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+        assert_eq!(model_data.format_default(&solution), "b = 1;\ny = 3;\n");
+    }
+
+    #[test]
+    fn test_count_relation_constraints() {
+        // c <op> (number of 2s in x), with x fixed to two 2s
+        let occurrences = |relation: &str, c: &str| {
+            let source = format!(
+                "array[1..4] of var 1..3: x; var 0..4: c;
+                 constraint x[1] = 2 /\\ x[2] = 1 /\\ x[3] = 2 /\\ x[4] = 3;
+                 constraint {}(x, 2, {});
+                 solve satisfy;",
+                relation, c
+            );
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let c = model_data.int_vars["c"];
+            let mut values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(c)).collect();
+            values.sort();
+            values
+        };
+        assert_eq!(occurrences("count_eq", "c"), vec![2]);
+        assert_eq!(occurrences("count_neq", "c"), vec![0, 1, 3, 4]);
+        assert_eq!(occurrences("count_lt", "c"), vec![0, 1]);
+        assert_eq!(occurrences("count_leq", "c"), vec![0, 1, 2]);
+        assert_eq!(occurrences("count_gt", "c"), vec![3, 4]);
+        assert_eq!(occurrences("count_geq", "c"), vec![2, 3, 4]);
+
+        // Constant thresholds leave c free when they hold
+        assert_eq!(occurrences("count_leq", "2").len(), 5);
+        assert!(occurrences("count_leq", "3").is_empty());
+        assert_eq!(occurrences("count_geq", "3").len(), 5);
+        assert!(occurrences("count_geq", "1").is_empty());
+        assert_eq!(occurrences("count_eq", "2").len(), 5);
+    }
+
+    #[test]
+    fn test_sum_equals_parameter_posts_constant() {
+        let declarations = "int: n = 4; array[1..3] of var 0..5: x; array[1..1] of var 0..9: y;";
+        let var_count = |constraint: &str| {
+            let source = format!("{} {} solve satisfy;", declarations, constraint);
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            model_data.model.get_vars().iter().count()
+        };
+        let baseline = var_count("");
+
+        // Linear over several variables: one lin_eq against the evaluated constant
+        let source = format!("{} constraint sum(x) = 2 * n + 1; solve satisfy;", declarations);
+        let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+        assert_eq!(model_data.model.get_vars().iter().count(), baseline);
+        assert!(matches!(
+            model_data.model.pending_constraint_asts.as_slice(),
+            [selen::runtime_api::ConstraintKind::LinearInt { constant: 9, .. }]
+        ));
+
+        // A single-variable sum keeps its result variable but no constant one,
+        // whichever side the parameter expression is on
+        assert_eq!(var_count("constraint sum(y) = n - 1;"), var_count("constraint sum(y) = 3;"));
+        assert_eq!(var_count("constraint n + 1 = sum(y);"), var_count("constraint sum(y) = 5;"));
+
+        let source = format!("{} constraint n + 1 >= sum(y) /\\ sum(y) > n; solve satisfy;", declarations);
+        let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+        let y = model_data.int_var_arrays["y"][0];
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(y), 5);
+    }
+
+    #[test]
+    fn test_float_rounding_functions() {
+        let source = "var 0.0..10.0: cost; constraint floor(cost) = 3; solve maximize cost;";
         let ast = parse(source).unwrap();
-        
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok(), "Failed to translate array with values");
-        
-        // Test that it solves correctly
-        let model_data = result.unwrap();
-        let sol = model_data.model.solve();
-        
-        if let Err(e) = sol {
-            eprintln!("FAILED TO SOLVE: {:?}", e);
-            eprintln!("This is the modulo with variable divisor issue!");
-            panic!("Model should solve but got: {:?}", e);
-        }
-        
-        let solution = sol.unwrap();
-        if let Some(dividend_var) = model_data.int_vars.get("dividend") {
-            let div_val = solution.get_int(*dividend_var);
-            assert_eq!(div_val, 47, "dividend should be 47");
+        let cost = Translator::translate_with_vars(&ast).unwrap().float_vars["cost"];
+        let solution = crate::optimize(&ast, crate::SolverConfig::default()).unwrap().unwrap();
+        let value = solution.get_float(cost);
+        assert!((3.9..4.0).contains(&value), "cost = {}", value);
+
+        // ceil and round bracket the float from the other side and the middle
+        for (constraint, range) in [("ceil(cost) = 3", 2.0..=3.0), ("round(cost) = 3", 2.5..=3.5)] {
+            let source = format!("var 0.0..10.0: cost; constraint {}; solve satisfy;", constraint);
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let cost = model_data.float_vars["cost"];
+            let value = model_data.model.solve().unwrap().get_float(cost);
+            assert!(range.contains(&value) && value != 2.0 && value != 3.5, "{}: cost = {}", constraint, value);
         }
-        
-        if let Some(divisor_var) = model_data.int_vars.get("divisor") {
-            let divisor_val = solution.get_int(*divisor_var);
-            assert_eq!(divisor_val, 10, "divisor should be 10");
+
+        // Constant floats fold at translation time
+        let source = "int: a = floor(2.7); int: b = ceil(-2.7); int: c = round(2.5); var 0..9: x;
+                      constraint x = a + b + c; solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let x = model_data.int_vars["x"];
+        assert_eq!(model_data.model.solve().unwrap().get_int(x), 3); // 2 + -2 + 3
+
+        // Halves round away from zero whether the operand is constant or not
+        for domain in ["-5.0..-1.0", "-5.0..5.0", "-2.5..-2.5"] {
+            let source = format!(
+                "var {}: f; var -9..9: r; constraint f = -2.5; constraint r = round(f); \
+                 int: c = round(-2.5); constraint r = c; solve satisfy;",
+                domain
+            );
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let r = model_data.int_vars["r"];
+            assert_eq!(model_data.model.solve().unwrap().get_int(r), -3, "f in {}", domain);
         }
-        
-        if let Some(remainder_var) = model_data.int_vars.get("remainder") {
-            let rem_val = solution.get_int(*remainder_var);
-            assert_eq!(rem_val, 7, "remainder should be 7 (47 mod 10 = 7)");
+        for (value, rounded) in [("-2.5", -3), ("2.5", 3), ("-2.4", -2), ("0.5", 1), ("-0.5", -1)] {
+            let source = format!(
+                "var -5.0..5.0: f; var -9..9: r; constraint f = {}; constraint r = round(f); solve satisfy;",
+                value
+            );
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let r = model_data.int_vars["r"];
+            let values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(r)).collect();
+            assert_eq!(values, vec![rounded], "round({})", value);
         }
     }
 
     #[test]
-    fn test_array_initialization_int() {
-        // Test integer parameter array initialization
+    fn test_int2float_times_float_rate() {
         let source = r#"
-            array[1..3] of int: limits = [5, 10, 15];
-            array[1..3] of var 1..10: x;
-            
-            constraint x[1] <= limits[1];
-            constraint x[2] <= limits[2];
-            constraint x[3] <= limits[3];
-            
+            float: rate = 2.5;
+            var 1..8: n;
+            var 0.0..100.0: total;
+            constraint total = int2float(n) * rate;
+            constraint total >= 9.0 /\ total <= 11.0;
             solve satisfy;
         "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let (n, total) = (model_data.int_vars["n"], model_data.float_vars["total"]);
+        let solution = model_data.model.solve().unwrap();
+        assert_eq!(solution.get_int(n), 4);
+        assert!((solution.get_float(total) - 10.0).abs() < 1e-6);
 
-        let ast = parse(source).unwrap();
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok(), "Failed to translate array initialization");
-        
-        let model_data = result.unwrap();
-        let solution = model_data.model.solve();
-        assert!(solution.is_ok(), "Failed to solve with parameter array");
-        
-        let sol = solution.unwrap();
-        if let Some(arr) = model_data.int_var_arrays.get("x") {
-            assert_eq!(arr.len(), 3, "Array should have 3 elements");
-            let x1 = sol.get_int(arr[0]);
-            let x2 = sol.get_int(arr[1]);
-            let x3 = sol.get_int(arr[2]);
-            
-            // Verify constraints were applied
-            assert!(x1 <= 5, "x[1] should be <= 5");
-            assert!(x2 <= 10, "x[2] should be <= 10");
-            assert!(x3 <= 15, "x[3] should be <= 15");
+        // Coerced constants and float variables mix the same way
+        let source = r#"
+            var 0.0..2.0: f;
+            var 0.0..100.0: total;
+            constraint f = 2.0;
+            constraint total = int2float(3) * f + 1.0;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let total = model_data.float_vars["total"];
+        assert!((model_data.model.solve().unwrap().get_float(total) - 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_comparison_type_mismatch_errors() {
+        let error_for = |source: &str| {
+            let ast = parse(source).unwrap();
+            let Err(err) = Translator::translate_with_vars(&ast) else {
+                panic!("expected a type error for {}", source);
+            };
+            assert_eq!(err.category(), crate::ErrorCategory::TypeMismatch, "{}", err);
+            err.to_string()
+        };
+
+        let message = error_for("var 1..5: i; constraint i = 3.5; solve satisfy;");
+        assert!(message.contains("expected int operand to compare with int variable 'i', found float literal 3.5"), "{}", message);
+
+        let message = error_for("array[1..2] of var 1..5: xs; constraint 2.5 < xs[1]; solve satisfy;");
+        assert!(message.contains("expected int operand to compare with int element of 'xs', found float literal 2.5"), "{}", message);
+
+        let message = error_for("var bool: b; constraint b != 3; solve satisfy;");
+        assert!(message.contains("expected bool operand to compare with bool variable 'b', found int literal 3"), "{}", message);
+
+        let message = error_for("var 1..5: i; constraint i = true; solve satisfy;");
+        assert!(message.contains("expected int operand to compare with int variable 'i', found bool literal true"), "{}", message);
+
+        let message = error_for("var 1..5: i; constraint i < 3.0; solve satisfy;");
+        assert!(message.contains("found float literal 3.0"), "{}", message);
+
+        // Matching types, and floats against int literals, still translate
+        for source in [
+            "var 1..5: i; constraint i = 3; solve satisfy;",
+            "var bool: b; constraint b = true; solve satisfy;",
+            "var 0.0..5.0: f; constraint f >= 1; solve satisfy;",
+        ] {
+            assert!(Translator::translate_with_vars(&parse(source).unwrap()).is_ok(), "{}", source);
         }
     }
 
     #[test]
-    fn test_array_initialization_float() {
-        // Test float parameter array initialization
+    fn test_par_keyword_translates_like_plain_parameter() {
+        let solve = |declarations: &str| {
+            let source = format!(
+                "{} var 1..n: x; array[1..2] of var 0..9: y; constraint x > n - 1 /\\ y[1] = a[2]; solve satisfy;",
+                declarations
+            );
+            let mut model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let solution = std::mem::take(&mut model_data.model).solve().unwrap();
+            model_data.pretty_print(&solution)
+        };
+        let explicit = solve("par int: n = 5; array[1..2] of par int: a = [3, 4];");
+        assert_eq!(explicit, solve("int: n = 5; array[1..2] of int: a = [3, 4];"));
+        assert!(explicit.starts_with("x = 5;\ny = [4, "), "{}", explicit);
+    }
+
+    #[test]
+    fn test_reversed_ranges_are_empty() {
+        // A reversed forall range posts nothing
+        let source = "int: n = 3; var 1..3: x; constraint forall(i in n..1)(x = i); solve satisfy;";
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert!(model_data.model.pending_constraint_asts.is_empty());
+        assert_eq!(model_data.model.enumerate().count(), 3);
+
+        // A reversed index set declares an empty array
         let source = r#"
-            array[1..2] of float: thresholds = [1.5, 2.5];
-            array[1..2] of var 0.0..5.0: values;
-            
-            constraint values[1] <= thresholds[1];
-            constraint values[2] <= thresholds[2];
-            
+            array[5..1] of var 1..3: a;
+            array[5..1] of int: p = [];
+            var 0..3: s;
+            constraint s = sum(a) + sum(i in 3..1)(i);
             solve satisfy;
         "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        assert!(model_data.int_var_arrays["a"].is_empty());
+        let s = model_data.int_vars["s"];
+        let values: Vec<i32> = model_data.model.enumerate().map(|solution| solution.get_int(s)).collect();
+        assert_eq!(values, vec![0]);
+    }
 
-        let ast = parse(source).unwrap();
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok(), "Failed to translate float array initialization");
-        
-        let model_data = result.unwrap();
-        let solution = model_data.model.solve();
-        assert!(solution.is_ok(), "Failed to solve with float parameter array");
-        
-        let sol = solution.unwrap();
-        if let Some(arr) = model_data.float_var_arrays.get("values") {
-            assert_eq!(arr.len(), 2, "Array should have 2 elements");
-            let v1 = sol.get_float(arr[0]);
-            let v2 = sol.get_float(arr[1]);
-            
-            // Verify constraints were applied
-            assert!(v1 <= 1.6, "values[1] should be <= 1.5 (with small tolerance)");
-            assert!(v2 <= 2.6, "values[2] should be <= 2.5 (with small tolerance)");
-        }
+    #[test]
+    fn test_enumerate_reified_disjunctions() {
+        // Each solution must satisfy the reified constraint exactly
+        let values = |constraints: &str| {
+            let source = format!("var 1..5: x; var 1..5: y; {} solve satisfy;", constraints);
+            let model_data = Translator::translate_with_vars(&parse(&source).unwrap()).unwrap();
+            let (x, y) = (model_data.int_vars["x"], model_data.int_vars["y"]);
+            let mut values: Vec<(i32, i32)> =
+                model_data.model.enumerate().map(|s| (s.get_int(x), s.get_int(y))).collect();
+            values.sort();
+            values
+        };
+        let xs = |constraints: &str| {
+            let mut xs: Vec<i32> = values(constraints).into_iter().map(|(x, _)| x).collect();
+            xs.dedup();
+            xs
+        };
+
+        assert_eq!(xs("constraint y = 1; constraint (x = 1) \\/ (x = 2);"), vec![1, 2]);
+        assert_eq!(xs("constraint y = 1; constraint not ((x = 1) \\/ (x = 2));"), vec![3, 4, 5]);
+        assert_eq!(xs("constraint y = 1; constraint (x < 2) \\/ (x > 4) \\/ (x = 3);"), vec![1, 3, 5]);
+        assert_eq!(xs("constraint y = 1; constraint ((x = 1) \\/ (x = 4)) /\\ (x >= 2);"), vec![4]);
+
+        // Separate constraint items conjoin
+        assert_eq!(
+            values("constraint (x = 1) \\/ (y = 1); constraint (x = 2) \\/ (y = 2); constraint x < y;"),
+            vec![(1, 2)]
+        );
+        assert_eq!(
+            values("constraint (x = 1) -> (y = 5); constraint (x >= 2) <-> (y = 1); constraint x <= 2;"),
+            vec![(1, 5), (2, 1)]
+        );
     }
 
     #[test]
-    fn test_array_initialization_bool() {
-        // Test bool parameter array initialization
+    fn test_predicate_calls_are_inlined() {
         let source = r#"
-            array[1..2] of bool: flags = [true, false];
-            array[1..2] of var bool: enabled;
-            
+            predicate differ(var int: a, var int: b) = a != b;
+            predicate all_below(array[int] of var int: xs, int: limit) = max(xs) < limit;
+            array[1..3] of var 1..3: q;
+            var bool: same;
+            constraint all_below(q, 3);
+            constraint differ(q[1], q[2]) /\ differ(q[2], q[3]);
+            constraint same <-> not differ(q[1], q[3]);
             solve satisfy;
         "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let q = model_data.int_var_arrays["q"].clone();
+        let same = model_data.bool_vars["same"];
+        let mut solutions: Vec<(Vec<i32>, i32)> = model_data
+            .model
+            .enumerate()
+            .map(|s| (q.iter().map(|&v| s.get_int(v)).collect(), s.get_int(same)))
+            .collect();
+        solutions.sort();
+        assert_eq!(solutions, vec![(vec![1, 2, 1], 1), (vec![2, 1, 2], 1)]);
+
+        let errors = [
+            ("predicate p(var int: a) = a > 0; var 1..3: x; constraint p(x, x);", "2 arguments"),
+            ("predicate p(var int: a) = p(a); var 1..3: x; constraint p(x);", "Recursive predicate 'p'"),
+        ];
+        for (source, expected) in errors {
+            let Err(err) = Translator::translate_with_vars(&parse(source).unwrap()) else {
+                panic!("expected an error for {}", source);
+            };
+            assert!(err.to_string().contains(expected), "{}", err);
+        }
+    }
 
+    #[test]
+    fn test_user_function_in_objective() {
+        let source = r#"
+            function var int: double(var int: v) = 2 * v;
+            function var int: cost(var int: a, var int: b) = double(a) + b;
+            var 1..5: x;
+            var 1..5: y;
+            constraint x + y >= 6;
+            solve minimize cost(x, y) + 1;
+        "#;
         let ast = parse(source).unwrap();
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok(), "Failed to translate bool array initialization");
-        
-        let model_data = result.unwrap();
-        let solution = model_data.model.solve();
-        assert!(solution.is_ok(), "Failed to solve with bool parameter array");
+        let model_data = Translator::translate_with_vars(&ast).unwrap();
+        let (x, y) = (model_data.int_vars["x"], model_data.int_vars["y"]);
+        let objective = model_data.objective_var.unwrap();
+        let solution = crate::optimize(&ast, crate::SolverConfig::default()).unwrap().unwrap();
+        // 2 * 1 + 5 + 1 is the cheapest way to reach x + y >= 6
+        assert_eq!((solution.get_int(x), solution.get_int(y), solution.get_int(objective)), (1, 5, 8));
+
+        let source = r#"
+            function var int: f(var int: a) = g(a) + 1;
+            function var int: g(var int: a) = f(a - 1);
+            var 1..3: x;
+            solve minimize f(x);
+        "#;
+        let Err(err) = Translator::translate_with_vars(&parse(source).unwrap()) else {
+            panic!("expected recursive functions to be rejected");
+        };
+        assert!(err.to_string().contains("Recursive function 'f' (f -> g -> f)"), "{}", err);
     }
 
     #[test]
-    fn test_array_initialization_in_arithmetic() {
-        // Test using parameter array elements in arithmetic expressions
+    fn test_predicate_arguments_are_not_captured_by_body_binders() {
+        let values = |source: &str| {
+            let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+            let var = model_data.int_vars["y"];
+            let mut values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(var)).collect();
+            values.sort();
+            values
+        };
+        // The caller's `y` is not the generator's `y`
         let source = r#"
-            array[1..2] of int: costs = [10, 20];
-            array[1..2] of var 0..1: select;
-            
-            constraint costs[1] * select[1] + costs[2] * select[2] <= 25;
-            
-            solve maximize select[1] + select[2];
+            predicate avoid(var int: a) = forall(y in 1..2)(a != y);
+            var 1..4: y;
+            constraint avoid(y);
+            solve satisfy;
+        "#;
+        assert_eq!(values(source), vec![3, 4]);
+        let source = r#"
+            predicate within(var int: x, array[int] of int: zs, int: d) = forall(y in zs)(abs(x - y) <= d);
+            array[1..2] of int: ys = [1, 4];
+            var 1..4: y;
+            constraint within(y, ys, 2);
+            solve satisfy;
         "#;
+        assert_eq!(values(source), vec![2, 3]);
+    }
 
-        let ast = parse(source).unwrap();
-        let result = Translator::translate_with_vars(&ast);
-        assert!(result.is_ok(), "Failed to translate array in arithmetic");
-        
-        let model_data = result.unwrap();
-        let solution = model_data.model.solve();
-        assert!(solution.is_ok(), "Failed to solve with array in arithmetic");
-        
-        let sol = solution.unwrap();
-        if let Some(arr) = model_data.int_var_arrays.get("select") {
-            assert_eq!(arr.len(), 2, "Array should have 2 elements");
-            let s1 = sol.get_int(arr[0]);
-            let s2 = sol.get_int(arr[1]);
-            
-            // Verify constraint: 10*s1 + 20*s2 <= 25
-            let total_cost = 10 * s1 + 20 * s2;
-            assert!(total_cost <= 25, "Cost constraint should be satisfied");
-        }
+    #[test]
+    fn test_function_arguments_are_not_captured_by_let_declarations() {
+        let source = r#"
+            function var int: f(var int: a) = let { var int: t = a + 1 } in t * 2;
+            var 1..4: t;
+            constraint f(t) = 6;
+            solve satisfy;
+        "#;
+        let model_data = Translator::translate_with_vars(&parse(source).unwrap()).unwrap();
+        let t = model_data.int_vars["t"];
+        let values: Vec<i32> = model_data.model.enumerate().map(|s| s.get_int(t)).collect();
+        assert_eq!(values, vec![2]);
     }
 }
-
@@ -16,5 +16,8 @@ mod test_output_formatting;
 #[path = "../tests_all/test_array2d_array3d.rs"]
 mod test_array2d_array3d;
 
+#[path = "../tests_all/test_cli.rs"]
+mod test_cli;
+
 
 
@@ -0,0 +1,98 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Run the zelen binary with `args`, feeding `stdin` to it, and return its stdout
+fn run_zelen(args: &[&str], stdin: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_zelen"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start zelen");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .expect("Failed to write stdin");
+    let output = child.wait_with_output().expect("Failed to wait for zelen");
+    assert!(
+        output.status.success(),
+        "zelen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Run the zelen binary with `args`, expecting it to fail, and return its stderr
+fn run_zelen_failing(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_zelen"))
+        .args(args)
+        .output()
+        .expect("Failed to run zelen");
+    assert!(
+        !output.status.success(),
+        "zelen succeeded: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn test_cli_reads_model_from_stdin() {
+    let stdout = run_zelen(&["-"], "var 1..3: x; constraint x > 2; solve satisfy;");
+    assert_eq!(stdout, "x = 3;\n----------\n");
+}
+
+#[test]
+fn test_cli_stdin_model_with_data_file() {
+    let data = std::env::temp_dir().join(format!("zelen_cli_stdin_{}.dzn", std::process::id()));
+    std::fs::write(&data, "int: n = 4;\n").unwrap();
+    let stdout = run_zelen(
+        &["-", data.to_str().unwrap()],
+        "var 1..9: x; constraint x >= n; solve minimize x;",
+    );
+    let _ = std::fs::remove_file(&data);
+    assert_eq!(stdout, "x = 4;\n----------\n");
+}
+
+#[test]
+fn test_cli_parse_errors_name_the_file() {
+    let dir = std::env::temp_dir().join(format!("zelen_cli_errors_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let model = dir.join("bad.mzn");
+    let data = dir.join("bad.dzn");
+    std::fs::write(&model, "var 1..3: x;\nconstraint x > ;\n").unwrap();
+    std::fs::write(&data, "int: n = 3;\nint: m = ;\n").unwrap();
+    let model_error = run_zelen_failing(&[model.to_str().unwrap()]);
+    std::fs::write(&model, "var 1..n: x;\nsolve satisfy;\n").unwrap();
+    let data_error = run_zelen_failing(&[model.to_str().unwrap(), data.to_str().unwrap()]);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let expected = format!("Error in {} at line 2, column 16", model.display());
+    assert!(model_error.contains(&expected), "{}", model_error);
+    let expected = format!("Error in {} at line 2, column 10", data.display());
+    assert!(data_error.contains(&expected), "{}", data_error);
+}
+
+#[test]
+fn test_cli_resolves_includes_next_to_the_model() {
+    let dir = std::env::temp_dir().join(format!("zelen_cli_include_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("helper.mzn"), "int: n = 3;\n").unwrap();
+    let model = dir.join("model.mzn");
+    std::fs::write(
+        &model,
+        "include \"globals.mzn\";\ninclude \"helper.mzn\";\nvar 1..9: x;\nconstraint x >= n;\nsolve minimize x;\n",
+    )
+    .unwrap();
+    let stdout = run_zelen(&[model.to_str().unwrap()], "");
+    let missing = dir.join("missing.mzn");
+    std::fs::write(&missing, "include \"helpr.mzn\";\nsolve satisfy;\n").unwrap();
+    let stderr = run_zelen_failing(&[missing.to_str().unwrap()]);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(stdout, "x = 3;\n----------\n");
+    assert!(stderr.contains("helpr.mzn' included from"), "{}", stderr);
+}